@@ -0,0 +1,199 @@
+/**
+ * Benchmark mesh and attribute transfer between the HDK and Rust on in-memory details.
+ *
+ * Builds grids of quads and blocks of tetrahedra with point, primitive and vertex attributes,
+ * then times extracting them with the `build_*` functions and adding them to an empty detail
+ * with the `add_*` functions. No files are involved, so the reported throughput covers only the
+ * transfer of positions, topology and attributes across the bridge.
+ *
+ * Usage:
+ *
+ *     transfer [--repeat N] [NUM_PRIMS ...]
+ *
+ * Each size is benchmarked on both mesh types, reporting the best of N runs.
+ */
+
+#include <algorithm>
+#include <chrono>
+#include <cmath>
+#include <cstdio>
+#include <cstdlib>
+#include <cstring>
+#include <exception>
+#include <functional>
+#include <iostream>
+#include <string>
+#include <vector>
+
+#include <GU/GU_Detail.h>
+#include <GEO/GEO_PolyCounts.h>
+#include <GEO/GEO_PrimPoly.h>
+#include <GEO/GEO_PrimTetrahedron.h>
+#include <UT/UT_Vector3.h>
+
+#include <hdkrs/prelude.h>
+
+namespace {
+
+// Add a float and an integer attribute of each class to the given detail.
+void add_attributes(GU_Detail &detail)
+{
+    GA_RWHandleV3 normal_h(detail.addFloatTuple(GA_ATTRIB_POINT, "N", 3));
+    GA_RWHandleI id_h(detail.addIntTuple(GA_ATTRIB_POINT, "id", 1));
+    for ( GA_Offset pt_off : detail.getPointRange() ) {
+        UT_Vector3 n = detail.getPos3(pt_off);
+        n.normalize();
+        normal_h.set(pt_off, n);
+        id_h.set(pt_off, int(detail.pointIndex(pt_off)));
+    }
+
+    GA_RWHandleF density_h(detail.addFloatTuple(GA_ATTRIB_PRIMITIVE, "density", 1));
+    GA_RWHandleI piece_h(detail.addIntTuple(GA_ATTRIB_PRIMITIVE, "piece", 1));
+    for ( GA_Offset prim_off : detail.getPrimitiveRange() ) {
+        GA_Index idx = detail.primitiveIndex(prim_off);
+        density_h.set(prim_off, 0.5f*idx);
+        piece_h.set(prim_off, int(idx % 16));
+    }
+
+    GA_RWHandleV3 uv_h(detail.addFloatTuple(GA_ATTRIB_VERTEX, "uv", 3));
+    GA_RWHandleI corner_h(detail.addIntTuple(GA_ATTRIB_VERTEX, "corner", 1));
+    for ( GA_Offset vtx_off : detail.getVertexRange() ) {
+        GA_Index idx = detail.vertexIndex(vtx_off);
+        uv_h.set(vtx_off, UT_Vector3(float(idx), 0.0f, 0.0f));
+        corner_h.set(vtx_off, int(idx % 4));
+    }
+}
+
+// Append a lattice of `(side + 1)^3` points spanning the unit cube, or a square if `flat`.
+GA_Offset add_lattice(GU_Detail &detail, int side, bool flat)
+{
+    int layers = flat ? 1 : side + 1;
+    GA_Offset startptoff = detail.appendPointBlock(GA_Size(side + 1)*(side + 1)*layers);
+    GA_Offset pt_off = startptoff;
+    for (int z = 0; z < layers; ++z) {
+        for (int y = 0; y <= side; ++y) {
+            for (int x = 0; x <= side; ++x, ++pt_off) {
+                detail.setPos3(pt_off, UT_Vector3(x, y, z) / side);
+            }
+        }
+    }
+    return startptoff;
+}
+
+// Build a grid of roughly `num_prims` quads.
+void make_polygons(GU_Detail &detail, exint num_prims)
+{
+    int side = std::max(1, int(std::sqrt(double(num_prims))));
+    GA_Offset startptoff = add_lattice(detail, side, true);
+    std::vector<int> indices;
+    indices.reserve(4*side*side);
+    for (int y = 0; y < side; ++y) {
+        for (int x = 0; x < side; ++x) {
+            int p = y*(side + 1) + x;
+            for (int i : { p, p + 1, p + side + 2, p + side + 1 }) {
+                indices.push_back(i);
+            }
+        }
+    }
+    GEO_PolyCounts counts;
+    counts.append(4, side*side);
+    GEO_PrimPoly::buildBlock(&detail, startptoff, detail.getNumPointOffsets(), counts, indices.data());
+    add_attributes(detail);
+}
+
+// Build a block of roughly `num_prims` tetrahedra, splitting each lattice cube into six.
+void make_tetrahedra(GU_Detail &detail, exint num_prims)
+{
+    int side = std::max(1, int(std::cbrt(num_prims / 6.0)));
+    GA_Offset startptoff = add_lattice(detail, side, false);
+    int n = side + 1;
+    std::vector<int> indices;
+    indices.reserve(24*side*side*side);
+    for (int z = 0; z < side; ++z) {
+        for (int y = 0; y < side; ++y) {
+            for (int x = 0; x < side; ++x) {
+                auto corner = [&](int i) {
+                    return ((z + (i >> 2 & 1))*n + y + (i >> 1 & 1))*n + x + (i & 1);
+                };
+                // Tetrahedra around the main diagonal of the cube from corner 0 to corner 7.
+                const int paths[6][2] = { {1, 3}, {3, 2}, {2, 6}, {6, 4}, {4, 5}, {5, 1} };
+                for (auto &path : paths) {
+                    for (int i : { 0, path[0], path[1], 7 }) {
+                        indices.push_back(corner(i));
+                    }
+                }
+            }
+        }
+    }
+    GEO_PrimTetrahedron::buildBlock(
+            &detail, startptoff, detail.getNumPointOffsets(), indices.size()/4, indices.data());
+    add_attributes(detail);
+}
+
+// Best time in seconds of `repeat` runs of the given function.
+double best_time(int repeat, const std::function<void()> &f)
+{
+    double best = INFINITY;
+    for (int i = 0; i < repeat; ++i) {
+        auto start = std::chrono::steady_clock::now();
+        f();
+        std::chrono::duration<double> elapsed = std::chrono::steady_clock::now() - start;
+        best = std::min(best, elapsed.count());
+    }
+    return best;
+}
+
+// Time extracting the given detail with `build` and adding the result to an empty detail
+// with `add`, printing the throughput of both directions.
+template<typename Build, typename Add>
+void bench(const char *kind, const GU_Detail &detail, int repeat, Build build, Add add)
+{
+    auto mesh = build(detail);
+    double build_time = best_time(repeat, [&]() { mesh = build(detail); });
+    double add_time = best_time(repeat, [&]() {
+        GU_Detail result;
+        add(result, *mesh);
+    });
+    exint n = detail.getNumPrimitives();
+    std::printf("%5s %10lld %14.2f %14.2f\n",
+            kind, (long long)n, n / build_time * 1e-6, n / add_time * 1e-6);
+}
+
+} // namespace
+
+int main(int argc, char *argv[])
+{
+    int repeat = 3;
+    std::vector<exint> sizes;
+    for (int i = 1; i < argc; ++i) {
+        if (!std::strcmp(argv[i], "--repeat") && i + 1 < argc) {
+            repeat = std::max(1, std::atoi(argv[++i]));
+        } else {
+            sizes.push_back(std::atoll(argv[i]));
+        }
+    }
+    if (sizes.empty()) {
+        sizes = { 1000000, 4000000 };
+    }
+
+    try {
+        std::printf("%5s %10s %14s %14s\n", "mesh", "prims", "build Mprim/s", "add Mprim/s");
+        for (exint num_prims : sizes) {
+            GU_Detail polygons;
+            make_polygons(polygons, num_prims);
+            bench("poly", polygons, repeat,
+                    [](const GU_Detail &d) { return hdkrs::build_polymesh(d); },
+                    [](GU_Detail &d, const hdkrs::PolyMesh &m) { hdkrs::add_polymesh(d, m); });
+
+            GU_Detail tetrahedra;
+            make_tetrahedra(tetrahedra, num_prims);
+            bench("tet", tetrahedra, repeat,
+                    [](const GU_Detail &d) { return hdkrs::build_tetmesh(d); },
+                    [](GU_Detail &d, const hdkrs::TetMesh &m) { hdkrs::add_tetmesh(d, m); });
+        }
+    } catch (const std::exception &e) {
+        std::cerr << e.what() << std::endl;
+        return 1;
+    }
+    return 0;
+}
@@ -0,0 +1,111 @@
+"""
+Benchmark saving and loading meshes end to end through the file translators.
+
+Builds grids of polygons and tetrahedra with point, primitive and vertex attributes, then times
+saving and loading them through each translator. The reported throughput includes reading and
+writing files and converting formats on top of the `build_*` and `add_*` transfer, which is
+measured on its own by `transfer.cpp`.
+
+Run with hython after installing the plugins, e.g.
+
+    hython hdkrs/bench/transfer.py --prims 1000000 4000000 --formats vtu obj
+
+Compare throughput across builds by running the script against each installed build.
+"""
+
+import argparse
+import math
+import os
+import tempfile
+import time
+
+import hou
+
+
+def make_polygons(num_prims):
+    """Build a grid of roughly `num_prims` quads with float and integer attributes."""
+    side = max(2, int(math.sqrt(num_prims)) + 1)
+    grid = hou.sopNodeTypeCategory().nodeVerb("grid")
+    grid.setParms({"rows": side, "cols": side, "size": hou.Vector2(10, 10)})
+    geo = hou.Geometry()
+    grid.execute(geo, [])
+    return with_attributes(geo)
+
+
+def make_tetrahedra(num_prims):
+    """Build a block of roughly `num_prims` tetrahedra with float and integer attributes."""
+    box = hou.sopNodeTypeCategory().nodeVerb("box")
+    tetconform = hou.sopNodeTypeCategory().nodeVerb("tetconform")
+    divs = max(2, int(round((num_prims / 5.0) ** (1.0 / 3.0))))
+    box.setParms({"type": 1, "divrate": hou.Vector3(divs, divs, divs)})
+    surface = hou.Geometry()
+    box.execute(surface, [])
+    geo = hou.Geometry()
+    tetconform.execute(geo, [surface])
+    return with_attributes(geo)
+
+
+def with_attributes(geo):
+    """Copy of the given geometry with a float and an integer attribute of each class."""
+    wrangle = hou.sopNodeTypeCategory().nodeVerb("attribwrangle")
+    # Values of the wrangle "Run Over" menu.
+    points, prims, vertices = 2, 1, 3
+    snippets = [
+        (points, "v@N = normalize(@P); i@id = @ptnum;"),
+        (prims, "f@density = @primnum * 0.5; i@piece = @primnum % 16;"),
+        (vertices, "v@uv = set(@vtxnum, 0, 0); i@corner = @vtxnum % 4;"),
+    ]
+    for run_over, snippet in snippets:
+        wrangle.setParms({"class": run_over, "snippet": snippet})
+        result = hou.Geometry()
+        wrangle.execute(result, [geo])
+        geo = result
+    return geo
+
+
+def timed(f):
+    start = time.perf_counter()
+    f()
+    return time.perf_counter() - start
+
+
+def bench(geo, path, repeat):
+    """Time saving and loading the given geometry, returning the best of `repeat` runs."""
+    save = min(timed(lambda: geo.saveToFile(path)) for _ in range(repeat))
+    loaded = hou.Geometry()
+    load = min(timed(lambda: loaded.loadFromFile(path)) for _ in range(repeat))
+    if loaded.intrinsicValue("primitivecount") == 0:
+        raise RuntimeError("Failed to load {}".format(path))
+    return save, load
+
+
+def main():
+    parser = argparse.ArgumentParser(description=__doc__.split("\n\n")[0])
+    parser.add_argument("--prims", type=int, nargs="+", default=[1000000, 4000000])
+    parser.add_argument("--formats", nargs="+", default=["vtu", "vtk", "obj", "msh"])
+    parser.add_argument("--repeat", type=int, default=3)
+    args = parser.parse_args()
+
+    # Formats storing tetrahedra, which are benchmarked on tetrahedral meshes as well.
+    tet_formats = {"vtu", "vtk", "msh"}
+
+    print("{:>6} {:>5} {:>10} {:>12} {:>12}".format(
+        "format", "mesh", "prims", "save Mprim/s", "load Mprim/s"))
+    with tempfile.TemporaryDirectory() as tmp:
+        for num_prims in args.prims:
+            meshes = [("poly", make_polygons(num_prims))]
+            if tet_formats.intersection(args.formats):
+                meshes.append(("tet", make_tetrahedra(num_prims)))
+            for kind, geo in meshes:
+                n = geo.intrinsicValue("primitivecount")
+                for fmt in args.formats:
+                    if kind == "tet" and fmt not in tet_formats:
+                        continue
+                    path = os.path.join(tmp, "bench.{}".format(fmt))
+                    save, load = bench(geo, path, args.repeat)
+                    print("{:>6} {:>5} {:>10} {:>12.2f} {:>12.2f}".format(
+                        fmt, kind, n, n / save * 1e-6, n / load * 1e-6))
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,63 @@
+#pragma once
+
+#include <cstring>
+#include <fstream>
+#include <sstream>
+#include <string>
+
+#include <GA/GA_Detail.h>
+#include <hdkrs/src/lib.rs.h>
+
+namespace hdkrs {
+
+// File name suffixes of supported compression formats.
+constexpr const char *COMPRESSION_SUFFIXES[] = { ".gz", ".zst" };
+
+inline bool ends_with(const std::string &name, const char *suffix) {
+    std::size_t n = std::strlen(suffix);
+    return name.size() >= n && name.compare(name.size() - n, n, suffix) == 0;
+}
+
+// Check if the given file name ends with a compression suffix.
+inline bool is_compressed_path(const char *name) {
+    std::string sname(name);
+    for (const char *suffix : COMPRESSION_SUFFIXES) {
+        if (ends_with(sname, suffix))
+            return true;
+    }
+    return false;
+}
+
+// Check if the given file name has the given extension, optionally followed by a compression
+// suffix as in `mesh.vtu.gz`.
+inline bool has_extension(const char *name, const char *ext) {
+    std::string sname(name);
+    for (const char *suffix : COMPRESSION_SUFFIXES) {
+        if (ends_with(sname, suffix)) {
+            sname.resize(sname.size() - std::strlen(suffix));
+            break;
+        }
+    }
+    return ends_with(sname, ext);
+}
+
+// Save a file compressed according to its suffix, using `save` to write the uncompressed data.
+template<typename F>
+GA_Detail::IOStatus save_compressed(const char *fname, F &&save) {
+    std::ostringstream buf;
+    GA_Detail::IOStatus status = save(buf);
+    if (!status.success())
+        return status;
+    std::string data = buf.str();
+    try {
+        rust::Vec<uint8_t> compressed = compress_for_path(
+                rust::Slice<const uint8_t>(reinterpret_cast<const uint8_t *>(data.data()), data.size()),
+                rust::Str(fname));
+        std::ofstream os(fname, std::ios::binary);
+        os.write(reinterpret_cast<const char *>(compressed.data()), compressed.size());
+        return GA_Detail::IOStatus(os.good());
+    } catch (...) {}
+    return GA_Detail::IOStatus(false);
+}
+
+} // namespace hdkrs
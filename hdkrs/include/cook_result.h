@@ -0,0 +1,22 @@
+#pragma once
+
+#include <string>
+
+#include <SOP/SOP_Node.h>
+#include <UT/UT_ErrorManager.h>
+#include <hdkrs/src/lib.rs.h>
+
+namespace hdkrs {
+
+// Report warnings and errors in the given cook result to the node reading or writing a file.
+// Returns false if the result is an error.
+inline bool report_cook_result(const CookResult &result) {
+    std::string message(result.message);
+    if (result.tag == CookResultTag::WARNING)
+        UTaddWarning("SOP", SOP_MESSAGE, message.c_str());
+    else if (result.tag == CookResultTag::ERROR)
+        UTaddError("SOP", SOP_MESSAGE, message.c_str());
+    return result.tag != CookResultTag::ERROR;
+}
+
+} // namespace hdkrs
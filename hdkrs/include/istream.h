@@ -0,0 +1,18 @@
+#pragma once
+
+#include <memory>
+#include <UT/UT_IStream.h>
+#include "rust/cxx.h"
+#include "interrupt.h"
+
+namespace hdkrs {
+
+// Reads a Houdini input stream incrementally, checking for user interrupts between reads.
+struct IStreamReader {
+    UT_IStream &stream;
+    std::unique_ptr<InterruptChecker> interrupt;
+    IStreamReader(UT_IStream &stream, const char * status_message);
+    std::size_t read(rust::Slice<uint8_t> buf);
+};
+
+} // namespace hdkrs
@@ -13,7 +13,28 @@ namespace hdkrs {
     struct UnstructuredMesh;
     struct TetMesh;
     struct PolyMesh;
+    struct PolylineMesh;
     struct PointCloud;
+    struct PolyMeshF32;
+    struct PolylineMeshF32;
+    struct TetMeshF32;
+    struct PointCloudF32;
+    struct UnstructuredMeshF32;
+    struct GridMesh;
+    struct AddOptions;
+    struct MeshCollection;
+    struct ExtractOptions;
+
+    // Reserved attributes storing the source index of each extracted point and primitive.
+    // Attributes with the reserved prefix are not transferred back onto details.
+    constexpr const char *RESERVED_ATTRIB_PREFIX = "__hdkrs_";
+    constexpr const char *POINT_INDEX_ATTRIB = "__hdkrs_point_index";
+    constexpr const char *PRIM_INDEX_ATTRIB = "__hdkrs_prim_index";
+    constexpr const char *VERTEX_INDEX_ATTRIB = "__hdkrs_vertex_index";
+
+    // Primitive attribute storing the index of the polygon each triangle was split from when
+    // polygons are triangulated during extraction.
+    constexpr const char *SOURCE_POLYGON_ATTRIB = "sourceprim";
 
     /**
     * Add the given meshes into the given detail
@@ -21,13 +42,43 @@ namespace hdkrs {
 
     void add_unstructured_mesh(GU_Detail& detail, const UnstructuredMesh& mesh);
     void add_polymesh(GU_Detail& detail, const PolyMesh& polymesh);
+    void add_polymesh_with_options(GU_Detail& detail, const PolyMesh& polymesh, const AddOptions& options);
+    void add_packed_by_name(GU_Detail& detail, const Mesh& mesh, const AddOptions& options);
+    void add_polylines(GU_Detail& detail, const PolylineMesh& polylines);
     void add_tetmesh(GU_Detail& detail, const TetMesh& tetmesh);
     void add_pointcloud(GU_Detail& detail, const PointCloud& ptcloud);
+    void add_polymesh_f32(GU_Detail& detail, const PolyMeshF32& polymesh);
+    void add_polymesh_f32_with_options(GU_Detail& detail, const PolyMeshF32& polymesh, const AddOptions& options);
+    void add_polylines_f32(GU_Detail& detail, const PolylineMeshF32& polylines);
+    void add_tetmesh_f32(GU_Detail& detail, const TetMeshF32& tetmesh);
+    void add_pointcloud_f32(GU_Detail& detail, const PointCloudF32& ptcloud);
+    void add_unstructured_mesh_f32(GU_Detail& detail, const UnstructuredMeshF32& mesh);
     void update_points(GU_Detail& detail, const PointCloud& ptcloud);
+    void scatter_to_source(GU_Detail& detail, const Mesh& mesh);
+    void add_grid(GU_Detail& detail, const GridMesh& grid);
+    void add_collection(GU_Detail& detail, const MeshCollection& collection);
 
     rust::box<UnstructuredMesh> build_unstructured_mesh(const GU_Detail& detail);
+    rust::box<UnstructuredMesh> build_unstructured_mesh_with_options(const GU_Detail& detail, const ExtractOptions& options);
     rust::box<TetMesh> build_tetmesh(const GU_Detail& detail);
+    rust::box<TetMesh> build_tetmesh_with_options(const GU_Detail& detail, const ExtractOptions& options);
     rust::box<PolyMesh> build_polymesh(const GU_Detail& detail);
+    rust::box<PolyMesh> build_polymesh_with_options(const GU_Detail& detail, const ExtractOptions& options);
+    rust::box<PolylineMesh> build_polylines(const GU_Detail& detail);
+    rust::box<PolylineMesh> build_polylines_with_options(const GU_Detail& detail, const ExtractOptions& options);
     rust::box<PointCloud> build_pointcloud(const GU_Detail& detail);
+    rust::box<PolyMeshF32> build_polymesh_f32(const GU_Detail& detail);
+    rust::box<PolyMeshF32> build_polymesh_f32_with_options(const GU_Detail& detail, const ExtractOptions& options);
+    rust::box<TetMeshF32> build_tetmesh_f32(const GU_Detail& detail);
+    rust::box<TetMeshF32> build_tetmesh_f32_with_options(const GU_Detail& detail, const ExtractOptions& options);
+    rust::box<PolylineMeshF32> build_polylines_f32(const GU_Detail& detail);
+    rust::box<PolylineMeshF32> build_polylines_f32_with_options(const GU_Detail& detail, const ExtractOptions& options);
+    rust::box<PointCloudF32> build_pointcloud_f32(const GU_Detail& detail);
+    rust::box<UnstructuredMeshF32> build_unstructured_mesh_f32(const GU_Detail& detail);
+    rust::box<UnstructuredMeshF32> build_unstructured_mesh_f32_with_options(const GU_Detail& detail, const ExtractOptions& options);
+    bool has_f64_positions(const GU_Detail& detail);
+    void promote_p64(GU_Detail& detail);
+    rust::box<GridMesh> build_grid(const GU_Detail& detail);
+    rust::box<MeshCollection> build_all(const GU_Detail& detail);
 
 } // namespace hdkrs
\ No newline at end of file
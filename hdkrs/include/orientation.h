@@ -0,0 +1,33 @@
+#pragma once
+
+#include <GU/GU_Detail.h>
+#include <GEO/GEO_PrimPoly.h>
+#include <hdkrs/src/lib.rs.h>
+
+namespace hdkrs {
+
+// Reverse closed polygons, including the polygons of polygon soups, and invert tetrahedra in the
+// given detail as requested. Vertices keep their attribute values, so vertex attributes are
+// reordered along with them.
+inline void reverse_prims(GU_Detail &detail, bool polygons, bool tets) {
+    if (!polygons && !tets)
+        return;
+    GEO_Primitive *prim;
+    GA_FOR_ALL_PRIMITIVES(&detail, prim) {
+        int type = prim->getTypeId().get();
+        if (polygons && type == GA_PRIMPOLY && static_cast<GEO_PrimPoly *>(prim)->isClosed())
+            prim->reverse();
+        else if (polygons && type == GA_PRIMPOLYSOUP)
+            prim->reverse();
+        else if (tets && type == GA_PRIMTETRAHEDRON)
+            prim->reverse();
+    }
+}
+
+// Convert the vertex order of polygons and tetrahedra in the given detail between the given
+// convention and Houdini. The conversion is its own inverse, so it applies to import and export.
+inline void reorder_detail(GU_Detail &detail, const VertexOrder &order) {
+    reverse_prims(detail, order.reverses_polygons(), order.inverts_tets());
+}
+
+} // namespace hdkrs
@@ -2,6 +2,11 @@
 #include <rust/cxx.h>
 #include "mesh.h"
 #include "interrupt.h"
+#include "istream.h"
+#include "compression.h"
+#include "cook_result.h"
+#include "orientation.h"
+#include "transform.h"
 
 namespace hdkrs {
     /// Utility function for casting a Box of one type to a Box of another.
@@ -0,0 +1,113 @@
+#pragma once
+
+#include <GU/GU_Detail.h>
+#include <GA/GA_Handle.h>
+#include <GA/GA_Iterator.h>
+#include <GEO/GEO_PrimVolume.h>
+#include <hdkrs/src/lib.rs.h>
+#include "orientation.h"
+
+namespace hdkrs {
+
+// Attributes converted along with positions. These match the names in the Rust `transform` module.
+constexpr const char *VECTOR_ATTRIBS[] = { "v" };
+constexpr const char *NORMAL_ATTRIBS[] = { "N" };
+constexpr const char *QUATERNION_ATTRIBS[] = { "orient" };
+
+// Multiply the given vector by a row-major 3x3 matrix followed by a scale.
+inline UT_Vector3D transform_vector(const rust::Vec<double> &m, const UT_Vector3D &v, double scale) {
+    return UT_Vector3D(
+        scale * (m[0] * v.x() + m[1] * v.y() + m[2] * v.z()),
+        scale * (m[3] * v.x() + m[4] * v.y() + m[5] * v.z()),
+        scale * (m[6] * v.x() + m[7] * v.y() + m[8] * v.z()));
+}
+
+inline void transform_vector_attrib(GA_Attribute *attrib, const rust::Vec<double> &m, double scale) {
+    GA_RWHandleV3D h(attrib);
+    if (h.isInvalid())
+        return;
+    for (GA_Iterator it(GA_Range(attrib->getIndexMap())); !it.atEnd(); ++it)
+        h.set(*it, transform_vector(m, h.get(*it), scale));
+    h.bumpDataId();
+}
+
+// Quaternions are stored as (x, y, z, w). The rotation axis is a pseudovector, so it is negated
+// when `m` is a reflection.
+inline void transform_quaternion_attrib(GA_Attribute *attrib, const rust::Vec<double> &m) {
+    GA_RWHandleV4D h(attrib);
+    if (h.isInvalid())
+        return;
+    double det = m[0] * (m[4] * m[8] - m[5] * m[7])
+               - m[1] * (m[3] * m[8] - m[5] * m[6])
+               + m[2] * (m[3] * m[7] - m[4] * m[6]);
+    for (GA_Iterator it(GA_Range(attrib->getIndexMap())); !it.atEnd(); ++it) {
+        UT_Vector4D q = h.get(*it);
+        UT_Vector3D axis = transform_vector(m, UT_Vector3D(q.x(), q.y(), q.z()), det);
+        h.set(*it, UT_Vector4D(axis.x(), axis.y(), axis.z(), q.w()));
+    }
+    h.bumpDataId();
+}
+
+// Volumes are placed by their point, which is converted with the other positions, and oriented by
+// their own transform, which is converted here. Houdini applies these transforms to row vectors,
+// so the axes are transposed.
+inline void transform_volumes(GU_Detail &detail, const rust::Vec<double> &m, double scale) {
+    UT_Matrix3D axes(m[0], m[3], m[6], m[1], m[4], m[7], m[2], m[5], m[8]);
+    axes *= scale;
+    for (GA_Offset primoff : detail.getPrimitiveRange()) {
+        GEO_Primitive *prim = detail.getGEOPrimitive(primoff);
+        if (prim->getTypeId() != GA_PRIMVOLUME)
+            continue;
+        GEO_PrimVolume *vol = static_cast<GEO_PrimVolume *>(prim);
+        vol->setTransform(UT_Matrix3(UT_Matrix3D(vol->getTransform()) * axes));
+    }
+}
+
+// Convert positions, volumes and the `v`, `N` and `orient` point, vertex and primitive
+// attributes of the given detail between the coordinate system of a file and Houdini. Polygons
+// and tetrahedra are reversed when mirroring, so that they keep facing outwards.
+inline void transform_detail(GU_Detail &detail, const CoordTransform &transform, TransformDirection direction) {
+    if (transform.is_identity())
+        return;
+    rust::Vec<double> axes = transform.axes(direction);
+    double scale = transform.unit_scale(direction);
+    transform_vector_attrib(detail.getP(), axes, scale);
+    transform_volumes(detail, axes, scale);
+    for (GA_AttributeOwner owner : { GA_ATTRIB_POINT, GA_ATTRIB_VERTEX, GA_ATTRIB_PRIMITIVE }) {
+        for (const char *name : VECTOR_ATTRIBS) {
+            if (GA_Attribute *attrib = detail.findFloatTuple(owner, name, 3, 3))
+                transform_vector_attrib(attrib, axes, scale);
+        }
+        for (const char *name : NORMAL_ATTRIBS) {
+            if (GA_Attribute *attrib = detail.findFloatTuple(owner, name, 3, 3))
+                transform_vector_attrib(attrib, axes, 1.0);
+        }
+        for (const char *name : QUATERNION_ATTRIBS) {
+            if (GA_Attribute *attrib = detail.findFloatTuple(owner, name, 4, 4))
+                transform_quaternion_attrib(attrib, axes);
+        }
+    }
+    if (transform.flip_handedness)
+        reverse_prims(detail, true, true);
+}
+
+// Convert a detail loaded from a file with the given coordinate system and vertex order.
+inline void import_detail(GU_Detail &detail, const CoordTransform &transform, const VertexOrder &order) {
+    transform_detail(detail, transform, TransformDirection::Import);
+    reorder_detail(detail, order);
+}
+
+// Get the given detail in the coordinate system and vertex order of a file for saving. The detail
+// is copied into `copy` and converted only if it differs from the file.
+inline const GU_Detail &export_detail(
+        GU_Detail &copy, const GU_Detail &detail, const CoordTransform &transform, const VertexOrder &order)
+{
+    if (transform.is_identity() && !order.reverses_polygons() && !order.inverts_tets())
+        return detail;
+    copy.duplicate(detail);
+    transform_detail(copy, transform, TransformDirection::Export);
+    reorder_detail(copy, order);
+    return copy;
+}
+
+} // namespace hdkrs
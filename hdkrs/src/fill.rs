@@ -0,0 +1,268 @@
+//!
+//! This module allows mesh attributes to be filled concurrently.
+//!
+//! Attributes are first added with zeros for all of their elements, which is the only step that
+//! needs exclusive access to the mesh. The values of all added attributes are then borrowed
+//! together as flat byte slices, which can be split into disjoint ranges of elements filled from
+//! different threads without any synchronization, for instance one page of elements per task.
+//!
+
+use std::collections::HashMap;
+
+use meshx::attrib::{self, Attrib};
+use meshx::mesh::topology::*;
+
+use crate::ffi::{AttribLayout, AttribLocation, DataType};
+use crate::Error;
+use crate::{PointCloud, PointCloudF32, PolyMesh, PolyMeshF32, PolylineMesh, PolylineMeshF32};
+use crate::{TetMesh, TetMeshF32, UnstructuredMesh, UnstructuredMeshF32};
+
+/// Insert an attribute of zeros of the given scalar type and tuple size.
+///
+/// Single component attributes are stored as scalars, and larger tuples as arrays, matching the
+/// `add_attrib_*` functions.
+macro_rules! insert_zeros {
+    ($mesh:ident, $index:ident, $name:ident, $tuple_size:ident, $type:ty) => {
+        insert_zeros!(@ $mesh, $index, $name, $tuple_size, $type;
+                      2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16)
+    };
+    (@ $mesh:ident, $index:ident, $name:ident, $tuple_size:ident, $type:ty; $($n:literal),*) => {{
+        let len = $mesh.attrib_size::<$index>();
+        match $tuple_size {
+            1 => {
+                $mesh.insert_attrib_data::<$type, $index>($name, vec![<$type>::default(); len])?;
+            }
+            $($n => {
+                let zeros = vec![[<$type>::default(); $n]; len];
+                $mesh.insert_attrib_data::<[$type; $n], $index>($name, zeros)?;
+            })*
+            _ => return Err(Error::Internal),
+        }
+    }};
+}
+
+/// Borrow the values of an attribute stored with the given scalar type and tuple size as bytes.
+macro_rules! values_as_bytes {
+    ($attrib:ident, $tuple_size:ident, $type:ty) => {
+        values_as_bytes!(@ $attrib, $tuple_size, $type;
+                         2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16)
+    };
+    (@ $attrib:ident, $tuple_size:ident, $type:ty; $($n:literal),*) => {
+        match $tuple_size {
+            1 => $attrib.as_mut_slice::<$type>().ok().map(bytemuck::cast_slice_mut),
+            $($n => $attrib
+                .as_mut_slice::<[$type; $n]>()
+                .ok()
+                .map(bytemuck::cast_slice_mut),)*
+            _ => None,
+        }
+    };
+}
+
+/// Insert an attribute of zeros at the location given by `I`, replacing any existing attribute
+/// with the same name.
+fn add_zeros<M, I>(
+    mesh: &mut M,
+    name: &str,
+    tuple_size: usize,
+    data_type: DataType,
+) -> Result<(), Error>
+where
+    M: Attrib,
+    I: attrib::AttribIndex<M>,
+{
+    match data_type {
+        DataType::I8 => insert_zeros!(mesh, I, name, tuple_size, i8),
+        DataType::I32 => insert_zeros!(mesh, I, name, tuple_size, i32),
+        DataType::I64 => insert_zeros!(mesh, I, name, tuple_size, i64),
+        DataType::F32 => insert_zeros!(mesh, I, name, tuple_size, f32),
+        DataType::F64 => insert_zeros!(mesh, I, name, tuple_size, f64),
+        _ => return Err(Error::Internal),
+    }
+    Ok(())
+}
+
+fn values_bytes<I>(
+    attrib: &mut attrib::Attribute<I>,
+    tuple_size: usize,
+    data_type: DataType,
+) -> Option<&mut [u8]> {
+    match data_type {
+        DataType::I8 => values_as_bytes!(attrib, tuple_size, i8),
+        DataType::I32 => values_as_bytes!(attrib, tuple_size, i32),
+        DataType::I64 => values_as_bytes!(attrib, tuple_size, i64),
+        DataType::F32 => values_as_bytes!(attrib, tuple_size, f32),
+        DataType::F64 => values_as_bytes!(attrib, tuple_size, f64),
+        _ => None,
+    }
+}
+
+/// Insert attributes of zeros with the given layouts at the location given by `I`, and then
+/// borrow all of their values at once.
+fn add_all_zeros<'a, M, I>(mesh: &'a mut M, layouts: &[AttribLayout]) -> AttribBuffers<'a>
+where
+    M: Attrib,
+    I: attrib::AttribIndex<M>,
+{
+    // Position of the layout of each added attribute. Later layouts replace earlier ones with
+    // the same name.
+    let mut added = HashMap::new();
+    for (i, layout) in layouts.iter().enumerate() {
+        if add_zeros::<M, I>(mesh, &layout.name, layout.tuple_size, layout.data_type).is_ok() {
+            added.insert(layout.name.as_str(), i);
+        }
+    }
+
+    // Each attribute owns its values, so the values of different attributes are disjoint.
+    let mut buffers: Vec<&mut [u8]> = layouts.iter().map(|_| Default::default()).collect();
+    for (name, attrib) in mesh.attrib_dict_mut::<I>().iter_mut() {
+        if let Some(&i) = added.get(name.as_str()) {
+            let layout = &layouts[i];
+            buffers[i] =
+                values_bytes(attrib, layout.tuple_size, layout.data_type).unwrap_or_default();
+        }
+    }
+    AttribBuffers(buffers)
+}
+
+/// Values of attributes added together with `add_attribs_zeros`, as bytes.
+pub struct AttribBuffers<'a>(Vec<&'a mut [u8]>);
+
+impl<'a> AttribBuffers<'a> {
+    /// Take the values of the attribute added with the layout at position `i`, leaving an empty
+    /// slice in its place.
+    ///
+    /// The values are empty if the attribute could not be added, for instance when its tuple
+    /// size is not supported.
+    pub fn take(&mut self, i: usize) -> &'a mut [u8] {
+        std::mem::take(&mut self.0[i])
+    }
+}
+
+macro_rules! impl_add_attribs_zeros {
+    ($mesh_type:ident, $($loc:ident => $index:ident),*) => {
+        impl $mesh_type {
+            /// Add numeric attributes of zeros with the given layouts at the given location, and
+            /// return their values as bytes.
+            ///
+            /// All attributes are added before any values are borrowed, so the values of all
+            /// attributes can be filled at once. Values are stored contiguously in element order,
+            /// with `tuple_size` components of the given type per element, and disjoint
+            /// subslices may be filled concurrently.
+            pub fn add_attribs_zeros(
+                &mut self,
+                loc: AttribLocation,
+                layouts: &[AttribLayout],
+            ) -> Box<AttribBuffers<'_>> {
+                Box::new(match loc {
+                    $(AttribLocation::$loc => add_all_zeros::<_, $index>(&mut self.0, layouts),)*
+                    _ => AttribBuffers(layouts.iter().map(|_| Default::default()).collect()),
+                })
+            }
+        }
+    };
+}
+
+impl_add_attribs_zeros!(PointCloud, VERTEX => VertexIndex);
+impl_add_attribs_zeros!(PointCloudF32, VERTEX => VertexIndex);
+impl_add_attribs_zeros!(PolyMesh,
+    VERTEX => VertexIndex, FACE => FaceIndex, FACEVERTEX => FaceVertexIndex);
+impl_add_attribs_zeros!(PolyMeshF32,
+    VERTEX => VertexIndex, FACE => FaceIndex, FACEVERTEX => FaceVertexIndex);
+impl_add_attribs_zeros!(PolylineMesh,
+    VERTEX => VertexIndex, CURVE => FaceIndex, CURVEVERTEX => FaceVertexIndex);
+impl_add_attribs_zeros!(PolylineMeshF32,
+    VERTEX => VertexIndex, CURVE => FaceIndex, CURVEVERTEX => FaceVertexIndex);
+impl_add_attribs_zeros!(TetMesh,
+    VERTEX => VertexIndex, CELL => CellIndex, CELLVERTEX => CellVertexIndex);
+impl_add_attribs_zeros!(TetMeshF32,
+    VERTEX => VertexIndex, CELL => CellIndex, CELLVERTEX => CellVertexIndex);
+impl_add_attribs_zeros!(UnstructuredMesh,
+    VERTEX => VertexIndex, CELL => CellIndex, CELLVERTEX => CellVertexIndex);
+impl_add_attribs_zeros!(UnstructuredMeshF32,
+    VERTEX => VertexIndex, CELL => CellIndex, CELLVERTEX => CellVertexIndex);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::make_polymesh;
+
+    fn layout(name: &str, tuple_size: usize, data_type: DataType) -> AttribLayout {
+        AttribLayout {
+            name: String::from(name),
+            tuple_size,
+            data_type,
+        }
+    }
+
+    #[test]
+    fn attributes_filled_from_disjoint_ranges() {
+        let coords = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0];
+        let mut mesh = make_polymesh(&coords, &[3, 0, 1, 2, 3, 0, 2, 3]);
+
+        let layouts = [
+            layout("v", 3, DataType::F32),
+            layout("w", 17, DataType::F32),
+            layout("id", 1, DataType::I64),
+        ];
+        let mut buffers = mesh.add_attribs_zeros(AttribLocation::VERTEX, &layouts[..2]);
+        let values: &mut [f32] = bytemuck::cast_slice_mut(buffers.take(0));
+        assert_eq!(values.len(), 12);
+        // Unsupported tuple sizes are not added.
+        assert!(buffers.take(1).is_empty());
+        std::thread::scope(|s| {
+            for (i, chunk) in values.chunks_mut(3).enumerate() {
+                s.spawn(move || chunk.iter_mut().for_each(|x| *x = i as f32));
+            }
+        });
+
+        let mut buffers = mesh.add_attribs_zeros(AttribLocation::FACE, &layouts[2..]);
+        let values: &mut [i64] = bytemuck::cast_slice_mut(buffers.take(0));
+        values[1] = 7;
+
+        assert_eq!(
+            mesh.0
+                .attrib_as_slice::<[f32; 3], VertexIndex>("v")
+                .unwrap(),
+            &[[0.0; 3], [1.0; 3], [2.0; 3], [3.0; 3]]
+        );
+        assert!(!mesh.0.attrib_exists::<VertexIndex>("w"));
+        assert_eq!(
+            mesh.0.attrib_as_slice::<i64, FaceIndex>("id").unwrap(),
+            &[0, 7]
+        );
+        assert!(mesh
+            .add_attribs_zeros(AttribLocation::CELL, &layouts[2..])
+            .take(0)
+            .is_empty());
+        assert!(mesh
+            .add_attribs_zeros(AttribLocation::FACE, &[layout("s", 1, DataType::STR)])
+            .take(0)
+            .is_empty());
+    }
+
+    #[test]
+    fn several_attributes_filled_at_once() {
+        let coords = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0];
+        let mut mesh = make_polymesh(&coords, &[3, 0, 1, 2]);
+
+        let layouts = [layout("a", 1, DataType::I32), layout("b", 2, DataType::F64)];
+        let mut buffers = mesh.add_attribs_zeros(AttribLocation::VERTEX, &layouts);
+        let a: &mut [i32] = bytemuck::cast_slice_mut(buffers.take(0));
+        let b: &mut [f64] = bytemuck::cast_slice_mut(buffers.take(1));
+        // Both attributes are borrowed at the same time.
+        a.copy_from_slice(&[1, 2, 3]);
+        b.copy_from_slice(&[0.5; 6]);
+
+        assert_eq!(
+            mesh.0.attrib_as_slice::<i32, VertexIndex>("a").unwrap(),
+            &[1, 2, 3]
+        );
+        assert_eq!(
+            mesh.0
+                .attrib_as_slice::<[f64; 2], VertexIndex>("b")
+                .unwrap(),
+            &[[0.5; 2]; 3]
+        );
+    }
+}
@@ -0,0 +1,15 @@
+#include "istream.h"
+
+#include <stdexcept>
+#include <UT/UT_IStream.h>
+
+hdkrs::IStreamReader::IStreamReader(UT_IStream &stream, const char * status_message)
+    : stream(stream), interrupt(std::make_unique<InterruptChecker>(status_message)) {
+}
+
+std::size_t hdkrs::IStreamReader::read(rust::Slice<uint8_t> buf) {
+    if (this->interrupt->check_interrupt()) {
+        throw std::runtime_error("Interrupted");
+    }
+    return this->stream.bread(reinterpret_cast<char *>(buf.data()), buf.size());
+}
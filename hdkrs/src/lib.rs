@@ -2,10 +2,20 @@ use std::any::TypeId;
 use std::iter::Peekable;
 use std::sync::Arc;
 
-use std::collections::hash_map::Iter;
 use meshx::{attrib, attrib::Attrib, mesh, mesh::topology as topo, mesh::VertexPositions};
+use std::collections::hash_map::Iter;
 
+pub mod fill;
 pub mod interop;
+pub mod orientation;
+pub mod stream;
+pub mod surface;
+pub mod transform;
+
+pub use fill::AttribBuffers;
+use orientation::vertex_order_from_env;
+use stream::compress_for_path;
+use transform::coord_transform_from_env;
 
 #[cxx::bridge(namespace = "hdkrs")]
 pub mod ffi {
@@ -13,6 +23,7 @@ pub mod ffi {
     unsafe extern "C++" {
         include!("hdkrs/mesh.h");
         include!("hdkrs/interrupt.h");
+        include!("hdkrs/istream.h");
         type GU_Detail;
         fn impl_shared_ptr(detail: SharedPtr<GU_Detail>);
     }
@@ -23,25 +34,98 @@ pub mod ffi {
         fn new_interrupt_checker(message: &CxxString) -> UniquePtr<InterruptChecker>;
     }
 
+    unsafe extern "C++" {
+        type IStreamReader;
+        fn read(self: Pin<&mut IStreamReader>, buf: &mut [u8]) -> Result<usize>;
+    }
+
     unsafe extern "C++" {
         fn add_polymesh(detail: Pin<&mut GU_Detail>, polymesh: &PolyMesh);
+        fn add_polymesh_with_options(
+            detail: Pin<&mut GU_Detail>,
+            polymesh: &PolyMesh,
+            options: &AddOptions,
+        );
+        fn add_packed_by_name(detail: Pin<&mut GU_Detail>, mesh: &Mesh, options: &AddOptions);
+        fn add_polylines(detail: Pin<&mut GU_Detail>, polylines: &PolylineMesh);
         fn add_tetmesh(detail: Pin<&mut GU_Detail>, tetmesh: &TetMesh);
         fn add_pointcloud(detail: Pin<&mut GU_Detail>, ptcloud: &PointCloud);
+        fn add_polymesh_f32(detail: Pin<&mut GU_Detail>, polymesh: &PolyMeshF32);
+        fn add_polymesh_f32_with_options(
+            detail: Pin<&mut GU_Detail>,
+            polymesh: &PolyMeshF32,
+            options: &AddOptions,
+        );
+        fn add_polylines_f32(detail: Pin<&mut GU_Detail>, polylines: &PolylineMeshF32);
+        fn add_tetmesh_f32(detail: Pin<&mut GU_Detail>, tetmesh: &TetMeshF32);
+        fn add_pointcloud_f32(detail: Pin<&mut GU_Detail>, ptcloud: &PointCloudF32);
         fn add_unstructured_mesh(detail: Pin<&mut GU_Detail>, mesh: &UnstructuredMesh);
+        fn add_unstructured_mesh_f32(detail: Pin<&mut GU_Detail>, mesh: &UnstructuredMeshF32);
+        fn add_grid(detail: Pin<&mut GU_Detail>, grid: &GridMesh);
+        fn add_collection(detail: Pin<&mut GU_Detail>, collection: &MeshCollection);
         fn update_points(detail: Pin<&mut GU_Detail>, ptcloud: &PointCloud);
 
+        fn scatter_to_source(detail: Pin<&mut GU_Detail>, mesh: &Mesh);
+
         fn build_polymesh(detail: &GU_Detail) -> Result<Box<PolyMesh>>;
+        fn build_polymesh_with_options(
+            detail: &GU_Detail,
+            options: &ExtractOptions,
+        ) -> Result<Box<PolyMesh>>;
+        fn build_polylines(detail: &GU_Detail) -> Result<Box<PolylineMesh>>;
+        fn build_polylines_with_options(
+            detail: &GU_Detail,
+            options: &ExtractOptions,
+        ) -> Result<Box<PolylineMesh>>;
         fn build_tetmesh(detail: &GU_Detail) -> Result<Box<TetMesh>>;
+        fn build_tetmesh_with_options(
+            detail: &GU_Detail,
+            options: &ExtractOptions,
+        ) -> Result<Box<TetMesh>>;
         fn build_pointcloud(detail: &GU_Detail) -> Result<Box<PointCloud>>;
+        fn build_polymesh_f32(detail: &GU_Detail) -> Result<Box<PolyMeshF32>>;
+        fn build_polymesh_f32_with_options(
+            detail: &GU_Detail,
+            options: &ExtractOptions,
+        ) -> Result<Box<PolyMeshF32>>;
+        fn build_tetmesh_f32(detail: &GU_Detail) -> Result<Box<TetMeshF32>>;
+        fn build_tetmesh_f32_with_options(
+            detail: &GU_Detail,
+            options: &ExtractOptions,
+        ) -> Result<Box<TetMeshF32>>;
+        fn build_polylines_f32(detail: &GU_Detail) -> Result<Box<PolylineMeshF32>>;
+        fn build_polylines_f32_with_options(
+            detail: &GU_Detail,
+            options: &ExtractOptions,
+        ) -> Result<Box<PolylineMeshF32>>;
+        fn build_pointcloud_f32(detail: &GU_Detail) -> Result<Box<PointCloudF32>>;
+        fn build_unstructured_mesh_f32(detail: &GU_Detail) -> Result<Box<UnstructuredMeshF32>>;
+        fn build_unstructured_mesh_f32_with_options(
+            detail: &GU_Detail,
+            options: &ExtractOptions,
+        ) -> Result<Box<UnstructuredMeshF32>>;
+        fn has_f64_positions(detail: &GU_Detail) -> bool;
+        fn promote_p64(detail: Pin<&mut GU_Detail>);
         fn build_unstructured_mesh(detail: &GU_Detail) -> Result<Box<UnstructuredMesh>>;
+        fn build_unstructured_mesh_with_options(
+            detail: &GU_Detail,
+            options: &ExtractOptions,
+        ) -> Result<Box<UnstructuredMesh>>;
+        fn build_grid(detail: &GU_Detail) -> Result<Box<GridMesh>>;
+        fn build_all(detail: &GU_Detail) -> Result<Box<MeshCollection>>;
     }
     extern "Rust" {
         type UnstructuredMesh;
         fn get_point_coords(&self) -> Vec<f64>;
+        fn point_coords(&self) -> &[f64];
         fn get_indices(&self) -> Vec<usize>;
+        fn indices(&self) -> &[usize];
         fn get_counts(&self) -> Vec<usize>;
         fn get_sizes(&self) -> Vec<usize>;
         fn get_cell_types(&self) -> Vec<CellType>;
+        fn cell_offsets(&self) -> &[usize];
+        fn block_offsets(&self) -> &[usize];
+        fn cell_type(&self, block: usize) -> CellType;
         fn attrib_iter(&self, loc: AttribLocation) -> Box<AttribIter<'_>>;
         fn add_attrib_f32(
             &mut self,
@@ -86,11 +170,76 @@ pub mod ffi {
             strings: &[&str],
             data: &[i64],
         );
+        fn add_attribs_zeros(
+            &mut self,
+            loc: AttribLocation,
+            layouts: &[AttribLayout],
+        ) -> Box<AttribBuffers<'_>>;
     }
     extern "Rust" {
         type PolyMesh;
         fn get_point_coords(&self) -> Vec<f64>;
+        fn point_coords(&self) -> &[f64];
+        fn get_indices(&self) -> Vec<usize>;
+        fn indices(&self) -> &[usize];
+        fn offsets(&self) -> &[usize];
+        fn attrib_iter(&self, loc: AttribLocation) -> Box<AttribIter<'_>>;
+        fn add_attrib_f32(
+            &mut self,
+            loc: AttribLocation,
+            name: &str,
+            tuple_size: usize,
+            data: &[f32],
+        );
+        fn add_attrib_f64(
+            &mut self,
+            loc: AttribLocation,
+            name: &str,
+            tuple_size: usize,
+            data: &[f64],
+        );
+        fn add_attrib_i8(
+            &mut self,
+            loc: AttribLocation,
+            name: &str,
+            tuple_size: usize,
+            data: &[i8],
+        );
+        fn add_attrib_i32(
+            &mut self,
+            loc: AttribLocation,
+            name: &str,
+            tuple_size: usize,
+            data: &[i32],
+        );
+        fn add_attrib_i64(
+            &mut self,
+            loc: AttribLocation,
+            name: &str,
+            tuple_size: usize,
+            data: &[i64],
+        );
+        fn add_attrib_str(
+            &mut self,
+            loc: AttribLocation,
+            name: &str,
+            tuple_size: usize,
+            strings: &[&str],
+            data: &[i64],
+        );
+        fn add_attribs_zeros(
+            &mut self,
+            loc: AttribLocation,
+            layouts: &[AttribLayout],
+        ) -> Box<AttribBuffers<'_>>;
+    }
+    extern "Rust" {
+        type PolylineMesh;
+        fn get_point_coords(&self) -> Vec<f64>;
+        fn point_coords(&self) -> &[f64];
         fn get_indices(&self) -> Vec<usize>;
+        fn indices(&self) -> &[usize];
+        fn offsets(&self) -> &[usize];
         fn attrib_iter(&self, loc: AttribLocation) -> Box<AttribIter<'_>>;
         fn add_attrib_f32(
             &mut self,
@@ -135,11 +284,18 @@ pub mod ffi {
             strings: &[&str],
             data: &[i64],
         );
+        fn add_attribs_zeros(
+            &mut self,
+            loc: AttribLocation,
+            layouts: &[AttribLayout],
+        ) -> Box<AttribBuffers<'_>>;
     }
     extern "Rust" {
         type TetMesh;
         fn get_point_coords(&self) -> Vec<f64>;
+        fn point_coords(&self) -> &[f64];
         fn get_indices(&self) -> Vec<usize>;
+        fn indices(&self) -> &[usize];
         fn attrib_iter(&self, loc: AttribLocation) -> Box<AttribIter<'_>>;
         fn add_attrib_f32(
             &mut self,
@@ -184,10 +340,16 @@ pub mod ffi {
             strings: &[&str],
             data: &[i64],
         );
+        fn add_attribs_zeros(
+            &mut self,
+            loc: AttribLocation,
+            layouts: &[AttribLayout],
+        ) -> Box<AttribBuffers<'_>>;
     }
     extern "Rust" {
         type PointCloud;
         fn get_point_coords(&self) -> Vec<f64>;
+        fn point_coords(&self) -> &[f64];
         fn attrib_iter(&self, loc: AttribLocation) -> Box<AttribIter<'_>>;
         fn add_attrib_f32(
             &mut self,
@@ -232,6 +394,132 @@ pub mod ffi {
             strings: &[&str],
             data: &[i64],
         );
+        fn add_attribs_zeros(
+            &mut self,
+            loc: AttribLocation,
+            layouts: &[AttribLayout],
+        ) -> Box<AttribBuffers<'_>>;
+    }
+    // Single precision meshes expose only what is needed to move them in and out of a detail.
+    // Numeric attributes are added with `add_attribs_zeros` and filled on the C++ side.
+    extern "Rust" {
+        type PolyMeshF32;
+        fn point_coords(&self) -> &[f32];
+        fn get_indices(&self) -> Vec<usize>;
+        fn indices(&self) -> &[usize];
+        fn offsets(&self) -> &[usize];
+        fn attrib_iter(&self, loc: AttribLocation) -> Box<AttribIter<'_>>;
+        fn add_attrib_str(
+            &mut self,
+            loc: AttribLocation,
+            name: &str,
+            tuple_size: usize,
+            strings: &[&str],
+            data: &[i64],
+        );
+        fn add_attribs_zeros(
+            &mut self,
+            loc: AttribLocation,
+            layouts: &[AttribLayout],
+        ) -> Box<AttribBuffers<'_>>;
+    }
+    extern "Rust" {
+        type PolylineMeshF32;
+        fn point_coords(&self) -> &[f32];
+        fn get_indices(&self) -> Vec<usize>;
+        fn indices(&self) -> &[usize];
+        fn offsets(&self) -> &[usize];
+        fn attrib_iter(&self, loc: AttribLocation) -> Box<AttribIter<'_>>;
+        fn add_attrib_str(
+            &mut self,
+            loc: AttribLocation,
+            name: &str,
+            tuple_size: usize,
+            strings: &[&str],
+            data: &[i64],
+        );
+        fn add_attribs_zeros(
+            &mut self,
+            loc: AttribLocation,
+            layouts: &[AttribLayout],
+        ) -> Box<AttribBuffers<'_>>;
+    }
+    extern "Rust" {
+        type TetMeshF32;
+        fn point_coords(&self) -> &[f32];
+        fn indices(&self) -> &[usize];
+        fn attrib_iter(&self, loc: AttribLocation) -> Box<AttribIter<'_>>;
+        fn add_attrib_str(
+            &mut self,
+            loc: AttribLocation,
+            name: &str,
+            tuple_size: usize,
+            strings: &[&str],
+            data: &[i64],
+        );
+        fn add_attribs_zeros(
+            &mut self,
+            loc: AttribLocation,
+            layouts: &[AttribLayout],
+        ) -> Box<AttribBuffers<'_>>;
+    }
+    extern "Rust" {
+        type PointCloudF32;
+        fn point_coords(&self) -> &[f32];
+        fn attrib_iter(&self, loc: AttribLocation) -> Box<AttribIter<'_>>;
+        fn add_attrib_str(
+            &mut self,
+            loc: AttribLocation,
+            name: &str,
+            tuple_size: usize,
+            strings: &[&str],
+            data: &[i64],
+        );
+        fn add_attribs_zeros(
+            &mut self,
+            loc: AttribLocation,
+            layouts: &[AttribLayout],
+        ) -> Box<AttribBuffers<'_>>;
+    }
+    extern "Rust" {
+        type UnstructuredMeshF32;
+        fn point_coords(&self) -> &[f32];
+        fn indices(&self) -> &[usize];
+        fn cell_offsets(&self) -> &[usize];
+        fn block_offsets(&self) -> &[usize];
+        fn cell_type(&self, block: usize) -> CellType;
+        fn attrib_iter(&self, loc: AttribLocation) -> Box<AttribIter<'_>>;
+        fn add_attrib_str(
+            &mut self,
+            loc: AttribLocation,
+            name: &str,
+            tuple_size: usize,
+            strings: &[&str],
+            data: &[i64],
+        );
+        fn add_attribs_zeros(
+            &mut self,
+            loc: AttribLocation,
+            layouts: &[AttribLayout],
+        ) -> Box<AttribBuffers<'_>>;
+    }
+    extern "Rust" {
+        type GridMesh;
+        fn get_origin(&self) -> Vec<f64>;
+        fn get_spacing(&self) -> Vec<f64>;
+        fn get_dims(&self) -> Vec<usize>;
+        fn num_fields(&self) -> usize;
+        fn field_name(&self, i: usize) -> &str;
+        fn field_tuple_size(&self, i: usize) -> usize;
+        fn field_is_cell(&self, i: usize) -> bool;
+        fn field_data(&self, i: usize) -> &[f32];
+        fn add_field(
+            &mut self,
+            name: &str,
+            tuple_size: usize,
+            cell: bool,
+            data: &[f32],
+        ) -> Result<()>;
     }
     #[rustfmt::skip]
     extern "Rust" {
@@ -239,6 +527,10 @@ pub mod ffi {
         fn has_next(&mut self) -> bool;
         unsafe fn next<'a>(self: &mut AttribIter<'a>) -> Result<Box<Attribute<'a>>>;
     }
+    extern "Rust" {
+        type AttribBuffers<'a>;
+        unsafe fn take<'a>(self: &mut AttribBuffers<'a>, i: usize) -> &'a mut [u8];
+    }
     extern "Rust" {
         type TupleVecStr;
         fn len(&self) -> usize;
@@ -256,29 +548,93 @@ pub mod ffi {
         fn get_data_f32(&self) -> TupleVecF32;
         fn get_data_f64(&self) -> TupleVecF64;
         fn get_data_str(&self) -> Box<TupleVecStr>;
+        fn tuple_size(&self) -> usize;
+        fn data_i8(&self) -> &[i8];
+        fn data_i32(&self) -> &[i32];
+        fn data_i64(&self) -> &[i64];
+        fn data_f32(&self) -> &[f32];
+        fn data_f64(&self) -> &[f64];
     }
     extern "Rust" {
         pub type Mesh;
         fn is_tetmesh(&self) -> bool;
         fn is_polymesh(&self) -> bool;
+        fn is_polyline_mesh(&self) -> bool;
         fn is_pointcloud(&self) -> bool;
         fn is_unstructured_mesh(&self) -> bool;
+        fn is_grid(&self) -> bool;
         fn tag(&self) -> MeshTag;
+        fn get_point_coords(&self) -> Vec<f64>;
+        fn point_coords(&self) -> &[f64];
+        fn point_coords_f32(&self) -> &[f32];
+        fn attrib_iter(&self, loc: AttribLocation) -> Box<AttribIter<'_>>;
+        fn source_indices(&self, loc: AttribLocation) -> Vec<i64>;
         fn add_to_detail(&self, detail: Pin<&mut GU_Detail>);
+        fn add_to_detail_with_options(&self, detail: Pin<&mut GU_Detail>, options: &AddOptions);
+        fn needs_f64_positions(&self) -> bool;
+        fn transform(&mut self, transform: &CoordTransform, direction: TransformDirection);
+        fn reorder(&mut self, vertex_order: &VertexOrder);
+        fn boundary_polymesh(&self, keep_cell_attribs: bool) -> Result<Box<PolyMesh>>;
+        fn to_triangle_cells(&self, source_polygons: bool) -> Result<Box<UnstructuredMesh>>;
         fn into_tetmesh(mesh: Box<Mesh>) -> Box<TetMesh>;
         fn into_polymesh(mesh: Box<Mesh>) -> Box<PolyMesh>;
+        fn into_polyline_mesh(mesh: Box<Mesh>) -> Box<PolylineMesh>;
         fn into_pointcloud(mesh: Box<Mesh>) -> Box<PointCloud>;
         fn into_unstructured_mesh(mesh: Box<Mesh>) -> Box<UnstructuredMesh>;
+        fn into_grid(mesh: Box<Mesh>) -> Box<GridMesh>;
+    }
+    extern "Rust" {
+        type MeshCollection;
+        fn new_mesh_collection(num_points: usize) -> Box<MeshCollection>;
+        fn num_points(&self) -> usize;
+        fn has_polymesh(&self) -> bool;
+        fn has_tetmesh(&self) -> bool;
+        fn has_polylines(&self) -> bool;
+        fn has_points(&self) -> bool;
+        fn polymesh(&self) -> Result<&PolyMesh>;
+        fn tetmesh(&self) -> Result<&TetMesh>;
+        fn polylines(&self) -> Result<&PolylineMesh>;
+        fn points(&self) -> Result<&PointCloud>;
+        fn set_polymesh(&mut self, polymesh: Box<PolyMesh>);
+        fn set_tetmesh(&mut self, tetmesh: Box<TetMesh>);
+        fn set_polylines(&mut self, polylines: Box<PolylineMesh>);
+        fn set_points(&mut self, points: Box<PointCloud>);
+        fn point_indices(&self, part: MeshTag) -> Vec<usize>;
+        fn prim_order(&self) -> Vec<PrimRef>;
+        fn add_to_detail(&self, detail: Pin<&mut GU_Detail>);
+    }
+    extern "Rust" {
+        fn compress_for_path(data: &[u8], path: &str) -> Result<Vec<u8>>;
+    }
+    extern "Rust" {
+        fn coord_transform_from_env(prefix: &str) -> CoordTransform;
+        fn is_identity(self: &CoordTransform) -> bool;
+        fn axes(self: &CoordTransform, direction: TransformDirection) -> Vec<f64>;
+        fn unit_scale(self: &CoordTransform, direction: TransformDirection) -> f64;
+        fn vertex_order_from_env(prefix: &str, default: &VertexOrder) -> VertexOrder;
+        fn reverses_polygons(self: &VertexOrder) -> bool;
+        fn inverts_tets(self: &VertexOrder) -> bool;
     }
     extern "Rust" {
         fn make_pointcloud(coords: &[f64]) -> Box<PointCloud>;
         fn make_polymesh(coords: &[f64], indices: &[usize]) -> Box<PolyMesh>;
+        fn make_polylines(coords: &[f64], indices: &[usize]) -> Box<PolylineMesh>;
         fn make_tetmesh(coords: &[f64], indices: &[usize]) -> Box<TetMesh>;
+        fn make_pointcloud_f32(coords: &[f32]) -> Box<PointCloudF32>;
+        fn make_polymesh_f32(coords: &[f32], indices: &[usize]) -> Box<PolyMeshF32>;
+        fn make_polylines_f32(coords: &[f32], indices: &[usize]) -> Box<PolylineMeshF32>;
+        fn make_tetmesh_f32(coords: &[f32], indices: &[usize]) -> Box<TetMeshF32>;
         fn make_unstructured_mesh(
             coords: &[f64],
             indices: &[usize],
             types: &[CellType],
         ) -> Box<UnstructuredMesh>;
+        fn make_unstructured_mesh_f32(
+            coords: &[f32],
+            indices: &[usize],
+            types: &[CellType],
+        ) -> Box<UnstructuredMeshF32>;
+        fn make_grid(origin: &[f64], spacing: &[f64], dims: &[usize]) -> Result<Box<GridMesh>>;
     }
 
     #[derive(Debug)]
@@ -326,6 +682,8 @@ pub mod ffi {
         CELL,
         FACEVERTEX,
         CELLVERTEX,
+        CURVE,
+        CURVEVERTEX,
     }
 
     #[derive(Debug)]
@@ -339,21 +697,138 @@ pub mod ffi {
         UNSUPPORTED,
     }
 
+    /// Name, tuple size and type of a numeric attribute added with `add_attribs_zeros`.
+    #[derive(Clone, Debug)]
+    pub struct AttribLayout {
+        pub name: String,
+        pub tuple_size: usize,
+        pub data_type: DataType,
+    }
+
     #[derive(Debug)]
     pub enum MeshTag {
         TetMesh,
         PolyMesh,
+        PolylineMesh,
         PointCloud,
         UnstructuredMesh,
+        GridMesh,
         None,
     }
 
+    /// Options controlling how meshes are added to a detail.
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct AddOptions {
+        /// Store polygons in polygon soup primitives, which use less memory for large meshes.
+        pub polysoup: bool,
+        /// Pack primitives sharing the same `name` primitive attribute into one packed
+        /// primitive per name.
+        pub pack_by_name: bool,
+        /// Promote point positions of the detail to 64-bit storage if the mesh has coordinates
+        /// that can't be represented in single precision. Applies to meshes added through
+        /// `Mesh::add_to_detail_with_options`.
+        pub promote_p64: bool,
+    }
+
+    /// Options controlling how meshes are extracted from a detail.
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct ExtractOptions {
+        /// Store the source index of each point, primitive and vertex in reserved integer
+        /// attributes, so that results can be scattered back onto the source detail.
+        ///
+        /// Extraction fails if the detail contains packed primitives, since their contents have
+        /// no source elements to scatter back onto.
+        pub keep_source_indices: bool,
+        /// Drop points not referenced by any extracted primitive. The original point number of
+        /// each remaining point is stored in the reserved point index attribute, except for
+        /// details with packed primitives, whose unpacked points have no original point number.
+        pub compact_points: bool,
+        /// Method used to split polygons with more than three vertices into triangles when
+        /// extracting unstructured meshes, which only support triangles and tetrahedra.
+        pub triangulation: Triangulation,
+        /// Store the index of the primitive each cell of an unstructured mesh was extracted
+        /// from in the `SOURCE_POLYGON_ATTRIB` cell attribute, which maps triangles back to
+        /// the polygons they were split from. The attribute is added to every extracted
+        /// unstructured mesh, whether or not any polygon was split, unless the detail already
+        /// has a primitive attribute with that name.
+        pub source_polygons: bool,
+    }
+
+    /// Method used to split polygons into triangles.
+    #[derive(Debug)]
+    pub enum Triangulation {
+        /// Connect the first vertex of each polygon to all of its edges. This is only valid for
+        /// convex polygons.
+        Fan,
+        /// Clip ears off each polygon projected onto its plane, which also handles concave
+        /// polygons.
+        EarClip,
+    }
+
+    /// A face or cell of a single part in a `MeshCollection`.
+    #[derive(Clone, Copy, Debug)]
+    pub struct PrimRef {
+        /// The part containing the primitive.
+        pub part: MeshTag,
+        /// Index of the face or cell within the part.
+        pub index: usize,
+    }
+
     /// The type of cell stored in an unstructured mesh.
     #[derive(Debug)]
     pub enum CellType {
         Triangle,
         Tetrahedron,
     }
+
+    /// The axis pointing up in a coordinate system.
+    #[derive(Debug)]
+    pub enum UpAxis {
+        Y,
+        Z,
+    }
+
+    /// Whether geometry is converted from a file into Houdini or from Houdini into a file.
+    #[derive(Debug)]
+    pub enum TransformDirection {
+        Import,
+        Export,
+    }
+
+    /// Coordinate system of geometry files relative to the right-handed, Y-up coordinate
+    /// system of Houdini scenes.
+    #[derive(Clone, Copy, Debug)]
+    pub struct CoordTransform {
+        /// The axis pointing up in files.
+        pub up_axis: UpAxis,
+        /// Files use a left-handed coordinate system, converted by mirroring the horizontal X
+        /// axis of files.
+        pub flip_handedness: bool,
+        /// Size of one file unit in scene units, e.g. `0.001` for files in millimetres loaded
+        /// into scenes in metres.
+        pub scale: f64,
+    }
+
+    /// Order of polygon vertices when viewed from the front of the polygon.
+    #[derive(Debug)]
+    pub enum Winding {
+        Clockwise,
+        CounterClockwise,
+    }
+
+    /// Sign of the volume of tetrahedra given by their vertex order.
+    #[derive(Debug)]
+    pub enum TetOrientation {
+        Negative,
+        Positive,
+    }
+
+    /// Vertex ordering convention of polygons and tetrahedra in a geometry file.
+    #[derive(Clone, Copy, Debug)]
+    pub struct VertexOrder {
+        pub winding: Winding,
+        pub tet_orientation: TetOrientation,
+    }
 }
 
 use self::ffi::*;
@@ -379,6 +854,30 @@ impl From<CellType> for mesh::CellType {
     }
 }
 
+impl Default for Triangulation {
+    fn default() -> Self {
+        Triangulation::Fan
+    }
+}
+
+/// Prefix of reserved attribute names, which are never transferred back onto details.
+pub const RESERVED_ATTRIB_PREFIX: &str = "__hdkrs_";
+/// Name of the reserved point attribute storing the source point index of each point.
+pub const POINT_INDEX_ATTRIB: &str = "__hdkrs_point_index";
+/// Name of the reserved primitive attribute storing the source primitive index of each face or cell.
+pub const PRIM_INDEX_ATTRIB: &str = "__hdkrs_prim_index";
+/// Name of the reserved vertex attribute storing the source vertex index of each face or cell vertex.
+pub const VERTEX_INDEX_ATTRIB: &str = "__hdkrs_vertex_index";
+/// Name of the primitive attribute storing the index of the polygon each triangle was split from
+/// when requested by `ExtractOptions::source_polygons` or `Mesh::to_triangle_cells`.
+pub const SOURCE_POLYGON_ATTRIB: &str = "sourceprim";
+
+/// Largest absolute error from rounding a position coordinate to single precision that does not
+/// require double precision storage.
+///
+/// Coordinates within about a thousand units of the origin are always rounded by less than this.
+pub const F32_POSITION_TOLERANCE: f64 = 1e-4;
+
 /// A Rust unstructuerd mesh struct.
 #[derive(Clone, PartialEq, Debug)]
 #[repr(transparent)]
@@ -401,6 +900,19 @@ impl From<mesh::PolyMesh<f64>> for PolyMesh {
     }
 }
 
+/// A Rust polyline mesh struct.
+///
+/// Each face of the underlying polygon mesh is interpreted as an open curve through its vertices.
+#[derive(Clone, PartialEq, Debug)]
+#[repr(transparent)]
+pub struct PolylineMesh(pub mesh::PolyMesh<f64>);
+
+impl From<mesh::PolyMesh<f64>> for PolylineMesh {
+    fn from(m: mesh::PolyMesh<f64>) -> Self {
+        PolylineMesh(m)
+    }
+}
+
 /// A Rust tetmesh struct.
 #[derive(Clone, PartialEq, Debug)]
 #[repr(transparent)]
@@ -412,6 +924,61 @@ impl From<mesh::TetMesh<f64>> for TetMesh {
     }
 }
 
+/// A Rust polygon mesh struct with single precision positions.
+#[derive(Clone, PartialEq, Debug)]
+#[repr(transparent)]
+pub struct PolyMeshF32(pub mesh::PolyMesh<f32>);
+
+impl From<mesh::PolyMesh<f32>> for PolyMeshF32 {
+    fn from(m: mesh::PolyMesh<f32>) -> Self {
+        PolyMeshF32(m)
+    }
+}
+
+/// A Rust polyline mesh struct with single precision positions.
+#[derive(Clone, PartialEq, Debug)]
+#[repr(transparent)]
+pub struct PolylineMeshF32(pub mesh::PolyMesh<f32>);
+
+impl From<mesh::PolyMesh<f32>> for PolylineMeshF32 {
+    fn from(m: mesh::PolyMesh<f32>) -> Self {
+        PolylineMeshF32(m)
+    }
+}
+
+/// A Rust tetmesh struct with single precision positions.
+#[derive(Clone, PartialEq, Debug)]
+#[repr(transparent)]
+pub struct TetMeshF32(pub mesh::TetMesh<f32>);
+
+impl From<mesh::TetMesh<f32>> for TetMeshF32 {
+    fn from(m: mesh::TetMesh<f32>) -> Self {
+        TetMeshF32(m)
+    }
+}
+
+/// A Rust pointcloud struct with single precision positions.
+#[derive(Clone, PartialEq, Debug)]
+#[repr(transparent)]
+pub struct PointCloudF32(pub mesh::PointCloud<f32>);
+
+impl From<mesh::PointCloud<f32>> for PointCloudF32 {
+    fn from(m: mesh::PointCloud<f32>) -> Self {
+        PointCloudF32(m)
+    }
+}
+
+/// A Rust unstructured mesh struct with single precision positions.
+#[derive(Clone, PartialEq, Debug)]
+#[repr(transparent)]
+pub struct UnstructuredMeshF32(pub mesh::Mesh<f32>);
+
+impl From<mesh::Mesh<f32>> for UnstructuredMeshF32 {
+    fn from(m: mesh::Mesh<f32>) -> Self {
+        UnstructuredMeshF32(m)
+    }
+}
+
 /// A Rust pointcloud struct.
 #[derive(Clone, PartialEq, Debug)]
 #[repr(transparent)]
@@ -423,6 +990,29 @@ impl From<mesh::PointCloud<f64>> for PointCloud {
     }
 }
 
+/// A field sampled on a regular grid.
+#[derive(Clone, PartialEq, Debug)]
+pub struct GridField {
+    pub name: String,
+    pub tuple_size: usize,
+    /// Whether values are stored at cell centers rather than at grid points.
+    pub cell: bool,
+    /// Field values with the x index varying fastest, then y, then z.
+    pub data: Vec<f32>,
+}
+
+/// A Rust regular grid struct.
+///
+/// Grid points are located at `origin + spacing * [i, j, k]` for `0 <= [i, j, k] < dims`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct GridMesh {
+    pub origin: [f64; 3],
+    pub spacing: [f64; 3],
+    /// Number of grid points along each axis.
+    pub dims: [usize; 3],
+    pub fields: Vec<GridField>,
+}
+
 // TODO: Arc is not currently supported by cxx. Move this into extern "Rust".
 pub struct TupleVecStr {
     vec: Vec<std::sync::Arc<String>>,
@@ -470,6 +1060,10 @@ impl PointCloud {
     pub fn get_point_coords(&self) -> Vec<f64> {
         bytemuck::cast_slice(self.0.vertex_positions()).to_vec()
     }
+    /// Point coordinates borrowed from the mesh without copying.
+    pub fn point_coords(&self) -> &[f64] {
+        bytemuck::cast_slice(self.0.vertex_positions())
+    }
     pub fn attrib_iter(&self, loc: AttribLocation) -> Box<AttribIter<'_>> {
         Box::new(match loc {
             AttribLocation::VERTEX => {
@@ -480,14 +1074,121 @@ impl PointCloud {
     }
 }
 
+impl GridMesh {
+    pub fn new(origin: [f64; 3], spacing: [f64; 3], dims: [usize; 3]) -> Self {
+        GridMesh {
+            origin,
+            spacing,
+            dims,
+            fields: Vec::new(),
+        }
+    }
+    pub fn get_origin(&self) -> Vec<f64> {
+        self.origin.to_vec()
+    }
+    pub fn get_spacing(&self) -> Vec<f64> {
+        self.spacing.to_vec()
+    }
+    pub fn get_dims(&self) -> Vec<usize> {
+        self.dims.to_vec()
+    }
+    /// Number of values expected for a field with tuple size one.
+    pub fn num_samples(&self, cell: bool) -> usize {
+        if cell {
+            self.dims.iter().map(|&n| n.max(2) - 1).product()
+        } else {
+            self.dims.iter().product()
+        }
+    }
+    pub fn num_fields(&self) -> usize {
+        self.fields.len()
+    }
+    pub fn field_name(&self, i: usize) -> &str {
+        self.fields[i].name.as_str()
+    }
+    pub fn field_tuple_size(&self, i: usize) -> usize {
+        self.fields[i].tuple_size
+    }
+    pub fn field_is_cell(&self, i: usize) -> bool {
+        self.fields[i].cell
+    }
+    pub fn field_data(&self, i: usize) -> &[f32] {
+        self.fields[i].data.as_slice()
+    }
+    /// Add a field to this grid.
+    ///
+    /// Houdini stores vector fields as three scalar volumes named `<name>.x`, `<name>.y` and
+    /// `<name>.z`. When the last of these is added, the three components are merged into a single
+    /// vector field called `<name>`.
+    ///
+    /// Returns `Error::MeshMismatch` if the size of `data` doesn't match the grid, in which case
+    /// the grid is left unchanged.
+    pub fn add_field(
+        &mut self,
+        name: &str,
+        tuple_size: usize,
+        cell: bool,
+        data: &[f32],
+    ) -> Result<(), Error> {
+        if tuple_size == 0 || data.len() != tuple_size * self.num_samples(cell) {
+            return Err(Error::MeshMismatch);
+        }
+
+        if let Some(base) = name.strip_suffix(".z") {
+            let n = self.fields.len();
+            if tuple_size == 1 && n >= 2 {
+                let is_component = |f: &GridField, suffix: &str| {
+                    f.tuple_size == 1 && f.cell == cell && f.name == format!("{}{}", base, suffix)
+                };
+                if is_component(&self.fields[n - 2], ".x")
+                    && is_component(&self.fields[n - 1], ".y")
+                {
+                    let y = self.fields.pop().unwrap();
+                    let x = self.fields.pop().unwrap();
+                    let data = x
+                        .data
+                        .iter()
+                        .zip(y.data.iter())
+                        .zip(data.iter())
+                        .flat_map(|((&x, &y), &z)| [x, y, z])
+                        .collect();
+                    self.fields.push(GridField {
+                        name: base.to_string(),
+                        tuple_size: 3,
+                        cell,
+                        data,
+                    });
+                    return Ok(());
+                }
+            }
+        }
+
+        self.fields.push(GridField {
+            name: name.to_string(),
+            tuple_size,
+            cell,
+            data: data.to_vec(),
+        });
+        Ok(())
+    }
+}
+
 impl UnstructuredMesh {
     pub fn get_point_coords(&self) -> Vec<f64> {
         bytemuck::cast_slice(self.0.vertex_positions()).to_vec()
     }
+    /// Point coordinates borrowed from the mesh without copying.
+    pub fn point_coords(&self) -> &[f64] {
+        bytemuck::cast_slice(self.0.vertex_positions())
+    }
 
     pub fn get_indices(&self) -> Vec<usize> {
         self.0.indices.data.clone()
     }
+    /// Cell indices borrowed from the mesh without copying.
+    pub fn indices(&self) -> &[usize] {
+        &self.0.indices.data
+    }
 
     pub fn get_counts(&self) -> Vec<usize> {
         self.0.indices.chunks.chunk_offsets.sizes().collect()
@@ -508,11 +1209,27 @@ impl UnstructuredMesh {
     pub fn get_cell_types(&self) -> Vec<CellType> {
         self.0.types.iter().cloned().map(Into::into).collect()
     }
-
-    pub fn attrib_iter(&self, loc: AttribLocation) -> Box<AttribIter<'_>> {
-        Box::new(match loc {
-            AttribLocation::VERTEX => {
-                AttribIter::Vertex(self.0.attrib_dict::<topo::VertexIndex>().iter().peekable())
+    /// Offsets of the first cell of each block of cells, followed by the total number of cells.
+    pub fn cell_offsets(&self) -> &[usize] {
+        self.0.indices.chunks.chunk_offsets.as_ref()
+    }
+    /// Offsets into `indices` of the first index of each block of cells, followed by the total
+    /// number of indices.
+    pub fn block_offsets(&self) -> &[usize] {
+        self.0.indices.chunks.offsets.as_ref()
+    }
+    /// Type of the cells in the given block.
+    pub fn cell_type(&self, block: usize) -> CellType {
+        match self.0.types[block] {
+            mesh::CellType::Triangle => CellType::Triangle,
+            mesh::CellType::Tetrahedron => CellType::Tetrahedron,
+        }
+    }
+
+    pub fn attrib_iter(&self, loc: AttribLocation) -> Box<AttribIter<'_>> {
+        Box::new(match loc {
+            AttribLocation::VERTEX => {
+                AttribIter::Vertex(self.0.attrib_dict::<topo::VertexIndex>().iter().peekable())
             }
             AttribLocation::CELL => {
                 AttribIter::Cell(self.0.attrib_dict::<topo::CellIndex>().iter().peekable())
@@ -532,12 +1249,16 @@ impl TetMesh {
     pub fn get_point_coords(&self) -> Vec<f64> {
         bytemuck::cast_slice(self.0.vertex_positions()).to_vec()
     }
+    /// Point coordinates borrowed from the mesh without copying.
+    pub fn point_coords(&self) -> &[f64] {
+        bytemuck::cast_slice(self.0.vertex_positions())
+    }
     pub fn get_indices(&self) -> Vec<usize> {
-        self.0
-            .indices
-            .iter()
-            .flat_map(|cell| cell.iter().cloned())
-            .collect()
+        self.indices().to_vec()
+    }
+    /// Tetrahedron indices borrowed from the mesh without copying, four per tetrahedron.
+    pub fn indices(&self) -> &[usize] {
+        bytemuck::cast_slice(self.0.indices.as_slice())
     }
 
     pub fn attrib_iter(&self, loc: AttribLocation) -> Box<AttribIter<'_>> {
@@ -559,23 +1280,121 @@ impl TetMesh {
     }
 }
 
+fn polygon_indices<T: meshx::Real>(mesh: &mesh::PolyMesh<T>) -> Vec<usize> {
+    let mut indices = Vec::new();
+
+    for poly in mesh.face_iter() {
+        indices.push(poly.len());
+        for &idx in poly.iter() {
+            indices.push(idx);
+        }
+    }
+
+    indices
+}
+
 impl PolyMesh {
     pub fn get_point_coords(&self) -> Vec<f64> {
         bytemuck::cast_slice(self.0.vertex_positions()).to_vec()
     }
+    /// Point coordinates borrowed from the mesh without copying.
+    pub fn point_coords(&self) -> &[f64] {
+        bytemuck::cast_slice(self.0.vertex_positions())
+    }
     /// Polygon mesh indices is a contiguous set of polygon indices, each in the form:
     /// `n, i_1, i_2, ..., i_n` where `n` is the number of sides on a polygon.
     pub fn get_indices(&self) -> Vec<usize> {
-        let mut indices = Vec::new();
+        polygon_indices(&self.0)
+    }
+    /// Point indices of all faces borrowed from the mesh without copying.
+    pub fn indices(&self) -> &[usize] {
+        &self.0.indices
+    }
+    /// Offsets into `indices` of the first vertex of each face, followed by the total number of
+    /// indices.
+    pub fn offsets(&self) -> &[usize] {
+        &self.0.offsets
+    }
+    pub fn attrib_iter(&self, loc: AttribLocation) -> Box<AttribIter<'_>> {
+        Box::new(match loc {
+            AttribLocation::VERTEX => {
+                AttribIter::Vertex(self.0.attrib_dict::<topo::VertexIndex>().iter().peekable())
+            }
+            AttribLocation::FACE => {
+                AttribIter::Face(self.0.attrib_dict::<topo::FaceIndex>().iter().peekable())
+            }
+            AttribLocation::FACEVERTEX => AttribIter::FaceVertex(
+                self.0
+                    .attrib_dict::<topo::FaceVertexIndex>()
+                    .iter()
+                    .peekable(),
+            ),
+            _ => AttribIter::None,
+        })
+    }
+}
 
-        for poly in self.0.face_iter() {
-            indices.push(poly.len());
-            for &idx in poly.iter() {
-                indices.push(idx);
+impl PolylineMesh {
+    pub fn get_point_coords(&self) -> Vec<f64> {
+        bytemuck::cast_slice(self.0.vertex_positions()).to_vec()
+    }
+    /// Point coordinates borrowed from the mesh without copying.
+    pub fn point_coords(&self) -> &[f64] {
+        bytemuck::cast_slice(self.0.vertex_positions())
+    }
+    /// Polyline indices are a contiguous set of curve indices, each in the form:
+    /// `n, i_1, i_2, ..., i_n` where `n` is the number of vertices in a curve.
+    pub fn get_indices(&self) -> Vec<usize> {
+        polygon_indices(&self.0)
+    }
+    /// Point indices of all faces borrowed from the mesh without copying.
+    pub fn indices(&self) -> &[usize] {
+        &self.0.indices
+    }
+    /// Offsets into `indices` of the first vertex of each face, followed by the total number of
+    /// indices.
+    pub fn offsets(&self) -> &[usize] {
+        &self.0.offsets
+    }
+    pub fn attrib_iter(&self, loc: AttribLocation) -> Box<AttribIter<'_>> {
+        Box::new(match loc {
+            AttribLocation::VERTEX => {
+                AttribIter::Vertex(self.0.attrib_dict::<topo::VertexIndex>().iter().peekable())
             }
-        }
+            AttribLocation::CURVE => {
+                AttribIter::Face(self.0.attrib_dict::<topo::FaceIndex>().iter().peekable())
+            }
+            AttribLocation::CURVEVERTEX => AttribIter::FaceVertex(
+                self.0
+                    .attrib_dict::<topo::FaceVertexIndex>()
+                    .iter()
+                    .peekable(),
+            ),
+            _ => AttribIter::None,
+        })
+    }
+}
 
-        indices
+impl PolyMeshF32 {
+    pub fn get_point_coords(&self) -> Vec<f32> {
+        bytemuck::cast_slice(self.0.vertex_positions()).to_vec()
+    }
+    /// Point coordinates borrowed from the mesh without copying.
+    pub fn point_coords(&self) -> &[f32] {
+        bytemuck::cast_slice(self.0.vertex_positions())
+    }
+    /// Polygon indices in the same format as `PolyMesh::get_indices`.
+    pub fn get_indices(&self) -> Vec<usize> {
+        polygon_indices(&self.0)
+    }
+    /// Point indices of all faces borrowed from the mesh without copying.
+    pub fn indices(&self) -> &[usize] {
+        &self.0.indices
+    }
+    /// Offsets into `indices` of the first vertex of each face, followed by the total number of
+    /// indices.
+    pub fn offsets(&self) -> &[usize] {
+        &self.0.offsets
     }
     pub fn attrib_iter(&self, loc: AttribLocation) -> Box<AttribIter<'_>> {
         Box::new(match loc {
@@ -596,6 +1415,163 @@ impl PolyMesh {
     }
 }
 
+impl PolylineMeshF32 {
+    pub fn get_point_coords(&self) -> Vec<f32> {
+        bytemuck::cast_slice(self.0.vertex_positions()).to_vec()
+    }
+    /// Point coordinates borrowed from the mesh without copying.
+    pub fn point_coords(&self) -> &[f32] {
+        bytemuck::cast_slice(self.0.vertex_positions())
+    }
+    /// Polyline indices in the same format as `PolylineMesh::get_indices`.
+    pub fn get_indices(&self) -> Vec<usize> {
+        polygon_indices(&self.0)
+    }
+    /// Point indices of all faces borrowed from the mesh without copying.
+    pub fn indices(&self) -> &[usize] {
+        &self.0.indices
+    }
+    /// Offsets into `indices` of the first vertex of each face, followed by the total number of
+    /// indices.
+    pub fn offsets(&self) -> &[usize] {
+        &self.0.offsets
+    }
+    pub fn attrib_iter(&self, loc: AttribLocation) -> Box<AttribIter<'_>> {
+        Box::new(match loc {
+            AttribLocation::VERTEX => {
+                AttribIter::Vertex(self.0.attrib_dict::<topo::VertexIndex>().iter().peekable())
+            }
+            AttribLocation::CURVE => {
+                AttribIter::Face(self.0.attrib_dict::<topo::FaceIndex>().iter().peekable())
+            }
+            AttribLocation::CURVEVERTEX => AttribIter::FaceVertex(
+                self.0
+                    .attrib_dict::<topo::FaceVertexIndex>()
+                    .iter()
+                    .peekable(),
+            ),
+            _ => AttribIter::None,
+        })
+    }
+}
+
+impl TetMeshF32 {
+    pub fn get_point_coords(&self) -> Vec<f32> {
+        bytemuck::cast_slice(self.0.vertex_positions()).to_vec()
+    }
+    /// Point coordinates borrowed from the mesh without copying.
+    pub fn point_coords(&self) -> &[f32] {
+        bytemuck::cast_slice(self.0.vertex_positions())
+    }
+    pub fn get_indices(&self) -> Vec<usize> {
+        self.indices().to_vec()
+    }
+    /// Tetrahedron indices borrowed from the mesh without copying, four per tetrahedron.
+    pub fn indices(&self) -> &[usize] {
+        bytemuck::cast_slice(self.0.indices.as_slice())
+    }
+    pub fn attrib_iter(&self, loc: AttribLocation) -> Box<AttribIter<'_>> {
+        Box::new(match loc {
+            AttribLocation::VERTEX => {
+                AttribIter::Vertex(self.0.attrib_dict::<topo::VertexIndex>().iter().peekable())
+            }
+            AttribLocation::CELL => {
+                AttribIter::Cell(self.0.attrib_dict::<topo::CellIndex>().iter().peekable())
+            }
+            AttribLocation::CELLVERTEX => AttribIter::CellVertex(
+                self.0
+                    .attrib_dict::<topo::CellVertexIndex>()
+                    .iter()
+                    .peekable(),
+            ),
+            _ => AttribIter::None,
+        })
+    }
+}
+
+impl PointCloudF32 {
+    pub fn get_point_coords(&self) -> Vec<f32> {
+        bytemuck::cast_slice(self.0.vertex_positions()).to_vec()
+    }
+    /// Point coordinates borrowed from the mesh without copying.
+    pub fn point_coords(&self) -> &[f32] {
+        bytemuck::cast_slice(self.0.vertex_positions())
+    }
+    pub fn attrib_iter(&self, loc: AttribLocation) -> Box<AttribIter<'_>> {
+        Box::new(match loc {
+            AttribLocation::VERTEX => {
+                AttribIter::Vertex(self.0.attrib_dict::<topo::VertexIndex>().iter().peekable())
+            }
+            _ => AttribIter::None,
+        })
+    }
+}
+
+impl UnstructuredMeshF32 {
+    pub fn get_point_coords(&self) -> Vec<f32> {
+        bytemuck::cast_slice(self.0.vertex_positions()).to_vec()
+    }
+    /// Point coordinates borrowed from the mesh without copying.
+    pub fn point_coords(&self) -> &[f32] {
+        bytemuck::cast_slice(self.0.vertex_positions())
+    }
+    pub fn get_indices(&self) -> Vec<usize> {
+        self.0.indices.data.clone()
+    }
+    /// Cell indices borrowed from the mesh without copying.
+    pub fn indices(&self) -> &[usize] {
+        &self.0.indices.data
+    }
+    pub fn get_counts(&self) -> Vec<usize> {
+        self.0.indices.chunks.chunk_offsets.sizes().collect()
+    }
+    pub fn get_sizes(&self) -> Vec<usize> {
+        self.0
+            .indices
+            .chunks
+            .chunk_offsets
+            .sizes()
+            .zip(self.0.indices.chunks.offsets.sizes())
+            .map(|(num_elements, num_indices)| num_indices / num_elements)
+            .collect()
+    }
+    pub fn get_cell_types(&self) -> Vec<CellType> {
+        self.0.types.iter().cloned().map(Into::into).collect()
+    }
+    /// Offsets of the first cell of each block of cells, as in `UnstructuredMesh::cell_offsets`.
+    pub fn cell_offsets(&self) -> &[usize] {
+        self.0.indices.chunks.chunk_offsets.as_ref()
+    }
+    /// Offsets into `indices` of each block of cells, as in `UnstructuredMesh::block_offsets`.
+    pub fn block_offsets(&self) -> &[usize] {
+        self.0.indices.chunks.offsets.as_ref()
+    }
+    /// Type of the cells in the given block.
+    pub fn cell_type(&self, block: usize) -> CellType {
+        match self.0.types[block] {
+            mesh::CellType::Triangle => CellType::Triangle,
+            mesh::CellType::Tetrahedron => CellType::Tetrahedron,
+        }
+    }
+    pub fn attrib_iter(&self, loc: AttribLocation) -> Box<AttribIter<'_>> {
+        Box::new(match loc {
+            AttribLocation::VERTEX => {
+                AttribIter::Vertex(self.0.attrib_dict::<topo::VertexIndex>().iter().peekable())
+            }
+            AttribLocation::CELL => {
+                AttribIter::Cell(self.0.attrib_dict::<topo::CellIndex>().iter().peekable())
+            }
+            AttribLocation::CELLVERTEX => AttribIter::CellVertex(
+                self.0
+                    .attrib_dict::<topo::CellVertexIndex>()
+                    .iter()
+                    .peekable(),
+            ),
+            _ => AttribIter::None,
+        })
+    }
+}
+
 pub enum AttribIter<'a> {
     Vertex(Peekable<Iter<'a, String, attrib::Attribute<topo::VertexIndex>>>),
     Face(Peekable<Iter<'a, String, attrib::Attribute<topo::FaceIndex>>>),
@@ -765,6 +1741,30 @@ fn attrib_type_id<I>(attrib: &attrib::Attribute<I>) -> DataType {
     }
 }
 
+/// Number of components in each element of the given attribute, or zero if unsupported.
+fn attrib_tuple_size<I>(attrib: &attrib::Attribute<I>) -> usize {
+    match attrib.data.element_type_id() {
+        x if impl_supported_sizes!(x, i8, i32, i64, f32, f64, Arc<String>) => 1,
+        x if impl_supported_sizes!(x, 1, i8, i32, i64, f32, f64) => 1,
+        x if impl_supported_sizes!(x, 2, i8, i32, i64, f32, f64) => 2,
+        x if impl_supported_sizes!(x, 3, i8, i32, i64, f32, f64) => 3,
+        x if impl_supported_sizes!(x, 4, i8, i32, i64, f32, f64) => 4,
+        x if impl_supported_sizes!(x, 5, i8, i32, i64, f32, f64) => 5,
+        x if impl_supported_sizes!(x, 6, i8, i32, i64, f32, f64) => 6,
+        x if impl_supported_sizes!(x, 7, i8, i32, i64, f32, f64) => 7,
+        x if impl_supported_sizes!(x, 8, i8, i32, i64, f32, f64) => 8,
+        x if impl_supported_sizes!(x, 9, i8, i32, i64, f32, f64) => 9,
+        x if impl_supported_sizes!(x, 10, i8, i32, i64, f32, f64) => 10,
+        x if impl_supported_sizes!(x, 11, i8, i32, i64, f32, f64) => 11,
+        x if impl_supported_sizes!(x, 12, i8, i32, i64, f32, f64) => 12,
+        x if impl_supported_sizes!(x, 13, i8, i32, i64, f32, f64) => 13,
+        x if impl_supported_sizes!(x, 14, i8, i32, i64, f32, f64) => 14,
+        x if impl_supported_sizes!(x, 15, i8, i32, i64, f32, f64) => 15,
+        x if impl_supported_sizes!(x, 16, i8, i32, i64, f32, f64) => 16,
+        _ => 0,
+    }
+}
+
 trait AttribFlatArray {
     fn maybe_str_array<I>(
         _attrib: &attrib::Attribute<I>,
@@ -779,26 +1779,7 @@ trait AttribFlatArray {
     where
         Self: Sized + Clone + 'static,
     {
-        let tuple_size = match attrib.data.element_type_id() {
-            x if impl_supported_sizes!(x, i8, i32, i64, f32, f64, Arc<String>) => 1,
-            x if impl_supported_sizes!(x, 1, i8, i32, i64, f32, f64) => 1,
-            x if impl_supported_sizes!(x, 2, i8, i32, i64, f32, f64) => 2,
-            x if impl_supported_sizes!(x, 3, i8, i32, i64, f32, f64) => 3,
-            x if impl_supported_sizes!(x, 4, i8, i32, i64, f32, f64) => 4,
-            x if impl_supported_sizes!(x, 5, i8, i32, i64, f32, f64) => 5,
-            x if impl_supported_sizes!(x, 6, i8, i32, i64, f32, f64) => 6,
-            x if impl_supported_sizes!(x, 7, i8, i32, i64, f32, f64) => 7,
-            x if impl_supported_sizes!(x, 8, i8, i32, i64, f32, f64) => 8,
-            x if impl_supported_sizes!(x, 9, i8, i32, i64, f32, f64) => 9,
-            x if impl_supported_sizes!(x, 10, i8, i32, i64, f32, f64) => 10,
-            x if impl_supported_sizes!(x, 11, i8, i32, i64, f32, f64) => 11,
-            x if impl_supported_sizes!(x, 12, i8, i32, i64, f32, f64) => 12,
-            x if impl_supported_sizes!(x, 13, i8, i32, i64, f32, f64) => 13,
-            x if impl_supported_sizes!(x, 14, i8, i32, i64, f32, f64) => 14,
-            x if impl_supported_sizes!(x, 15, i8, i32, i64, f32, f64) => 15,
-            x if impl_supported_sizes!(x, 16, i8, i32, i64, f32, f64) => 16,
-            _ => 0,
-        };
+        let tuple_size = attrib_tuple_size(attrib);
 
         // Strings are stored in indirect attributes and cannot be directly cast.
         if let Some(str_array) = Self::maybe_str_array(attrib, tuple_size) {
@@ -831,6 +1812,48 @@ trait AttribFlatArray {
         }
     }
 }
+macro_rules! cast_to_slice {
+    ($type:ident, $data:ident) => {
+        $data.as_slice::<$type>().unwrap_or(&[])
+    };
+    ($type:ident, $data:ident, $tuple_size:expr) => {
+        $data
+            .as_slice::<[$type; $tuple_size]>()
+            .map(|s| bytemuck::cast_slice(s))
+            .unwrap_or(&[])
+    };
+}
+
+/// Flat view into numeric attribute data, borrowed directly from the mesh.
+trait AttribFlatSlice: bytemuck::Pod {
+    fn attrib_flat_slice<I>(attrib: &attrib::Attribute<I>) -> &[Self] {
+        match attrib_tuple_size(attrib) {
+            1 => cast_to_slice!(Self, attrib),
+            2 => cast_to_slice!(Self, attrib, 2),
+            3 => cast_to_slice!(Self, attrib, 3),
+            4 => cast_to_slice!(Self, attrib, 4),
+            5 => cast_to_slice!(Self, attrib, 5),
+            6 => cast_to_slice!(Self, attrib, 6),
+            7 => cast_to_slice!(Self, attrib, 7),
+            8 => cast_to_slice!(Self, attrib, 8),
+            9 => cast_to_slice!(Self, attrib, 9),
+            10 => cast_to_slice!(Self, attrib, 10),
+            11 => cast_to_slice!(Self, attrib, 11),
+            12 => cast_to_slice!(Self, attrib, 12),
+            13 => cast_to_slice!(Self, attrib, 13),
+            14 => cast_to_slice!(Self, attrib, 14),
+            15 => cast_to_slice!(Self, attrib, 15),
+            16 => cast_to_slice!(Self, attrib, 16),
+            _ => &[],
+        }
+    }
+}
+impl AttribFlatSlice for i8 {}
+impl AttribFlatSlice for i32 {}
+impl AttribFlatSlice for i64 {}
+impl AttribFlatSlice for f32 {}
+impl AttribFlatSlice for f64 {}
+
 impl AttribFlatArray for i8 {}
 impl AttribFlatArray for i32 {}
 impl AttribFlatArray for i64 {}
@@ -874,7 +1897,49 @@ macro_rules! impl_get_attrib_data {
     }};
 }
 
+macro_rules! impl_get_attrib_slice {
+    ($attrib_data:ident) => {{
+        match $attrib_data.data {
+            AttribData::Vertex(data) => AttribFlatSlice::attrib_flat_slice(data),
+            AttribData::Face(data) => AttribFlatSlice::attrib_flat_slice(data),
+            AttribData::Cell(data) => AttribFlatSlice::attrib_flat_slice(data),
+            AttribData::FaceVertex(data) => AttribFlatSlice::attrib_flat_slice(data),
+            AttribData::CellVertex(data) => AttribFlatSlice::attrib_flat_slice(data),
+            AttribData::None => &[],
+        }
+    }};
+}
+
 impl<'a> Attribute<'a> {
+    /// Number of components in each element of this attribute.
+    pub fn tuple_size(&self) -> usize {
+        match self.data {
+            AttribData::Vertex(data) => attrib_tuple_size(data),
+            AttribData::Face(data) => attrib_tuple_size(data),
+            AttribData::Cell(data) => attrib_tuple_size(data),
+            AttribData::FaceVertex(data) => attrib_tuple_size(data),
+            AttribData::CellVertex(data) => attrib_tuple_size(data),
+            AttribData::None => 0,
+        }
+    }
+    /// Flat attribute data borrowed from the mesh without copying.
+    ///
+    /// Returns an empty slice if the attribute doesn't store `i8` values.
+    pub fn data_i8(&self) -> &'a [i8] {
+        impl_get_attrib_slice!(self)
+    }
+    pub fn data_i32(&self) -> &'a [i32] {
+        impl_get_attrib_slice!(self)
+    }
+    pub fn data_i64(&self) -> &'a [i64] {
+        impl_get_attrib_slice!(self)
+    }
+    pub fn data_f32(&self) -> &'a [f32] {
+        impl_get_attrib_slice!(self)
+    }
+    pub fn data_f64(&self) -> &'a [f64] {
+        impl_get_attrib_slice!(self)
+    }
     pub fn get_data_i8(&self) -> TupleVecI8 {
         impl_get_attrib_data!(self).into()
     }
@@ -909,33 +1974,61 @@ pub fn make_pointcloud(coords: &[f64]) -> Box<PointCloud> {
     Box::new(PointCloud(mesh::PointCloud::new(verts)))
 }
 
+/// Create an empty grid.
+///
+/// Grid origin, spacing and dimensions must have exactly 3 components, otherwise
+/// `Error::MeshMismatch` is returned.
+pub fn make_grid(origin: &[f64], spacing: &[f64], dims: &[usize]) -> Result<Box<GridMesh>, Error> {
+    use std::convert::TryInto;
+    match (origin.try_into(), spacing.try_into(), dims.try_into()) {
+        (Ok(origin), Ok(spacing), Ok(dims)) => Ok(Box::new(GridMesh::new(origin, spacing, dims))),
+        _ => Err(Error::MeshMismatch),
+    }
+}
+
+macro_rules! make_cells_impl {
+    ($hr_mesh:ident, $coords:ident, $indices:ident, $types:ident, $scalar:ty) => {{
+        use std::convert::TryInto;
+        // check invariants
+        assert!(
+            $coords.len() % 3 == 0,
+            "Given coordinate array size is not a multiple of 3."
+        );
+
+        let verts: Vec<[$scalar; 3]> = $coords
+            .chunks_exact(3)
+            .map(|chunk| chunk.try_into().unwrap())
+            .collect();
+
+        Box::new($hr_mesh(mesh::Mesh::from_cells_with_type(
+            verts,
+            $indices,
+            |i| $types[i].into(),
+        )))
+    }};
+}
+
 pub fn make_unstructured_mesh(
     coords: &[f64],
     indices: &[usize],
     types: &[CellType],
 ) -> Box<UnstructuredMesh> {
-    use std::convert::TryInto;
-    // check invariants
-    assert!(
-        coords.len() % 3 == 0,
-        "Given coordinate array size is not a multiple of 3."
-    );
-
-    let indices = indices;
-    let verts: Vec<[f64; 3]> = coords
-        .chunks_exact(3)
-        .map(|chunk| chunk.try_into().unwrap())
-        .collect();
+    make_cells_impl!(UnstructuredMesh, coords, indices, types, f64)
+}
 
-    Box::new(UnstructuredMesh(mesh::Mesh::from_cells_with_type(
-        verts,
-        indices,
-        |i| types[i].into(),
-    )))
+pub fn make_unstructured_mesh_f32(
+    coords: &[f32],
+    indices: &[usize],
+    types: &[CellType],
+) -> Box<UnstructuredMeshF32> {
+    make_cells_impl!(UnstructuredMeshF32, coords, indices, types, f32)
 }
 
 macro_rules! make_mesh_impl {
-    ($hr_mesh:ident, $mesh_ty:ident, $coords:ident, $convert:expr) => {{
+    ($hr_mesh:ident, $mesh_ty:ident, $coords:ident, $convert:expr) => {
+        make_mesh_impl!($hr_mesh, $mesh_ty, $coords, $convert, f64)
+    };
+    ($hr_mesh:ident, $mesh_ty:ident, $coords:ident, $convert:expr, $scalar:ty) => {{
         use std::convert::TryInto;
         // check invariants
         assert!(
@@ -944,7 +2037,7 @@ macro_rules! make_mesh_impl {
         );
 
         let indices = $convert;
-        let verts: Vec<[f64; 3]> = $coords
+        let verts: Vec<[$scalar; 3]> = $coords
             .chunks_exact(3)
             .map(|chunk| chunk.try_into().unwrap())
             .collect();
@@ -957,6 +2050,10 @@ pub fn make_polymesh(coords: &[f64], indices: &[usize]) -> Box<PolyMesh> {
     make_mesh_impl!(PolyMesh, PolyMesh, coords, indices)
 }
 
+pub fn make_polylines(coords: &[f64], indices: &[usize]) -> Box<PolylineMesh> {
+    make_mesh_impl!(PolylineMesh, PolyMesh, coords, indices)
+}
+
 pub fn make_tetmesh(coords: &[f64], indices: &[usize]) -> Box<TetMesh> {
     make_mesh_impl!(
         TetMesh,
@@ -966,13 +2063,45 @@ pub fn make_tetmesh(coords: &[f64], indices: &[usize]) -> Box<TetMesh> {
     )
 }
 
-#[derive(Debug, PartialEq)]
-pub enum Error {
-    Attrib(attrib::Error),
-    Internal,
-    MeshMismatch,
-    AttribNotFound,
-}
+pub fn make_pointcloud_f32(coords: &[f32]) -> Box<PointCloudF32> {
+    use std::convert::TryInto;
+    // check invariants
+    assert!(
+        coords.len() % 3 == 0,
+        "Given coordinate array size is not a multiple of 3."
+    );
+    let verts: Vec<[f32; 3]> = coords
+        .chunks_exact(3)
+        .map(|chunk| chunk.try_into().unwrap())
+        .collect();
+    Box::new(PointCloudF32(mesh::PointCloud::new(verts)))
+}
+
+pub fn make_polymesh_f32(coords: &[f32], indices: &[usize]) -> Box<PolyMeshF32> {
+    make_mesh_impl!(PolyMeshF32, PolyMesh, coords, indices, f32)
+}
+
+pub fn make_polylines_f32(coords: &[f32], indices: &[usize]) -> Box<PolylineMeshF32> {
+    make_mesh_impl!(PolylineMeshF32, PolyMesh, coords, indices, f32)
+}
+
+pub fn make_tetmesh_f32(coords: &[f32], indices: &[usize]) -> Box<TetMeshF32> {
+    make_mesh_impl!(
+        TetMeshF32,
+        TetMesh,
+        coords,
+        bytemuck::cast_slice(indices).to_vec(),
+        f32
+    )
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    Attrib(attrib::Error),
+    Internal,
+    MeshMismatch,
+    AttribNotFound,
+}
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -1016,6 +2145,11 @@ macro_rules! impl_add_attrib {
         let vec = $data.to_vec();
         impl_add_attrib!(_impl_surface $mesh, $loc, $name, vec);
     };
+    (_impl PolylineMesh, $data_type:ty, $mesh:ident,
+     $data:ident, $name:ident, $loc:ident) => {
+        let vec = $data.to_vec();
+        impl_add_attrib!(_impl_curve $mesh, $loc, $name, vec);
+    };
     (_impl TetMesh, $data_type:ty, $mesh:ident,
      $data:ident, $name:ident, $loc:ident) => {
         let vec = $data.to_vec();
@@ -1036,11 +2170,66 @@ macro_rules! impl_add_attrib {
         let vec = bytemuck::cast_slice::<_, [$data_type; $tuple_size]>($data).to_vec();
         impl_add_attrib!(_impl_surface $mesh, $loc, $name, vec);
     };
+    (_impl PolylineMesh, $data_type:ty, $tuple_size:expr, $mesh:ident,
+     $data:ident, $name:ident, $loc:ident) => {
+        let vec = bytemuck::cast_slice::<_, [$data_type; $tuple_size]>($data).to_vec();
+        impl_add_attrib!(_impl_curve $mesh, $loc, $name, vec);
+    };
     (_impl TetMesh, $data_type:ty, $tuple_size:expr, $mesh:ident,
      $data:ident, $name:ident, $loc:ident) => {
         let vec = bytemuck::cast_slice::<_, [$data_type; $tuple_size]>($data).to_vec();
         impl_add_attrib!(_impl_volume $mesh, $loc, $name, vec);
     };
+    (_impl PolyMeshF32, $data_type:ty, $mesh:ident,
+     $data:ident, $name:ident, $loc:ident) => {
+        let vec = $data.to_vec();
+        impl_add_attrib!(_impl_surface $mesh, $loc, $name, vec);
+    };
+    (_impl PolylineMeshF32, $data_type:ty, $mesh:ident,
+     $data:ident, $name:ident, $loc:ident) => {
+        let vec = $data.to_vec();
+        impl_add_attrib!(_impl_curve $mesh, $loc, $name, vec);
+    };
+    (_impl TetMeshF32, $data_type:ty, $mesh:ident,
+     $data:ident, $name:ident, $loc:ident) => {
+        let vec = $data.to_vec();
+        impl_add_attrib!(_impl_volume $mesh, $loc, $name, vec);
+    };
+    (_impl PointCloudF32, $data_type:ty, $mesh:ident,
+     $data:ident, $name:ident, $loc:ident) => {
+        let vec = $data.to_vec();
+        impl_add_attrib!(_impl_points $mesh, $loc, $name, vec);
+    };
+    (_impl UnstructuredMeshF32, $data_type:ty, $mesh:ident,
+     $data:ident, $name:ident, $loc:ident) => {
+        let vec = $data.to_vec();
+        impl_add_attrib!(_impl_volume $mesh, $loc, $name, vec);
+    };
+    (_impl PolyMeshF32, $data_type:ty, $tuple_size:expr, $mesh:ident,
+     $data:ident, $name:ident, $loc:ident) => {
+        let vec = bytemuck::cast_slice::<_, [$data_type; $tuple_size]>($data).to_vec();
+        impl_add_attrib!(_impl_surface $mesh, $loc, $name, vec);
+    };
+    (_impl PolylineMeshF32, $data_type:ty, $tuple_size:expr, $mesh:ident,
+     $data:ident, $name:ident, $loc:ident) => {
+        let vec = bytemuck::cast_slice::<_, [$data_type; $tuple_size]>($data).to_vec();
+        impl_add_attrib!(_impl_curve $mesh, $loc, $name, vec);
+    };
+    (_impl TetMeshF32, $data_type:ty, $tuple_size:expr, $mesh:ident,
+     $data:ident, $name:ident, $loc:ident) => {
+        let vec = bytemuck::cast_slice::<_, [$data_type; $tuple_size]>($data).to_vec();
+        impl_add_attrib!(_impl_volume $mesh, $loc, $name, vec);
+    };
+    (_impl PointCloudF32, $data_type:ty, $tuple_size:expr, $mesh:ident,
+     $data:ident, $name:ident, $loc:ident) => {
+        let vec = bytemuck::cast_slice::<_, [$data_type; $tuple_size]>($data).to_vec();
+        impl_add_attrib!(_impl_points $mesh, $loc, $name, vec);
+    };
+    (_impl UnstructuredMeshF32, $data_type:ty, $tuple_size:expr, $mesh:ident,
+     $data:ident, $name:ident, $loc:ident) => {
+        let vec = bytemuck::cast_slice::<_, [$data_type; $tuple_size]>($data).to_vec();
+        impl_add_attrib!(_impl_volume $mesh, $loc, $name, vec);
+    };
     // Points only attributes
     (_impl_points $mesh:ident, $loc:ident, $name:ident, $vec:ident) => {
         {
@@ -1075,6 +2264,29 @@ macro_rules! impl_add_attrib {
             };
         }
     };
+    // Curves are stored as open polygons, so curve attributes live on faces.
+    (_impl_curve $mesh:ident, $loc:ident, $name:ident, $vec:ident) => {
+        {
+            match $loc {
+                AttribLocation::VERTEX => {
+                    if let Err(error) = $mesh.0.insert_attrib_data::<_,topo::VertexIndex>($name, $vec) {
+                        println!("Warning: failed to add attribute \"{}\" at {:?}, with error: {:?}", $name, $loc, error);
+                    }
+                },
+                AttribLocation::CURVE => {
+                    if let Err(error) = $mesh.0.insert_attrib_data::<_,topo::FaceIndex>($name, $vec) {
+                        println!("Warning: failed to add attribute \"{}\" at {:?}, with error: {:?}", $name, $loc, error);
+                    }
+                },
+                AttribLocation::CURVEVERTEX => {
+                    if let Err(error) = $mesh.0.insert_attrib_data::<_,topo::FaceVertexIndex>($name, $vec) {
+                        println!("Warning: failed to add attribute \"{}\" at {:?}, with error: {:?}", $name, $loc, error);
+                    }
+                },
+                _ => (),
+            };
+        }
+    };
     // Volume type meshes like tet and hex meshes have cell attributes. This is
     // also true for mixed meshes where faces are treated as cells.
     (_impl_volume $mesh:ident, $loc:ident, $name:ident, $vec:ident) => {
@@ -1159,6 +2371,29 @@ macro_rules! impl_add_attrib {
             }
         }
     };
+    // Curve meshes store curve attributes on faces of open polygons.
+    (_impl_str_curve $mesh:ident, $loc:ident, $name:ident, $update_fn:expr) => {
+        {
+            match $loc {
+                AttribLocation::VERTEX => {
+                    $mesh.0.insert_indirect_attrib::<_, topo::VertexIndex>($name, String::new())
+                        .and_then(|(attrib, cache)| { attrib.indirect_update_with($update_fn, cache)?; Ok(()) })
+                        .map_err(Error::from)
+                },
+                AttribLocation::CURVE => {
+                    $mesh.0.insert_indirect_attrib::<_, topo::FaceIndex>($name, String::new())
+                        .and_then(|(attrib, cache)| { attrib.indirect_update_with($update_fn, cache)?; Ok(()) })
+                        .map_err(Error::from)
+                },
+                AttribLocation::CURVEVERTEX => {
+                    $mesh.0.insert_indirect_attrib::<_, topo::FaceVertexIndex>($name, String::new())
+                        .and_then(|(attrib, cache)| { attrib.indirect_update_with($update_fn, cache)?; Ok(()) })
+                        .map_err(Error::from)
+                },
+                _ => Err(Error::Internal),
+            }
+        }
+    };
     // Volume type meshes like tet and hex meshes have cell attributes.
     (_impl_str_volume $mesh:ident, $loc:ident, $name:ident, $update_fn:expr) => {
         {
@@ -1192,9 +2427,27 @@ macro_rules! impl_add_attrib {
     (_impl_str PolyMesh, $mesh:ident, $loc:ident, $name:ident, $update_fn:expr) => {
         impl_add_attrib!(_impl_str_surface $mesh, $loc, $name, $update_fn)
     };
+    (_impl_str PolylineMesh, $mesh:ident, $loc:ident, $name:ident, $update_fn:expr) => {
+        impl_add_attrib!(_impl_str_curve $mesh, $loc, $name, $update_fn)
+    };
     (_impl_str TetMesh, $mesh:ident, $loc:ident, $name:ident, $update_fn:expr) => {
         impl_add_attrib!(_impl_str_volume $mesh, $loc, $name, $update_fn)
     };
+    (_impl_str PointCloudF32, $mesh:ident, $loc:ident, $name:ident, $update_fn:expr) => {
+        impl_add_attrib!(_impl_str_points $mesh, $loc, $name, $update_fn)
+    };
+    (_impl_str PolyMeshF32, $mesh:ident, $loc:ident, $name:ident, $update_fn:expr) => {
+        impl_add_attrib!(_impl_str_surface $mesh, $loc, $name, $update_fn)
+    };
+    (_impl_str TetMeshF32, $mesh:ident, $loc:ident, $name:ident, $update_fn:expr) => {
+        impl_add_attrib!(_impl_str_volume $mesh, $loc, $name, $update_fn)
+    };
+    (_impl_str PolylineMeshF32, $mesh:ident, $loc:ident, $name:ident, $update_fn:expr) => {
+        impl_add_attrib!(_impl_str_curve $mesh, $loc, $name, $update_fn)
+    };
+    (_impl_str UnstructuredMeshF32, $mesh:ident, $loc:ident, $name:ident, $update_fn:expr) => {
+        impl_add_attrib!(_impl_str_volume $mesh, $loc, $name, $update_fn)
+    };
     // Implementation for string attributes
     ($mesh_type:ident, $mesh:ident,
      $loc:ident, $name:ident, $tuple_size:ident,
@@ -1409,6 +2662,68 @@ impl PolyMesh {
     }
 }
 
+impl PolylineMesh {
+    pub fn add_attrib_f32(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        data: &[f32],
+    ) {
+        impl_add_attrib!(PolylineMesh, self, loc, name, tuple_size, data: f32);
+    }
+
+    pub fn add_attrib_f64(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        data: &[f64],
+    ) {
+        impl_add_attrib!(PolylineMesh, self, loc, name, tuple_size, data: f64);
+    }
+
+    pub fn add_attrib_i8(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        data: &[i8],
+    ) {
+        impl_add_attrib!(PolylineMesh, self, loc, name, tuple_size, data: i8);
+    }
+
+    pub fn add_attrib_i32(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        data: &[i32],
+    ) {
+        impl_add_attrib!(PolylineMesh, self, loc, name, tuple_size, data: i32);
+    }
+
+    pub fn add_attrib_i64(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        data: &[i64],
+    ) {
+        impl_add_attrib!(PolylineMesh, self, loc, name, tuple_size, data: i64);
+    }
+    pub fn add_attrib_str(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        strings: &[&str],
+        data: &[i64],
+    ) {
+        impl_add_attrib!(PolylineMesh, self, loc, name, tuple_size, strings, data);
+    }
+}
+
 impl TetMesh {
     pub fn add_attrib_f32(
         &mut self,
@@ -1471,54 +2786,419 @@ impl TetMesh {
     }
 }
 
-#[derive(Clone, PartialEq, Debug)]
-pub enum Mesh {
-    TetMesh(TetMesh),
-    PolyMesh(PolyMesh),
-    PointCloud(PointCloud),
-    UnstructuredMesh(UnstructuredMesh),
-    None,
-}
-
-impl<M: Into<Mesh>> From<Option<M>> for Mesh {
-    fn from(m: Option<M>) -> Self {
-        m.map(|x| x.into()).unwrap_or(Mesh::None)
-    }
-}
-
-impl From<mesh::Mesh<f64>> for Mesh {
-    fn from(m: mesh::Mesh<f64>) -> Self {
-        Mesh::UnstructuredMesh(m.into())
+impl PolyMeshF32 {
+    pub fn add_attrib_f32(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        data: &[f32],
+    ) {
+        impl_add_attrib!(PolyMeshF32, self, loc, name, tuple_size, data: f32);
     }
-}
 
-impl From<mesh::TetMesh<f64>> for Mesh {
-    fn from(m: mesh::TetMesh<f64>) -> Self {
-        Mesh::TetMesh(m.into())
+    pub fn add_attrib_f64(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        data: &[f64],
+    ) {
+        impl_add_attrib!(PolyMeshF32, self, loc, name, tuple_size, data: f64);
     }
-}
 
-impl From<mesh::PolyMesh<f64>> for Mesh {
-    fn from(m: mesh::PolyMesh<f64>) -> Self {
-        Mesh::PolyMesh(m.into())
+    pub fn add_attrib_i8(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        data: &[i8],
+    ) {
+        impl_add_attrib!(PolyMeshF32, self, loc, name, tuple_size, data: i8);
     }
-}
 
-impl From<mesh::PointCloud<f64>> for Mesh {
-    fn from(m: mesh::PointCloud<f64>) -> Self {
-        Mesh::PointCloud(m.into())
+    pub fn add_attrib_i32(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        data: &[i32],
+    ) {
+        impl_add_attrib!(PolyMeshF32, self, loc, name, tuple_size, data: i32);
     }
-}
 
-impl From<UnstructuredMesh> for Mesh {
-    fn from(m: UnstructuredMesh) -> Self {
-        Mesh::UnstructuredMesh(m)
+    pub fn add_attrib_i64(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        data: &[i64],
+    ) {
+        impl_add_attrib!(PolyMeshF32, self, loc, name, tuple_size, data: i64);
     }
-}
 
-impl From<TetMesh> for Mesh {
-    fn from(m: TetMesh) -> Self {
-        Mesh::TetMesh(m)
+    pub fn add_attrib_str(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        strings: &[&str],
+        data: &[i64],
+    ) {
+        impl_add_attrib!(PolyMeshF32, self, loc, name, tuple_size, strings, data);
+    }
+}
+
+impl PolylineMeshF32 {
+    pub fn add_attrib_f32(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        data: &[f32],
+    ) {
+        impl_add_attrib!(PolylineMeshF32, self, loc, name, tuple_size, data: f32);
+    }
+
+    pub fn add_attrib_f64(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        data: &[f64],
+    ) {
+        impl_add_attrib!(PolylineMeshF32, self, loc, name, tuple_size, data: f64);
+    }
+
+    pub fn add_attrib_i8(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        data: &[i8],
+    ) {
+        impl_add_attrib!(PolylineMeshF32, self, loc, name, tuple_size, data: i8);
+    }
+
+    pub fn add_attrib_i32(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        data: &[i32],
+    ) {
+        impl_add_attrib!(PolylineMeshF32, self, loc, name, tuple_size, data: i32);
+    }
+
+    pub fn add_attrib_i64(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        data: &[i64],
+    ) {
+        impl_add_attrib!(PolylineMeshF32, self, loc, name, tuple_size, data: i64);
+    }
+
+    pub fn add_attrib_str(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        strings: &[&str],
+        data: &[i64],
+    ) {
+        impl_add_attrib!(PolylineMeshF32, self, loc, name, tuple_size, strings, data);
+    }
+}
+
+impl TetMeshF32 {
+    pub fn add_attrib_f32(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        data: &[f32],
+    ) {
+        impl_add_attrib!(TetMeshF32, self, loc, name, tuple_size, data: f32);
+    }
+
+    pub fn add_attrib_f64(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        data: &[f64],
+    ) {
+        impl_add_attrib!(TetMeshF32, self, loc, name, tuple_size, data: f64);
+    }
+
+    pub fn add_attrib_i8(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        data: &[i8],
+    ) {
+        impl_add_attrib!(TetMeshF32, self, loc, name, tuple_size, data: i8);
+    }
+
+    pub fn add_attrib_i32(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        data: &[i32],
+    ) {
+        impl_add_attrib!(TetMeshF32, self, loc, name, tuple_size, data: i32);
+    }
+
+    pub fn add_attrib_i64(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        data: &[i64],
+    ) {
+        impl_add_attrib!(TetMeshF32, self, loc, name, tuple_size, data: i64);
+    }
+
+    pub fn add_attrib_str(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        strings: &[&str],
+        data: &[i64],
+    ) {
+        impl_add_attrib!(TetMeshF32, self, loc, name, tuple_size, strings, data);
+    }
+}
+
+impl PointCloudF32 {
+    pub fn add_attrib_f32(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        data: &[f32],
+    ) {
+        impl_add_attrib!(PointCloudF32, self, loc, name, tuple_size, data: f32);
+    }
+
+    pub fn add_attrib_f64(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        data: &[f64],
+    ) {
+        impl_add_attrib!(PointCloudF32, self, loc, name, tuple_size, data: f64);
+    }
+
+    pub fn add_attrib_i8(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        data: &[i8],
+    ) {
+        impl_add_attrib!(PointCloudF32, self, loc, name, tuple_size, data: i8);
+    }
+
+    pub fn add_attrib_i32(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        data: &[i32],
+    ) {
+        impl_add_attrib!(PointCloudF32, self, loc, name, tuple_size, data: i32);
+    }
+
+    pub fn add_attrib_i64(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        data: &[i64],
+    ) {
+        impl_add_attrib!(PointCloudF32, self, loc, name, tuple_size, data: i64);
+    }
+
+    pub fn add_attrib_str(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        strings: &[&str],
+        data: &[i64],
+    ) {
+        impl_add_attrib!(PointCloudF32, self, loc, name, tuple_size, strings, data);
+    }
+}
+
+impl UnstructuredMeshF32 {
+    pub fn add_attrib_f32(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        data: &[f32],
+    ) {
+        impl_add_attrib!(UnstructuredMeshF32, self, loc, name, tuple_size, data: f32);
+    }
+
+    pub fn add_attrib_f64(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        data: &[f64],
+    ) {
+        impl_add_attrib!(UnstructuredMeshF32, self, loc, name, tuple_size, data: f64);
+    }
+
+    pub fn add_attrib_i8(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        data: &[i8],
+    ) {
+        impl_add_attrib!(UnstructuredMeshF32, self, loc, name, tuple_size, data: i8);
+    }
+
+    pub fn add_attrib_i32(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        data: &[i32],
+    ) {
+        impl_add_attrib!(UnstructuredMeshF32, self, loc, name, tuple_size, data: i32);
+    }
+
+    pub fn add_attrib_i64(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        data: &[i64],
+    ) {
+        impl_add_attrib!(UnstructuredMeshF32, self, loc, name, tuple_size, data: i64);
+    }
+
+    pub fn add_attrib_str(
+        &mut self,
+        loc: AttribLocation,
+        name: &str,
+        tuple_size: usize,
+        strings: &[&str],
+        data: &[i64],
+    ) {
+        impl_add_attrib!(
+            UnstructuredMeshF32,
+            self,
+            loc,
+            name,
+            tuple_size,
+            strings,
+            data
+        );
+    }
+}
+
+/// A mesh of any supported type.
+///
+/// Meshes loaded from files storing single precision positions use the `*F32` variants, which
+/// are added to a detail without widening their positions. The `is_*` and `into_*` functions
+/// only match double precision variants, while `tag` reports the type of either.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Mesh {
+    TetMesh(TetMesh),
+    PolyMesh(PolyMesh),
+    PolylineMesh(PolylineMesh),
+    PointCloud(PointCloud),
+    UnstructuredMesh(UnstructuredMesh),
+    TetMeshF32(TetMeshF32),
+    PolyMeshF32(PolyMeshF32),
+    PolylineMeshF32(PolylineMeshF32),
+    PointCloudF32(PointCloudF32),
+    UnstructuredMeshF32(UnstructuredMeshF32),
+    GridMesh(GridMesh),
+    None,
+}
+
+impl<M: Into<Mesh>> From<Option<M>> for Mesh {
+    fn from(m: Option<M>) -> Self {
+        m.map(|x| x.into()).unwrap_or(Mesh::None)
+    }
+}
+
+impl From<mesh::Mesh<f64>> for Mesh {
+    fn from(m: mesh::Mesh<f64>) -> Self {
+        Mesh::UnstructuredMesh(m.into())
+    }
+}
+
+impl From<mesh::TetMesh<f64>> for Mesh {
+    fn from(m: mesh::TetMesh<f64>) -> Self {
+        Mesh::TetMesh(m.into())
+    }
+}
+
+impl From<mesh::PolyMesh<f64>> for Mesh {
+    fn from(m: mesh::PolyMesh<f64>) -> Self {
+        Mesh::PolyMesh(m.into())
+    }
+}
+
+impl From<mesh::PointCloud<f64>> for Mesh {
+    fn from(m: mesh::PointCloud<f64>) -> Self {
+        Mesh::PointCloud(m.into())
+    }
+}
+
+impl From<mesh::Mesh<f32>> for Mesh {
+    fn from(m: mesh::Mesh<f32>) -> Self {
+        Mesh::UnstructuredMeshF32(m.into())
+    }
+}
+
+impl From<mesh::TetMesh<f32>> for Mesh {
+    fn from(m: mesh::TetMesh<f32>) -> Self {
+        Mesh::TetMeshF32(m.into())
+    }
+}
+
+impl From<mesh::PolyMesh<f32>> for Mesh {
+    fn from(m: mesh::PolyMesh<f32>) -> Self {
+        Mesh::PolyMeshF32(m.into())
+    }
+}
+
+impl From<mesh::PointCloud<f32>> for Mesh {
+    fn from(m: mesh::PointCloud<f32>) -> Self {
+        Mesh::PointCloudF32(m.into())
+    }
+}
+
+impl From<UnstructuredMesh> for Mesh {
+    fn from(m: UnstructuredMesh) -> Self {
+        Mesh::UnstructuredMesh(m)
+    }
+}
+
+impl From<GridMesh> for Mesh {
+    fn from(m: GridMesh) -> Self {
+        Mesh::GridMesh(m)
+    }
+}
+
+impl From<TetMesh> for Mesh {
+    fn from(m: TetMesh) -> Self {
+        Mesh::TetMesh(m)
     }
 }
 
@@ -1528,12 +3208,48 @@ impl From<PolyMesh> for Mesh {
     }
 }
 
+impl From<PolylineMesh> for Mesh {
+    fn from(m: PolylineMesh) -> Self {
+        Mesh::PolylineMesh(m)
+    }
+}
+
 impl From<PointCloud> for Mesh {
     fn from(m: PointCloud) -> Self {
         Mesh::PointCloud(m)
     }
 }
 
+impl From<UnstructuredMeshF32> for Mesh {
+    fn from(m: UnstructuredMeshF32) -> Self {
+        Mesh::UnstructuredMeshF32(m)
+    }
+}
+
+impl From<TetMeshF32> for Mesh {
+    fn from(m: TetMeshF32) -> Self {
+        Mesh::TetMeshF32(m)
+    }
+}
+
+impl From<PolyMeshF32> for Mesh {
+    fn from(m: PolyMeshF32) -> Self {
+        Mesh::PolyMeshF32(m)
+    }
+}
+
+impl From<PolylineMeshF32> for Mesh {
+    fn from(m: PolylineMeshF32) -> Self {
+        Mesh::PolylineMeshF32(m)
+    }
+}
+
+impl From<PointCloudF32> for Mesh {
+    fn from(m: PointCloudF32) -> Self {
+        Mesh::PointCloudF32(m)
+    }
+}
+
 impl Mesh {
     #[inline]
     pub fn or_else<F: FnOnce() -> Mesh>(self, f: F) -> Mesh {
@@ -1551,11 +3267,33 @@ impl Mesh {
     }
     /// Add this mesh to the given detail.
     pub fn add_to_detail(&self, detail: std::pin::Pin<&mut GU_Detail>) {
+        self.add_to_detail_with_options(detail, &AddOptions::default());
+    }
+    /// Add this mesh to the given detail with the given options.
+    pub fn add_to_detail_with_options(
+        &self,
+        mut detail: std::pin::Pin<&mut GU_Detail>,
+        options: &AddOptions,
+    ) {
+        if options.promote_p64 && self.needs_f64_positions() {
+            promote_p64(detail.as_mut());
+        }
+        if options.pack_by_name {
+            add_packed_by_name(detail, self, options);
+            return;
+        }
         match self {
             Mesh::TetMesh(m) => add_tetmesh(detail, m),
-            Mesh::PolyMesh(m) => add_polymesh(detail, m),
+            Mesh::PolyMesh(m) => add_polymesh_with_options(detail, m, options),
+            Mesh::PolylineMesh(m) => add_polylines(detail, m),
             Mesh::PointCloud(m) => add_pointcloud(detail, m),
             Mesh::UnstructuredMesh(m) => add_unstructured_mesh(detail, m),
+            Mesh::TetMeshF32(m) => add_tetmesh_f32(detail, m),
+            Mesh::PolyMeshF32(m) => add_polymesh_f32_with_options(detail, m, options),
+            Mesh::PolylineMeshF32(m) => add_polylines_f32(detail, m),
+            Mesh::PointCloudF32(m) => add_pointcloud_f32(detail, m),
+            Mesh::UnstructuredMeshF32(m) => add_unstructured_mesh_f32(detail, m),
+            Mesh::GridMesh(m) => add_grid(detail, m),
             Mesh::None => {}
         }
     }
@@ -1568,15 +3306,113 @@ impl Mesh {
     pub fn is_polymesh(&self) -> bool {
         matches!(self, Mesh::PolyMesh(_))
     }
+    pub fn is_polyline_mesh(&self) -> bool {
+        matches!(self, Mesh::PolylineMesh(_))
+    }
     pub fn is_pointcloud(&self) -> bool {
         matches!(self, Mesh::PointCloud(_))
     }
+    pub fn is_grid(&self) -> bool {
+        matches!(self, Mesh::GridMesh(_))
+    }
+    /// Check if point positions of this mesh would lose precision when stored in single
+    /// precision.
+    ///
+    /// Rounding to single precision is significant when it moves any coordinate by more than
+    /// `F32_POSITION_TOLERANCE`, as for points far from the origin. Single precision meshes never
+    /// need double precision.
+    pub fn needs_f64_positions(&self) -> bool {
+        self.point_coords()
+            .iter()
+            .filter(|x| x.is_finite())
+            .any(|&x| (f64::from(x as f32) - x).abs() > F32_POSITION_TOLERANCE)
+    }
+    /// Point positions of this mesh in double precision, or nothing for grids.
+    pub fn get_point_coords(&self) -> Vec<f64> {
+        match self.point_coords_f32() {
+            [] => self.point_coords().to_vec(),
+            coords => coords.iter().map(|&x| f64::from(x)).collect(),
+        }
+    }
+    /// Point positions borrowed from this mesh, or nothing for grids and single precision
+    /// meshes.
+    pub fn point_coords(&self) -> &[f64] {
+        match self {
+            Mesh::TetMesh(m) => m.point_coords(),
+            Mesh::PolyMesh(m) => m.point_coords(),
+            Mesh::PolylineMesh(m) => m.point_coords(),
+            Mesh::PointCloud(m) => m.point_coords(),
+            Mesh::UnstructuredMesh(m) => m.point_coords(),
+            _ => &[],
+        }
+    }
+    /// Point positions borrowed from this mesh if it is stored in single precision, or nothing
+    /// otherwise.
+    pub fn point_coords_f32(&self) -> &[f32] {
+        match self {
+            Mesh::TetMeshF32(m) => m.point_coords(),
+            Mesh::PolyMeshF32(m) => m.point_coords(),
+            Mesh::PolylineMeshF32(m) => m.point_coords(),
+            Mesh::PointCloudF32(m) => m.point_coords(),
+            Mesh::UnstructuredMeshF32(m) => m.point_coords(),
+            _ => &[],
+        }
+    }
+    pub fn attrib_iter(&self, loc: AttribLocation) -> Box<AttribIter<'_>> {
+        match self {
+            Mesh::TetMesh(m) => m.attrib_iter(loc),
+            Mesh::PolyMesh(m) => m.attrib_iter(loc),
+            Mesh::PolylineMesh(m) => m.attrib_iter(loc),
+            Mesh::PointCloud(m) => m.attrib_iter(loc),
+            Mesh::UnstructuredMesh(m) => m.attrib_iter(loc),
+            Mesh::TetMeshF32(m) => m.attrib_iter(loc),
+            Mesh::PolyMeshF32(m) => m.attrib_iter(loc),
+            Mesh::PolylineMeshF32(m) => m.attrib_iter(loc),
+            Mesh::PointCloudF32(m) => m.attrib_iter(loc),
+            Mesh::UnstructuredMeshF32(m) => m.attrib_iter(loc),
+            Mesh::GridMesh(_) | Mesh::None => Box::new(AttribIter::None),
+        }
+    }
+    /// Source indices recorded at the given location when extracting this mesh with
+    /// `keep_source_indices`.
+    ///
+    /// Returns an empty vector if no source indices were recorded.
+    pub fn source_indices(&self, loc: AttribLocation) -> Vec<i64> {
+        let name = match loc {
+            AttribLocation::VERTEX => POINT_INDEX_ATTRIB,
+            AttribLocation::FACE | AttribLocation::CELL | AttribLocation::CURVE => {
+                PRIM_INDEX_ATTRIB
+            }
+            _ => VERTEX_INDEX_ATTRIB,
+        };
+        let mut iter = self.attrib_iter(loc);
+        while iter.has_next() {
+            match iter.next() {
+                Ok(attrib) if attrib.name() == name && attrib.data_type() == DataType::I64 => {
+                    return attrib.get_data_i64().vec;
+                }
+                _ => {}
+            }
+        }
+        Vec::new()
+    }
+    /// Write positions and attributes of this mesh back onto the source elements of the
+    /// given detail.
+    ///
+    /// The mesh must be extracted with `keep_source_indices`, and the detail must have the
+    /// same topology as the detail it was extracted from.
+    pub fn scatter_to_detail(&self, detail: std::pin::Pin<&mut GU_Detail>) {
+        scatter_to_source(detail, self);
+    }
+    /// The type of this mesh, regardless of the precision of its positions.
     pub fn tag(&self) -> MeshTag {
         match self {
-            Mesh::TetMesh(_) => MeshTag::TetMesh,
-            Mesh::PolyMesh(_) => MeshTag::PolyMesh,
-            Mesh::PointCloud(_) => MeshTag::PointCloud,
-            Mesh::UnstructuredMesh(_) => MeshTag::UnstructuredMesh,
+            Mesh::TetMesh(_) | Mesh::TetMeshF32(_) => MeshTag::TetMesh,
+            Mesh::PolyMesh(_) | Mesh::PolyMeshF32(_) => MeshTag::PolyMesh,
+            Mesh::PolylineMesh(_) | Mesh::PolylineMeshF32(_) => MeshTag::PolylineMesh,
+            Mesh::PointCloud(_) | Mesh::PointCloudF32(_) => MeshTag::PointCloud,
+            Mesh::UnstructuredMesh(_) | Mesh::UnstructuredMeshF32(_) => MeshTag::UnstructuredMesh,
+            Mesh::GridMesh(_) => MeshTag::GridMesh,
             Mesh::None => MeshTag::None,
         }
     }
@@ -1600,9 +3436,262 @@ pub fn into_polymesh(mesh: Box<Mesh>) -> Box<PolyMesh> {
         _ => panic!("Mesh mismatch"),
     }
 }
+pub fn into_polyline_mesh(mesh: Box<Mesh>) -> Box<PolylineMesh> {
+    match *mesh {
+        Mesh::PolylineMesh(m) => Box::new(m),
+        _ => panic!("Mesh mismatch"),
+    }
+}
 pub fn into_pointcloud(mesh: Box<Mesh>) -> Box<PointCloud> {
     match *mesh {
         Mesh::PointCloud(m) => Box::new(m),
         _ => panic!("Mesh mismatch"),
     }
 }
+pub fn into_grid(mesh: Box<Mesh>) -> Box<GridMesh> {
+    match *mesh {
+        Mesh::GridMesh(m) => Box::new(m),
+        _ => panic!("Mesh mismatch"),
+    }
+}
+
+/// Meshes of different types extracted together from a single detail.
+///
+/// A collection holds polygons, tetrahedra, open polylines and loose points. Other primitive
+/// types, detail attributes and groups are not represented, so a detail rebuilt from a
+/// collection keeps only the points and the supported primitives along with their attributes.
+/// Details with packed primitives must be unpacked before building a collection.
+///
+/// Each part stores the source index of its points and primitives in the reserved
+/// `POINT_INDEX_ATTRIB` and `PRIM_INDEX_ATTRIB` attributes. Parts without these attributes are
+/// placed after all indexed points and primitives when added to a detail.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct MeshCollection {
+    /// Number of points in the source detail.
+    pub num_points: usize,
+    pub polymesh: Option<PolyMesh>,
+    pub tetmesh: Option<TetMesh>,
+    pub polylines: Option<PolylineMesh>,
+    pub points: Option<PointCloud>,
+}
+
+pub fn new_mesh_collection(num_points: usize) -> Box<MeshCollection> {
+    Box::new(MeshCollection {
+        num_points,
+        ..Default::default()
+    })
+}
+
+/// Read source indices stored in the given reserved attribute.
+fn source_indices<M, I>(mesh: &M, name: &str) -> Option<Vec<usize>>
+where
+    M: Attrib,
+    I: attrib::AttribIndex<M>,
+{
+    mesh.attrib_as_slice::<i64, I>(name)
+        .ok()
+        .map(|indices| indices.iter().map(|&i| i as usize).collect())
+}
+
+/// Fill in missing source indices with consecutive indices following all stored indices.
+fn resolve_source_indices(
+    parts: Vec<(MeshTag, usize, Option<Vec<usize>>)>,
+) -> Vec<(MeshTag, Vec<usize>)> {
+    let mut next = parts
+        .iter()
+        .filter_map(|(_, _, indices)| indices.as_ref())
+        .flat_map(|indices| indices.iter().map(|&i| i + 1))
+        .max()
+        .unwrap_or(0);
+    parts
+        .into_iter()
+        .map(|(tag, n, indices)| {
+            let indices = indices.unwrap_or_else(|| {
+                next += n;
+                (next - n..next).collect()
+            });
+            (tag, indices)
+        })
+        .collect()
+}
+
+impl MeshCollection {
+    pub fn has_polymesh(&self) -> bool {
+        self.polymesh.is_some()
+    }
+    pub fn has_tetmesh(&self) -> bool {
+        self.tetmesh.is_some()
+    }
+    pub fn has_polylines(&self) -> bool {
+        self.polylines.is_some()
+    }
+    pub fn has_points(&self) -> bool {
+        self.points.is_some()
+    }
+    /// The polygon part of this collection, or `Error::MeshMismatch` if there is none.
+    pub fn polymesh(&self) -> Result<&PolyMesh, Error> {
+        self.polymesh.as_ref().ok_or(Error::MeshMismatch)
+    }
+    /// The tetrahedron part of this collection, or `Error::MeshMismatch` if there is none.
+    pub fn tetmesh(&self) -> Result<&TetMesh, Error> {
+        self.tetmesh.as_ref().ok_or(Error::MeshMismatch)
+    }
+    /// The polyline part of this collection, or `Error::MeshMismatch` if there is none.
+    pub fn polylines(&self) -> Result<&PolylineMesh, Error> {
+        self.polylines.as_ref().ok_or(Error::MeshMismatch)
+    }
+    /// The loose points of this collection, or `Error::MeshMismatch` if there are none.
+    pub fn points(&self) -> Result<&PointCloud, Error> {
+        self.points.as_ref().ok_or(Error::MeshMismatch)
+    }
+    pub fn set_polymesh(&mut self, polymesh: Box<PolyMesh>) {
+        self.polymesh = Some(*polymesh);
+    }
+    pub fn set_tetmesh(&mut self, tetmesh: Box<TetMesh>) {
+        self.tetmesh = Some(*tetmesh);
+    }
+    pub fn set_polylines(&mut self, polylines: Box<PolylineMesh>) {
+        self.polylines = Some(*polylines);
+    }
+    pub fn set_points(&mut self, points: Box<PointCloud>) {
+        self.points = Some(*points);
+    }
+
+    /// Source point indices of all parts in this collection.
+    fn all_point_indices(&self) -> Vec<(MeshTag, Vec<usize>)> {
+        use topo::{NumVertices, VertexIndex};
+        let mut parts = Vec::new();
+        if let Some(m) = &self.polymesh {
+            let indices = source_indices::<_, VertexIndex>(&m.0, POINT_INDEX_ATTRIB);
+            parts.push((MeshTag::PolyMesh, m.0.num_vertices(), indices));
+        }
+        if let Some(m) = &self.tetmesh {
+            let indices = source_indices::<_, VertexIndex>(&m.0, POINT_INDEX_ATTRIB);
+            parts.push((MeshTag::TetMesh, m.0.num_vertices(), indices));
+        }
+        if let Some(m) = &self.polylines {
+            let indices = source_indices::<_, VertexIndex>(&m.0, POINT_INDEX_ATTRIB);
+            parts.push((MeshTag::PolylineMesh, m.0.num_vertices(), indices));
+        }
+        if let Some(m) = &self.points {
+            let indices = source_indices::<_, VertexIndex>(&m.0, POINT_INDEX_ATTRIB);
+            parts.push((MeshTag::PointCloud, m.0.num_vertices(), indices));
+        }
+        resolve_source_indices(parts)
+    }
+
+    /// Source primitive indices of all parts in this collection with primitives.
+    fn all_prim_indices(&self) -> Vec<(MeshTag, Vec<usize>)> {
+        use topo::{CellIndex, FaceIndex, NumCells, NumFaces};
+        let mut parts = Vec::new();
+        if let Some(m) = &self.polymesh {
+            let indices = source_indices::<_, FaceIndex>(&m.0, PRIM_INDEX_ATTRIB);
+            parts.push((MeshTag::PolyMesh, m.0.num_faces(), indices));
+        }
+        if let Some(m) = &self.tetmesh {
+            let indices = source_indices::<_, CellIndex>(&m.0, PRIM_INDEX_ATTRIB);
+            parts.push((MeshTag::TetMesh, m.0.num_cells(), indices));
+        }
+        if let Some(m) = &self.polylines {
+            let indices = source_indices::<_, FaceIndex>(&m.0, PRIM_INDEX_ATTRIB);
+            parts.push((MeshTag::PolylineMesh, m.0.num_faces(), indices));
+        }
+        resolve_source_indices(parts)
+    }
+
+    /// Number of points needed to hold all points of this collection.
+    pub fn num_points(&self) -> usize {
+        self.all_point_indices()
+            .iter()
+            .flat_map(|(_, indices)| indices.iter().map(|&i| i + 1))
+            .max()
+            .unwrap_or(0)
+            .max(self.num_points)
+    }
+
+    /// Source point index of each point in the given part.
+    pub fn point_indices(&self, part: MeshTag) -> Vec<usize> {
+        self.all_point_indices()
+            .into_iter()
+            .find(|(tag, _)| *tag == part)
+            .map(|(_, indices)| indices)
+            .unwrap_or_default()
+    }
+
+    /// Faces and cells of all parts ordered by their source primitive index.
+    pub fn prim_order(&self) -> Vec<PrimRef> {
+        let mut order: Vec<_> = self
+            .all_prim_indices()
+            .into_iter()
+            .flat_map(|(part, indices)| {
+                indices
+                    .into_iter()
+                    .enumerate()
+                    .map(move |(index, src)| (src, PrimRef { part, index }))
+            })
+            .collect();
+        order.sort_by_key(|&(src, _)| src);
+        order.into_iter().map(|(_, prim)| prim).collect()
+    }
+
+    /// Add all parts of this collection to the given detail.
+    pub fn add_to_detail(&self, detail: std::pin::Pin<&mut GU_Detail>) {
+        add_collection(detail, self);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_triangle(offset: f64) -> Vec<f64> {
+        vec![
+            offset,
+            offset,
+            offset,
+            offset + 0.1,
+            offset,
+            offset,
+            offset,
+            offset + 0.1,
+            offset,
+        ]
+    }
+
+    #[test]
+    fn polygon_indices_borrowed_with_offsets() {
+        let coords = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0];
+        let mesh = make_polymesh(&coords, &[3, 0, 1, 2, 4, 0, 1, 2, 3]);
+        assert_eq!(mesh.indices(), &[0, 1, 2, 0, 1, 2, 3]);
+        assert_eq!(mesh.offsets(), &[0, 3, 7]);
+        assert_eq!(mesh.get_indices(), vec![3, 0, 1, 2, 4, 0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn f64_positions_needed_far_from_origin() {
+        // Values like 0.1 are not exactly representable, but their rounding error is negligible.
+        let near = make_polymesh(&unit_triangle(0.0), &[3, 0, 1, 2]);
+        assert!(!Mesh::from(*near).needs_f64_positions());
+
+        let large = make_polymesh(&unit_triangle(999.9), &[3, 0, 1, 2]);
+        assert!(!Mesh::from(*large).needs_f64_positions());
+
+        let far = make_polymesh(&unit_triangle(1.0e6), &[3, 0, 1, 2]);
+        assert!(Mesh::from(*far).needs_f64_positions());
+
+        // Single points are judged by their rounding error alone, like any other mesh.
+        let point = make_pointcloud(&[0.1, 1.0e6 + 0.1, -0.3]);
+        assert!(Mesh::from(*point).needs_f64_positions());
+
+        // Exactly representable coordinates are never rounded.
+        let exact = make_pointcloud(&[0.5, 1.0e6, -0.25]);
+        assert!(!Mesh::from(*exact).needs_f64_positions());
+
+        let empty = make_pointcloud(&[]);
+        assert!(!Mesh::from(*empty).needs_f64_positions());
+
+        let coords_f32: Vec<f32> = unit_triangle(1.0e6).iter().map(|&x| x as f32).collect();
+        let far_f32 = make_polymesh_f32(&coords_f32, &[3, 0, 1, 2]);
+        assert!(!Mesh::from(*far_f32).needs_f64_positions());
+    }
+}
@@ -1,16 +1,28 @@
+#include <algorithm>
+#include <array>
 #include <optional>
 #include <vector>
 #include <cassert>
+#include <cstring>
 #include <string>
 #include <iostream>
 #include <stdexcept>
+#include <type_traits>
 
 #include <UT/UT_Debug.h>
 #include <GU/GU_Detail.h>
 #include <GEO/GEO_PrimTetrahedron.h>
 #include <GEO/GEO_PrimPoly.h>
+#include <GEO/GEO_PrimPolySoup.h>
+#include <GEO/GEO_PolySoupParms.h>
+#include <GU/GU_PrimPacked.h>
+#include <GU/GU_PackedGeometry.h>
+#include <UT/UT_StringMap.h>
 #include <GEO/GEO_PolyCounts.h>
 #include <GA/GA_PageHandle.h>
+#include <GA/GA_SplittableRange.h>
+#include <UT/UT_ParallelUtil.h>
+#include <UT/UT_Vector2.h>
 
 #include <rust/cxx.h>
 #include "hdkrs/src/lib.rs.h"
@@ -26,118 +38,158 @@ inline std::ostream& operator<<(std::ostream& out, AttribLocation where) {
         case AttribLocation::CELL: out << "Cell"; break;
         case AttribLocation::FACEVERTEX: out << "FaceVertex"; break;
         case AttribLocation::CELLVERTEX: out << "CellVertex"; break;
+        case AttribLocation::CURVE: out << "Curve"; break;
+        case AttribLocation::CURVEVERTEX: out << "CurveVertex"; break;
         default: break;
     }
     return out;
 }
 
-// The following items define various version of the add_attrib function which
-// adds an attribute of a given type to a given mesh or point cloud.
-
-#define HR_ADD_NUM_ATTRIB_IMPL(MTYPE, TYPE, FN) \
-    void add_attrib( \
-            MTYPE *m, \
-            AttribLocation where, \
-            const char *name, \
-            std::size_t tuple_size, \
-            const std::vector<TYPE> &data) \
-    { \
-        m->FN( where, rust::Str(name), tuple_size, rust::Slice(data.data(), data.size()) ); \
-    }
+// Type of mesh attributes storing values of type `T`.
+template<typename T>
+DataType attrib_data_type();
 
-HR_ADD_NUM_ATTRIB_IMPL(UnstructuredMesh, int8, add_attrib_i8)
-HR_ADD_NUM_ATTRIB_IMPL(UnstructuredMesh, int32, add_attrib_i32)
-HR_ADD_NUM_ATTRIB_IMPL(UnstructuredMesh, int64_t, add_attrib_i64)
-HR_ADD_NUM_ATTRIB_IMPL(UnstructuredMesh, fpreal32, add_attrib_f32)
-HR_ADD_NUM_ATTRIB_IMPL(UnstructuredMesh, fpreal64, add_attrib_f64)
+template<>
+DataType attrib_data_type<int8>() { return DataType::I8; }
 
-HR_ADD_NUM_ATTRIB_IMPL(PointCloud, int8, add_attrib_i8)
-HR_ADD_NUM_ATTRIB_IMPL(PointCloud, int32, add_attrib_i32)
-HR_ADD_NUM_ATTRIB_IMPL(PointCloud, int64_t, add_attrib_i64)
-HR_ADD_NUM_ATTRIB_IMPL(PointCloud, fpreal32, add_attrib_f32)
-HR_ADD_NUM_ATTRIB_IMPL(PointCloud, fpreal64, add_attrib_f64)
+template<>
+DataType attrib_data_type<int32>() { return DataType::I32; }
 
-HR_ADD_NUM_ATTRIB_IMPL(PolyMesh, int8, add_attrib_i8)
-HR_ADD_NUM_ATTRIB_IMPL(PolyMesh, int32, add_attrib_i32)
-HR_ADD_NUM_ATTRIB_IMPL(PolyMesh, int64_t, add_attrib_i64)
-HR_ADD_NUM_ATTRIB_IMPL(PolyMesh, fpreal32, add_attrib_f32)
-HR_ADD_NUM_ATTRIB_IMPL(PolyMesh, fpreal64, add_attrib_f64)
+template<>
+DataType attrib_data_type<int64_t>() { return DataType::I64; }
 
-HR_ADD_NUM_ATTRIB_IMPL(TetMesh, int8, add_attrib_i8)
-HR_ADD_NUM_ATTRIB_IMPL(TetMesh, int32, add_attrib_i32)
-HR_ADD_NUM_ATTRIB_IMPL(TetMesh, int64_t, add_attrib_i64)
-HR_ADD_NUM_ATTRIB_IMPL(TetMesh, fpreal32, add_attrib_f32)
-HR_ADD_NUM_ATTRIB_IMPL(TetMesh, fpreal64, add_attrib_f64)
+template<>
+DataType attrib_data_type<fpreal32>() { return DataType::F32; }
 
-#undef ADD_NUM_ATTRIB_IMPL
+template<>
+DataType attrib_data_type<fpreal64>() { return DataType::F64; }
 
+// Add a numeric attribute with the given values to a mesh or point cloud.
+template<typename M, typename T>
 void add_attrib(
-        UnstructuredMesh *mesh,
+        M *mesh,
         AttribLocation where,
         const char *name,
         std::size_t tuple_size,
-        const std::vector<rust::Str> &strings,
-        const std::vector<int64_t> &indices)
+        const std::vector<T> &data)
 {
-    mesh->add_attrib_str(
-            where, rust::Str(name), tuple_size,
-            rust::Slice(strings.data(), strings.size()),
-            rust::Slice(indices.data(), indices.size()));
+    AttribLayout layout{ rust::String(name), tuple_size, attrib_data_type<T>() };
+    auto bytes = mesh->add_attribs_zeros(where, rust::Slice<const AttribLayout>(&layout, 1))->take(0);
+    std::memcpy(bytes.data(), data.data(), std::min(bytes.size(), data.size()*sizeof(T)));
 }
 
+// Add a string attribute to a mesh or point cloud, given unique strings and per element indices
+// into them.
+template<typename M>
 void add_attrib(
-        PointCloud *ptcloud,
+        M *mesh,
         AttribLocation where,
         const char *name,
         std::size_t tuple_size,
         const std::vector<rust::Str> &strings,
         const std::vector<int64_t> &indices)
 {
-    ptcloud->add_attrib_str(
+    mesh->add_attrib_str(
             where, rust::Str(name), tuple_size,
             rust::Slice(strings.data(), strings.size()),
             rust::Slice(indices.data(), indices.size()));
 }
 
-void add_attrib(
-        PolyMesh *polymesh,
-        AttribLocation where,
-        const char *name,
-        std::size_t tuple_size,
-        const std::vector<rust::Str> &strings,
-        const std::vector<int64_t> &indices)
-{
-    polymesh->add_attrib_str(
-            where, rust::Str(name), tuple_size,
-            rust::Slice(strings.data(), strings.size()),
-            rust::Slice(indices.data(), indices.size()));
+// Check if the given primitive is a closed polygon. Open polygons are treated as curves.
+inline bool is_closed_poly(const GEO_Primitive *prim) {
+    return prim->getTypeId() == GA_PRIMPOLY && static_cast<const GEO_PrimPoly*>(prim)->isClosed();
 }
 
-void add_attrib(
-        TetMesh *tetmesh,
-        AttribLocation where,
-        const char *name,
-        std::size_t tuple_size,
-        const std::vector<rust::Str> &strings,
-        const std::vector<int64_t> &indices)
-{
-    tetmesh->add_attrib_str(
-            where, rust::Str(name), tuple_size,
-            rust::Slice(strings.data(), strings.size()),
-            rust::Slice(indices.data(), indices.size()));
+inline bool is_open_poly(const GEO_Primitive *prim) {
+    return prim->getTypeId() == GA_PRIMPOLY && !static_cast<const GEO_PrimPoly*>(prim)->isClosed();
 }
 
 template<typename M>
-bool is_valid_prim_type(GA_PrimitiveTypeId id, GA_Size num_verts);
+bool is_valid_prim_type(const GEO_Primitive *prim, GA_Size num_verts);
+
+template<>
+bool is_valid_prim_type<PolyMesh>(const GEO_Primitive *prim, GA_Size num_verts) { return is_closed_poly(prim); }
+
+template<>
+bool is_valid_prim_type<PolylineMesh>(const GEO_Primitive *prim, GA_Size num_verts) { return is_open_poly(prim); }
+
+template<>
+bool is_valid_prim_type<TetMesh>(const GEO_Primitive *prim, GA_Size num_verts) { return prim->getTypeId() == GA_PRIMTETRAHEDRON; }
+
+template<>
+bool is_valid_prim_type<PolyMeshF32>(const GEO_Primitive *prim, GA_Size num_verts) { return is_closed_poly(prim); }
+
+template<>
+bool is_valid_prim_type<PolylineMeshF32>(const GEO_Primitive *prim, GA_Size num_verts) { return is_open_poly(prim); }
+
+template<>
+bool is_valid_prim_type<TetMeshF32>(const GEO_Primitive *prim, GA_Size num_verts) { return prim->getTypeId() == GA_PRIMTETRAHEDRON; }
+
+template<>
+bool is_valid_prim_type<UnstructuredMesh>(const GEO_Primitive *prim, GA_Size num_verts) { return prim->getTypeId() == GA_PRIMTETRAHEDRON || (is_closed_poly(prim) && num_verts >= 3); }
+
+template<>
+bool is_valid_prim_type<UnstructuredMeshF32>(const GEO_Primitive *prim, GA_Size num_verts) { return is_valid_prim_type<UnstructuredMesh>(prim, num_verts); }
+
+// Check if a polygon with the given number of vertices inside a polygon soup is valid.
+template<typename M>
+bool is_valid_soup_polygon(GA_Size num_verts) { return false; }
+
+template<>
+bool is_valid_soup_polygon<PolyMesh>(GA_Size num_verts) { return true; }
 
 template<>
-bool is_valid_prim_type<PolyMesh>(GA_PrimitiveTypeId id, GA_Size num_verts) { return id == GA_PRIMPOLY; }
+bool is_valid_soup_polygon<PolyMeshF32>(GA_Size num_verts) { return true; }
 
 template<>
-bool is_valid_prim_type<TetMesh>(GA_PrimitiveTypeId id, GA_Size num_verts) { return id == GA_PRIMTETRAHEDRON; }
+bool is_valid_soup_polygon<UnstructuredMesh>(GA_Size num_verts) { return num_verts >= 3; }
 
 template<>
-bool is_valid_prim_type<UnstructuredMesh>(GA_PrimitiveTypeId id, GA_Size num_verts) { return id == GA_PRIMTETRAHEDRON || (id == GA_PRIMPOLY && num_verts == 3); }
+bool is_valid_soup_polygon<UnstructuredMeshF32>(GA_Size num_verts) { return num_verts >= 3; }
+
+// Check if polygons with more than three vertices are split into triangles for the given mesh type.
+template<typename M>
+bool triangulates_polygons() { return false; }
+
+template<>
+bool triangulates_polygons<UnstructuredMesh>() { return true; }
+
+template<>
+bool triangulates_polygons<UnstructuredMeshF32>() { return true; }
+
+// Call `f(prim_off, vertex_offsets)` for every face or cell of the given mesh type in the detail.
+//
+// Polygon soups are expanded into individual polygons, each reported with the offset of the
+// soup primitive and the offsets of the soup vertices it uses.
+template<typename M, typename F>
+void for_each_face(const GU_Detail &detail, F &&f)
+{
+    UT_Array<GA_Offset> vertices;
+    for ( GA_Offset prim_off : detail.getPrimitiveRange() )
+    {
+        const GEO_Primitive *prim = detail.getGEOPrimitive(prim_off);
+        if (prim->getTypeId() == GA_PRIMPOLYSOUP) {
+            const GEO_PrimPolySoup *soup = static_cast<const GEO_PrimPolySoup*>(prim);
+            for ( GEO_PrimPolySoup::PolygonIterator it(*soup); !it.atEnd(); ++it ) {
+                GA_Size num_verts = it.nvertices();
+                if (!is_valid_soup_polygon<M>(num_verts)) continue;
+                vertices.clear();
+                for ( GA_Size idx = 0; idx < num_verts; ++idx ) {
+                    vertices.append(it.getVertexOffset(idx));
+                }
+                f(prim_off, vertices);
+            }
+        } else {
+            GA_Size num_prim_verts = detail.getPrimitiveVertexCount(prim_off);
+            if (!is_valid_prim_type<M>(prim, num_prim_verts)) continue;
+            vertices.clear();
+            for ( GA_Size idx = 0; idx < num_prim_verts; ++idx ) {
+                vertices.append(detail.getPrimitiveVertexOffset(prim_off, idx));
+            }
+            f(prim_off, vertices);
+        }
+    }
+}
 
 template<typename T>
 AttribLocation mesh_prim_attrib_location();
@@ -145,407 +197,542 @@ AttribLocation mesh_prim_attrib_location();
 template<>
 AttribLocation mesh_prim_attrib_location<PolyMesh>() { return AttribLocation::FACE; }
 
+template<>
+AttribLocation mesh_prim_attrib_location<PolylineMesh>() { return AttribLocation::CURVE; }
+
 template<>
 AttribLocation mesh_prim_attrib_location<TetMesh>() { return AttribLocation::CELL; }
 
+template<>
+AttribLocation mesh_prim_attrib_location<PolyMeshF32>() { return AttribLocation::FACE; }
+
+template<>
+AttribLocation mesh_prim_attrib_location<PolylineMeshF32>() { return AttribLocation::CURVE; }
+
+template<>
+AttribLocation mesh_prim_attrib_location<TetMeshF32>() { return AttribLocation::CELL; }
+
 template<>
 AttribLocation mesh_prim_attrib_location<UnstructuredMesh>() { return AttribLocation::CELL; }
 
+template<>
+AttribLocation mesh_prim_attrib_location<UnstructuredMeshF32>() { return AttribLocation::CELL; }
+
 template<typename T>
 AttribLocation mesh_vertex_attrib_location();
 
 template<>
 AttribLocation mesh_vertex_attrib_location<PolyMesh>() { return AttribLocation::FACEVERTEX; }
 
+template<>
+AttribLocation mesh_vertex_attrib_location<PolylineMesh>() { return AttribLocation::CURVEVERTEX; }
+
 template<>
 AttribLocation mesh_vertex_attrib_location<TetMesh>() { return AttribLocation::CELLVERTEX; }
 
+template<>
+AttribLocation mesh_vertex_attrib_location<PolyMeshF32>() { return AttribLocation::FACEVERTEX; }
+
+template<>
+AttribLocation mesh_vertex_attrib_location<PolylineMeshF32>() { return AttribLocation::CURVEVERTEX; }
+
+template<>
+AttribLocation mesh_vertex_attrib_location<TetMeshF32>() { return AttribLocation::CELLVERTEX; }
+
 template<>
 AttribLocation mesh_vertex_attrib_location<UnstructuredMesh>() { return AttribLocation::CELLVERTEX; }
 
-// Mark all points and vectors in the given detail that intersect the primitives of interest.
-template<typename M>
-std::tuple<std::vector<bool>, std::size_t>
-mark_points_and_count_vertices(const GU_Detail& detail)
+template<>
+AttribLocation mesh_vertex_attrib_location<UnstructuredMeshF32>() { return AttribLocation::CELLVERTEX; }
+
+// Offsets of the points marked in the given group.
+GA_OffsetList marked_points(const GU_Detail &detail, const std::vector<bool> &group)
 {
-    std::vector<bool> points(detail.getNumPointOffsets(), false);
-    std::size_t num_vertices = 0;
-    for ( GA_Offset prim_off : detail.getPrimitiveRange() )
-    {
-        const GEO_Primitive *prim = detail.getGEOPrimitive(prim_off);
-        GA_Size num_prim_verts = detail.getPrimitiveVertexCount(prim_off);
-        if (!is_valid_prim_type<M>(prim->getTypeId(), num_prim_verts)) continue;
-        num_vertices += num_prim_verts;
-        for ( GA_Size idx = 0; idx < num_prim_verts; ++idx ) {
-            auto vtx_off = detail.getPrimitiveVertexOffset(prim_off, idx);
-            points[detail.vertexPoint(vtx_off)] = true;
+    GA_OffsetList offsets;
+    GA_Offset start, end;
+    for (GA_Iterator it(detail.getPointRange()); it.blockAdvance(start, end); ) {
+        for (GA_Offset pt_off = start; pt_off < end; ++pt_off) {
+            if (group[pt_off]) offsets.append(pt_off);
         }
     }
-
-    return std::make_pair(std::move(points), num_vertices);
+    return offsets;
 }
 
-template<typename T, typename M, typename S = T>
-void
-fill_prim_attrib(
-        const GU_Detail& detail,
-        const GA_AIFTuple *aif,
-        const GA_Attribute *attrib,
-        std::size_t tuple_size,
-        std::size_t num_elem,
-        M *mesh)
+GA_OffsetList to_offset_list(const std::vector<GA_Offset> &offsets)
 {
-    std::vector<T> data(tuple_size*num_elem);
-    int i = 0;
-    for ( GA_Offset prim_off : detail.getPrimitiveRange() )
-    {
-        const GEO_Primitive *prim = detail.getGEOPrimitive(prim_off);
-        GA_Size num_prim_verts = detail.getPrimitiveVertexCount(prim_off);
-        if (!is_valid_prim_type<M>(prim->getTypeId(), num_prim_verts)) continue;
-        for ( int k = 0, k_end = tuple_size; k < k_end; ++k ) {
-            S val;
-            aif->get(attrib, prim_off, val, k);
-            data[tuple_size*i + k] = val;
-        }
-        i += 1;
+    GA_OffsetList list;
+    list.reserve(offsets.size());
+    for ( GA_Offset off : offsets ) {
+        list.append(off);
     }
-
-    auto name = attrib->getName().c_str();
-    add_attrib(mesh, mesh_prim_attrib_location<M>(), name, tuple_size, data);
+    return list;
 }
 
-template<typename T, typename M, typename S = T>
-void fill_point_attrib(
-        const GU_Detail &detail,
+// Read tuples of a numeric attribute on the given elements as `S`.
+//
+// Elements are read in bulk in parallel chunks of a page each. Every chunk writes to its own
+// part of the output, so the result doesn't depend on scheduling.
+template<typename S>
+std::vector<S> read_tuples(
         const GA_AIFTuple *aif,
         const GA_Attribute *attrib,
         std::size_t tuple_size,
-        std::size_t num_elem,
-        const std::vector<bool> &group,
-        M *mesh)
+        const GA_OffsetList &offsets)
 {
-    std::vector<T> data(tuple_size*num_elem);
-    int i = 0;
-    for ( GA_Offset pt_off : detail.getPointRange() )
-    {
-        if (!group[pt_off]) continue;
-        for ( int k = 0, k_end = tuple_size; k < k_end; ++k ) {
-            S val;
-            aif->get(attrib, pt_off, val, k);
-            data[tuple_size*i + k] = val;
+    std::vector<S> values(tuple_size*offsets.size());
+    const GA_IndexMap &index_map = attrib->getIndexMap();
+    UTparallelFor(UT_BlockedRange<exint>(0, offsets.size(), GA_PAGE_SIZE), [&](const UT_BlockedRange<exint> &r) {
+        GA_OffsetList chunk;
+        chunk.reserve(r.size());
+        for (exint i = r.begin(); i < r.end(); ++i) {
+            chunk.append(offsets(i));
         }
-        i += 1;
-    }
-
-    auto name = attrib->getName().c_str();
-    add_attrib(mesh, AttribLocation::VERTEX, name, tuple_size, data);
+        aif->getRange(attrib, GA_Range(index_map, chunk), values.data() + tuple_size*r.begin(), 0, tuple_size);
+    });
+    return values;
 }
 
-template<typename T, typename M, typename S = T>
-void fill_vertex_attrib(
-        const GU_Detail &detail,
-        const GA_AIFTuple *aif,
-        const GA_Attribute *attrib,
-        std::size_t tuple_size,
-        std::size_t num_elem,
-        M *mesh)
+// Positions of the given points as flat coordinates of type `S`.
+template<typename S>
+std::vector<S> point_coords(const GU_Detail &detail, const GA_OffsetList &offsets)
 {
-    std::vector<T> data(tuple_size*num_elem);
-    int i = 0;
-    for ( GA_Offset prim_off : detail.getPrimitiveRange() ) {
-        const GEO_Primitive *prim = detail.getGEOPrimitive(prim_off);
-        GA_Size num_prim_verts = detail.getPrimitiveVertexCount(prim_off);
-        if (!is_valid_prim_type<M>(prim->getTypeId(), num_prim_verts)) continue;
-        for (GA_Size idx = 0; idx < num_prim_verts; ++idx) {
-            auto vtx_off = detail.getPrimitiveVertexOffset(prim_off, idx);
-            for (int k = 0, k_end = tuple_size; k < k_end; ++k) {
-                S val;
-                aif->get(attrib, vtx_off, val, k);
-                data[tuple_size * i + k] = val;
-            }
-            i += 1;
-        }
-    }
-
-    auto name = attrib->getName().c_str();
-    add_attrib(mesh, mesh_vertex_attrib_location<M>(), name, tuple_size, data);
+    const GA_Attribute *P = detail.getP();
+    return read_tuples<S>(P->getAIFTuple(), P, 3, offsets);
 }
 
+// Scalar type of the point positions stored in a mesh.
+template<typename M>
+struct MeshScalar { using type = double; };
+
+template<>
+struct MeshScalar<PolyMeshF32> { using type = float; };
+
+template<>
+struct MeshScalar<PolylineMeshF32> { using type = float; };
+
+template<>
+struct MeshScalar<TetMeshF32> { using type = float; };
+
+template<>
+struct MeshScalar<PointCloudF32> { using type = float; };
+
+template<>
+struct MeshScalar<UnstructuredMeshF32> { using type = float; };
+
+// Positions of the given points as flat coordinates at the precision of mesh `M`.
 template<typename M>
-void fill_prim_str_attrib(
+std::vector<typename MeshScalar<M>::type> mesh_point_coords(const GU_Detail &detail, const GA_OffsetList &offsets)
+{
+    return point_coords<typename MeshScalar<M>::type>(detail, offsets);
+}
+
+// Topology of a mesh extracted from a detail, along with the source elements of each
+// extracted element.
+struct Extracted {
+    // Offsets of the extracted points.
+    GA_OffsetList point_offsets;
+    // Number of vertices of each face followed by its point indices into `point_offsets`.
+    std::vector<std::size_t> indices;
+    // Extracted points marked by offset.
+    std::vector<bool> points;
+    // Source point index of each extracted point.
+    std::vector<int64_t> point_indices;
+    // Source primitive of each extracted face or cell.
+    std::vector<GA_Offset> prims;
+    // Source vertex of each extracted face or cell vertex.
+    std::vector<GA_Offset> vertices;
+};
+
+// Flags controlling the extraction of meshes from a detail.
+struct ExtractFlags {
+    // Keep only points referenced by the extracted primitives.
+    bool compact_points = false;
+    // Store source point and primitive indices in reserved attributes.
+    bool source_indices = false;
+    // Method used to split polygons into triangles for meshes that only support triangles.
+    Triangulation triangulation = Triangulation::Fan;
+    // Store the source polygon of each triangle for meshes that only support triangles.
+    bool source_polygons = false;
+    // Extracting from an unpacked copy, whose point numbers don't refer to the source detail.
+    bool unpacked = false;
+};
+
+// Split a polygon with the given vertices into triangles given by indices into `vertices`.
+//
+// Fans connect the first vertex to every edge, which is only valid for convex polygons. Ear
+// clipping projects the polygon along the dominant axis of its Newell normal and repeatedly
+// clips a convex corner that contains no other vertex, which also handles concave polygons.
+// Triangles keep the winding of the polygon.
+std::vector<std::array<GA_Size, 3>> triangulate_polygon(
         const GU_Detail &detail,
-        const GA_AIFSharedStringTuple *aif,
-        const GA_Attribute *attrib,
-        std::size_t tuple_size,
-        std::size_t num_elem,
-        M *mesh)
+        const UT_Array<GA_Offset> &vertices,
+        Triangulation method)
 {
-    // Try with different types
-    std::vector<int64_t> ids(aif->getTableEntries(attrib), -1);
-    std::vector<rust::Str> strings;
-    for (auto it = aif->begin(attrib); !it.atEnd(); ++it) {
-        ids[it.getHandle()] = strings.size();
-        strings.push_back( rust::Str(it.getString()) );
+    GA_Size n = vertices.size();
+    std::vector<std::array<GA_Size, 3>> tris;
+    tris.reserve(n - 2);
+    if (method == Triangulation::Fan) {
+        for ( GA_Size i = 1; i + 1 < n; ++i ) {
+            tris.push_back({0, i, i + 1});
+        }
+        return tris;
     }
 
-    std::vector<int64_t> indices(tuple_size*num_elem, -1);
+    std::vector<UT_Vector3D> pos;
+    pos.reserve(n);
+    for ( GA_Offset vtx_off : vertices ) {
+        pos.push_back(detail.getPos3D(detail.vertexPoint(vtx_off)));
+    }
+    UT_Vector3D normal(0.0, 0.0, 0.0);
+    for ( GA_Size i = 0; i < n; ++i ) {
+        const UT_Vector3D &p = pos[i];
+        const UT_Vector3D &q = pos[(i + 1) % n];
+        normal.x() += (p.y() - q.y())*(p.z() + q.z());
+        normal.y() += (p.z() - q.z())*(p.x() + q.x());
+        normal.z() += (p.x() - q.x())*(p.y() + q.y());
+    }
+    // Drop the dominant axis of the normal, flipping the projection to face along the normal.
+    int axis = normal.findMaxAbsAxis();
+    int u = (axis + 1) % 3, v = (axis + 2) % 3;
+    double sign = normal(axis) < 0.0 ? -1.0 : 1.0;
+    std::vector<UT_Vector2D> uv;
+    uv.reserve(n);
+    for ( const UT_Vector3D &p : pos ) {
+        uv.push_back(UT_Vector2D(p(u), sign*p(v)));
+    }
 
-    int i = 0;
-    for ( GA_Offset prim_off : detail.getPrimitiveRange() )
-    {
-        const GEO_Primitive *prim = detail.getGEOPrimitive(prim_off);
-        GA_Size num_prim_verts = detail.getPrimitiveVertexCount(prim_off);
-        if (!is_valid_prim_type<M>(prim->getTypeId(), num_prim_verts)) continue;
-        for ( int k = 0, k_end = tuple_size; k < k_end; ++k ) {
-            GA_StringIndexType handle = aif->getHandle(attrib, prim_off, k);
-            indices[tuple_size*i + k] = handle > -1 ? ids[handle] : -1;
+    auto cross = [&](GA_Size a, GA_Size b, GA_Size c) {
+        UT_Vector2D ab = uv[b] - uv[a], ac = uv[c] - uv[a];
+        return ab.x()*ac.y() - ab.y()*ac.x();
+    };
+    std::vector<GA_Size> remaining(n);
+    for ( GA_Size i = 0; i < n; ++i ) {
+        remaining[i] = i;
+    }
+    // A corner is an ear if it is convex and no other remaining vertex lies inside it.
+    auto is_ear = [&](GA_Size a, GA_Size b, GA_Size c) {
+        if (cross(a, b, c) <= 0.0) return false;
+        for ( GA_Size p : remaining ) {
+            if (p == a || p == b || p == c) continue;
+            if (cross(a, b, p) >= 0.0 && cross(b, c, p) >= 0.0 && cross(c, a, p) >= 0.0) return false;
+        }
+        return true;
+    };
+    while (remaining.size() > 3) {
+        std::size_t m = remaining.size();
+        std::size_t ear = 0;
+        for ( std::size_t k = 0; k < m; ++k ) {
+            if (is_ear(remaining[(k + m - 1) % m], remaining[k], remaining[(k + 1) % m])) {
+                ear = k;
+                break;
+            }
         }
-        i += 1;
+        // Degenerate polygons may have no ears, in which case the first corner is clipped.
+        tris.push_back({remaining[(ear + m - 1) % m], remaining[ear], remaining[(ear + 1) % m]});
+        remaining.erase(remaining.begin() + ear);
     }
-
-    auto name = attrib->getName().c_str();
-    add_attrib(mesh, mesh_prim_attrib_location<M>(), name, tuple_size, strings, indices);
+    tris.push_back({remaining[0], remaining[1], remaining[2]});
+    return tris;
 }
 
+// Extract points and faces or cells of the given mesh type from the detail.
 template<typename M>
-void fill_point_str_attrib(
-        const GU_Detail &detail,
-        const GA_AIFSharedStringTuple *aif,
-        const GA_Attribute *attrib,
-        std::size_t tuple_size,
-        std::size_t num_elem,
-        const std::vector<bool> &group,
-        M *mesh)
+Extracted extract_topology(const GU_Detail &detail, const ExtractFlags &flags)
 {
-    // Try with different types
-    std::vector<int64_t> ids(aif->getTableEntries(attrib), -1);
-    std::vector<rust::Str> strings;
-    for (auto it = aif->begin(attrib); !it.atEnd(); ++it) {
-        ids[it.getHandle()] = strings.size();
-        strings.push_back( rust::Str(it.getString()) );
+    Extracted result;
+    result.points.assign(detail.getNumPointOffsets(), false);
+    std::vector<std::size_t> face_sizes;
+    for_each_face<M>(detail, [&](GA_Offset prim_off, const UT_Array<GA_Offset> &vertices) {
+        if (triangulates_polygons<M>() && vertices.size() > 3
+                && detail.getPrimitiveTypeId(prim_off) != GA_PRIMTETRAHEDRON) {
+            // Each triangle keeps the source primitive and vertices of the polygon, so
+            // primitive and vertex attributes are duplicated onto all of its triangles.
+            for ( const auto &tri : triangulate_polygon(detail, vertices, flags.triangulation) ) {
+                result.prims.push_back(prim_off);
+                face_sizes.push_back(3);
+                for ( GA_Size idx : tri ) {
+                    result.vertices.push_back(vertices[idx]);
+                    result.points[detail.vertexPoint(vertices[idx])] = true;
+                }
+            }
+            return;
+        }
+        result.prims.push_back(prim_off);
+        face_sizes.push_back(vertices.size());
+        for ( GA_Offset vtx_off : vertices ) {
+            result.vertices.push_back(vtx_off);
+            result.points[detail.vertexPoint(vtx_off)] = true;
+        }
+    });
+    if (!flags.compact_points) {
+        for ( GA_Offset pt_off : detail.getPointRange() ) {
+            result.points[pt_off] = true;
+        }
     }
 
-    std::vector<int64_t> indices(tuple_size*num_elem, -1);
+    std::vector<std::size_t> new_index(detail.getNumPointOffsets(), 0);
+    result.point_offsets = marked_points(detail, result.points);
+    for ( exint i = 0; i < result.point_offsets.size(); ++i ) {
+        GA_Offset pt_off = result.point_offsets(i);
+        new_index[pt_off] = i;
+        result.point_indices.push_back(detail.pointIndex(pt_off));
+    }
 
-    int i = 0;
-    for ( GA_Offset pt_off : detail.getPointRange() )
-    {
-        if (!group[pt_off]) continue;
-        for ( int k = 0, k_end = tuple_size; k < k_end; ++k ) {
-            GA_StringIndexType handle = aif->getHandle(attrib, pt_off, k);
-            indices[tuple_size*i + k] = handle > -1 ? ids[handle] : -1;
+    result.indices.reserve(face_sizes.size() + result.vertices.size());
+    auto vtx_it = result.vertices.begin();
+    for ( std::size_t n : face_sizes ) {
+        result.indices.push_back(n);
+        for ( std::size_t i = 0; i < n; ++i, ++vtx_it ) {
+            result.indices.push_back(new_index[detail.vertexPoint(*vtx_it)]);
         }
-        i += 1;
     }
-
-    auto name = attrib->getName().c_str();
-    add_attrib(mesh, AttribLocation::VERTEX, name, tuple_size, strings, indices);
+    return result;
 }
 
-template<typename M>
-void fill_vertex_str_attrib(
-        const GU_Detail &detail,
-        const GA_AIFSharedStringTuple *aif,
-        const GA_Attribute *attrib,
-        std::size_t tuple_size,
-        std::size_t num_elem,
-        M *mesh)
+// String attribute values read from a detail, waiting to be added to a mesh.
+struct StrValues {
+    const GA_Attribute *attrib = nullptr;
+    std::size_t tuple_size = 0;
+    // Unique strings and per element indices into them.
+    std::vector<rust::Str> strings;
+    std::vector<int64_t> indices;
+};
+
+StrValues read_str_values(const GA_Attribute *attrib, const GA_OffsetList &offsets)
 {
-    // Try with different types
+    auto aif = attrib->getAIFSharedStringTuple();
+    StrValues result;
+    result.attrib = attrib;
+    result.tuple_size = attrib->getTupleSize();
+
     std::vector<int64_t> ids(aif->getTableEntries(attrib), -1);
-    std::vector<rust::Str> strings;
     for (auto it = aif->begin(attrib); !it.atEnd(); ++it) {
-        ids[it.getHandle()] = strings.size();
-        strings.push_back( rust::Str(it.getString()) );
+        ids[it.getHandle()] = result.strings.size();
+        result.strings.push_back( rust::Str(it.getString()) );
     }
 
-    std::vector<int64_t> indices(tuple_size*num_elem, -1);
-
-    int i = 0;
-    for ( GA_Offset prim_off : detail.getPrimitiveRange() ) {
-        const GEO_Primitive *prim = detail.getGEOPrimitive(prim_off);
-        GA_Size num_prim_verts = detail.getPrimitiveVertexCount(prim_off);
-        if (!is_valid_prim_type<M>(prim->getTypeId(), num_prim_verts)) continue;
-        for (GA_Size idx = 0; idx < num_prim_verts; ++idx) {
-            auto vtx_off = detail.getPrimitiveVertexOffset(prim_off, idx);
-            for (int k = 0, k_end = tuple_size; k < k_end; ++k) {
-                GA_StringIndexType handle = aif->getHandle(attrib, vtx_off, k);
-                indices[tuple_size * i + k] = handle > -1 ? ids[handle] : -1;
+    std::size_t tuple_size = result.tuple_size;
+    result.indices.assign(tuple_size*offsets.size(), -1);
+    UTparallelFor(UT_BlockedRange<exint>(0, offsets.size(), GA_PAGE_SIZE), [&](const UT_BlockedRange<exint> &r) {
+        for (exint i = r.begin(); i < r.end(); ++i) {
+            for ( int k = 0, k_end = tuple_size; k < k_end; ++k ) {
+                GA_StringIndexType handle = aif->getHandle(attrib, offsets(i), k);
+                result.indices[tuple_size*i + k] = handle > -1 ? ids[handle] : -1;
             }
-            i += 1;
         }
-    }
-
-    auto name = attrib->getName().c_str();
-    add_attrib(mesh, mesh_vertex_attrib_location<M>(), name, tuple_size, strings, indices);
+    });
+    return result;
 }
 
-template<typename M>
-void transfer_primitive_attributes(const GU_Detail& detail, M* mesh, std::size_t num_prims) {
-    // Get prim data attributes
-    for (auto it = detail.getAttributeDict(GA_ATTRIB_PRIMITIVE).begin(GA_SCOPE_PUBLIC); !it.atEnd(); ++it)
-    {
-        GA_Attribute *attrib = it.attrib();
-        std::size_t tuple_size = attrib->getTupleSize();
-        {
-            auto aif = attrib->getAIFTuple(); // array of data
-            if ( aif )
-            {
-                switch (aif->getStorage(attrib)) {
-                    case GA_STORE_BOOL:
-                        fill_prim_attrib<int8, M, int32>(detail, aif, attrib, tuple_size, num_prims, mesh); break;
-                    case GA_STORE_INT8:
-                        fill_prim_attrib<int8, M, int32>(detail, aif, attrib, tuple_size, num_prims, mesh); break;
-                    case GA_STORE_INT32:
-                        fill_prim_attrib<int32>(detail, aif, attrib, tuple_size, num_prims, mesh); break;
-                    case GA_STORE_INT64:
-                        fill_prim_attrib<int64_t>(detail, aif, attrib, tuple_size, num_prims, mesh); break;
-                    case GA_STORE_REAL32:
-                        fill_prim_attrib<fpreal32>(detail, aif, attrib, tuple_size, num_prims, mesh); break;
-                    case GA_STORE_REAL64:
-                        fill_prim_attrib<fpreal64>(detail, aif, attrib, tuple_size, num_prims, mesh); break;
-                    default: break; // do nothing
-                }
-            }
-        }
+// Type of the mesh attribute storing values of the given numeric storage.
+DataType tuple_data_type(GA_Storage storage)
+{
+    switch (storage) {
+        case GA_STORE_BOOL:
+        case GA_STORE_INT8: return DataType::I8;
+        case GA_STORE_INT32: return DataType::I32;
+        case GA_STORE_INT64: return DataType::I64;
+        case GA_STORE_REAL32: return DataType::F32;
+        case GA_STORE_REAL64: return DataType::F64;
+        default: return DataType::UNSUPPORTED;
+    }
+}
 
-        {
-            auto aif = attrib->getAIFSharedStringTuple(); // array of strings
-            if ( aif ) {
-                aif->compactStorage(attrib);
-                fill_prim_str_attrib(detail, aif, attrib, tuple_size, num_prims, mesh);
-            }
+// A numeric attribute being copied directly into the storage of a mesh attribute.
+struct AttribTarget {
+    const GA_Attribute *attrib;
+    const GA_AIFTuple *aif;
+    std::size_t tuple_size;
+    DataType type;
+    uint8_t *data;
+};
+
+// Copy values of the target attribute on the elements at positions [begin, end) of `offsets`.
+//
+// Only the corresponding range of the mesh attribute is written, so disjoint ranges can be
+// copied concurrently.
+void fill_range(const AttribTarget &target, const GA_OffsetList &offsets, exint begin, exint end)
+{
+    GA_OffsetList chunk;
+    chunk.reserve(end - begin);
+    for (exint i = begin; i < end; ++i) {
+        chunk.append(offsets(i));
+    }
+    GA_Range range(target.attrib->getIndexMap(), chunk);
+    std::size_t first = target.tuple_size*begin;
+    switch (target.type) {
+        case DataType::I8: {
+            // There is no 8 bit interface for reading tuples.
+            std::vector<int32> values(target.tuple_size*(end - begin));
+            target.aif->getRange(target.attrib, range, values.data(), 0, target.tuple_size);
+            std::copy(values.begin(), values.end(), reinterpret_cast<int8 *>(target.data) + first);
+            break;
         }
-
-        // don't know how to handle these yet.
-        //aif = attrib->getAIFNumericArray(); // variable sized array
-        //aif = attrib->getAIFSharedStringArray(); // variable sized array of strings
+        case DataType::I32:
+            target.aif->getRange(target.attrib, range, reinterpret_cast<int32 *>(target.data) + first, 0, target.tuple_size);
+            break;
+        case DataType::I64:
+            target.aif->getRange(target.attrib, range, reinterpret_cast<int64_t *>(target.data) + first, 0, target.tuple_size);
+            break;
+        case DataType::F32:
+            target.aif->getRange(target.attrib, range, reinterpret_cast<fpreal32 *>(target.data) + first, 0, target.tuple_size);
+            break;
+        case DataType::F64:
+            target.aif->getRange(target.attrib, range, reinterpret_cast<fpreal64 *>(target.data) + first, 0, target.tuple_size);
+            break;
+        default: break; // do nothing
     }
 }
 
-// Transfer attributes from points marked in the given pt_grp
+// Transfer all public attributes of the given owner on the given elements to the mesh.
+//
+// Numeric attributes are first added to the mesh together, and then filled in parallel across
+// attributes and pages of elements, with each task writing a disjoint range of a single mesh
+// attribute. The result does not depend on scheduling. String attributes are read in parallel
+// and added afterwards.
 template<typename M>
-void transfer_point_attributes(const GU_Detail& detail, M* mesh, const std::vector<bool>& pt_grp)
+void transfer_owner_attributes(
+        const GU_Detail& detail,
+        M* mesh,
+        GA_AttributeOwner owner,
+        const GA_OffsetList &offsets,
+        AttribLocation where)
 {
-    std::size_t num_points = std::count(pt_grp.begin(), pt_grp.end(), true);
-    for (auto it = detail.getAttributeDict(GA_ATTRIB_POINT).begin(GA_SCOPE_PUBLIC); !it.atEnd(); ++it)
+    std::vector<AttribTarget> targets;
+    std::vector<AttribLayout> layouts;
+    std::vector<const GA_Attribute *> str_attribs;
+    for (auto it = detail.getAttributeDict(owner).begin(GA_SCOPE_PUBLIC); !it.atEnd(); ++it)
     {
         GA_Attribute *attrib = it.attrib();
-        if (attrib->getTypeInfo() == GA_TYPE_POINT) // ignore position attribute
+        if (owner == GA_ATTRIB_POINT && attrib->getTypeInfo() == GA_TYPE_POINT) // ignore position attribute
             continue;
         std::size_t tuple_size = attrib->getTupleSize();
-        {
-            auto aif = attrib->getAIFTuple(); // array of data
-            if ( aif )
-            {
-                switch (aif->getStorage(attrib)) {
-                    case GA_STORE_BOOL:
-                        fill_point_attrib<int8, M, int32>(detail, aif, attrib, tuple_size, num_points, pt_grp, mesh); break;
-                    case GA_STORE_INT8:
-                        fill_point_attrib<int8, M, int32>(detail, aif, attrib, tuple_size, num_points, pt_grp, mesh); break;
-                    case GA_STORE_INT32:
-                        fill_point_attrib<int32>(detail, aif, attrib, tuple_size, num_points, pt_grp, mesh); break;
-                    case GA_STORE_INT64:
-                        fill_point_attrib<int64_t>(detail, aif, attrib, tuple_size, num_points, pt_grp, mesh); break;
-                    case GA_STORE_REAL32:
-                        fill_point_attrib<fpreal32>(detail, aif, attrib, tuple_size, num_points, pt_grp, mesh); break;
-                    case GA_STORE_REAL64:
-                        fill_point_attrib<fpreal64>(detail, aif, attrib, tuple_size, num_points, pt_grp, mesh); break;
-                    default: break; // do nothing
-                }
-            }
-        }
-
-
-        {
-            auto aif = attrib->getAIFSharedStringTuple(); // array of strings
-            if ( aif ) {
-                aif->compactStorage(attrib);
-                fill_point_str_attrib(detail, aif, attrib, tuple_size, num_points, pt_grp, mesh);
-            }
+        if (auto aif = attrib->getAIFTuple()) { // array of data
+            DataType type = tuple_data_type(aif->getStorage(attrib));
+            if (type == DataType::UNSUPPORTED)
+                continue;
+            targets.push_back(AttribTarget{ attrib, aif, tuple_size, type, nullptr });
+            layouts.push_back(AttribLayout{ rust::String(attrib->getName().c_str()), tuple_size, type });
+        } else if (auto str_aif = attrib->getAIFSharedStringTuple()) { // array of strings
+            str_aif->compactStorage(attrib);
+            str_attribs.push_back(attrib);
         }
 
         // don't know how to handle these yet.
         //aif = attrib->getAIFNumericArray(); // variable sized array
         //aif = attrib->getAIFSharedStringArray(); // variable sized array of strings
     }
-}
 
-// Transfer attributes from vertices marked in the given vtx_grp
-template<typename M>
-void transfer_vertex_attributes(const GU_Detail& detail, M* mesh, std::size_t num_vertices)
-{
-    for (auto it = detail.getAttributeDict(GA_ATTRIB_VERTEX).begin(GA_SCOPE_PUBLIC); !it.atEnd(); ++it)
     {
-        GA_Attribute *attrib = it.attrib();
-        std::size_t tuple_size = attrib->getTupleSize();
-        {
-            auto aif = attrib->getAIFTuple(); // array of data
-            if ( aif )
-            {
-                switch (aif->getStorage(attrib)) {
-                    case GA_STORE_BOOL:
-                        fill_vertex_attrib<int8, M, int32>(detail, aif, attrib, tuple_size, num_vertices, mesh); break;
-                    case GA_STORE_INT8:
-                        fill_vertex_attrib<int8, M, int32>(detail, aif, attrib, tuple_size, num_vertices, mesh); break;
-                    case GA_STORE_INT32:
-                        fill_vertex_attrib<int32>(detail, aif, attrib, tuple_size, num_vertices, mesh); break;
-                    case GA_STORE_INT64:
-                        fill_vertex_attrib<int64_t>(detail, aif, attrib, tuple_size, num_vertices, mesh); break;
-                    case GA_STORE_REAL32:
-                        fill_vertex_attrib<fpreal32>(detail, aif, attrib, tuple_size, num_vertices, mesh); break;
-                    case GA_STORE_REAL64:
-                        fill_vertex_attrib<fpreal64>(detail, aif, attrib, tuple_size, num_vertices, mesh); break;
-                    default: break; // do nothing
-                }
-            }
+        auto buffers = mesh->add_attribs_zeros(where, rust::Slice<const AttribLayout>(layouts.data(), layouts.size()));
+        for (std::size_t i = 0; i < targets.size(); ++i) {
+            auto bytes = buffers->take(i);
+            targets[i].data = bytes.size() > 0 ? bytes.data() : nullptr;
         }
+    }
+    // Attributes with unsupported tuple sizes or no elements have no values to fill.
+    targets.erase(
+            std::remove_if(targets.begin(), targets.end(), [](const AttribTarget &t) { return !t.data; }),
+            targets.end());
+
+    exint num_pages = (offsets.size() + GA_PAGE_SIZE - 1) / GA_PAGE_SIZE;
+    UTparallelFor(UT_BlockedRange<exint>(0, targets.size()*num_pages), [&](const UT_BlockedRange<exint> &r) {
+        for (exint task = r.begin(); task < r.end(); ++task) {
+            exint begin = (task % num_pages)*GA_PAGE_SIZE;
+            exint end = std::min(begin + GA_PAGE_SIZE, exint(offsets.size()));
+            fill_range(targets[task / num_pages], offsets, begin, end);
+        }
+    });
 
-
-        {
-            auto aif = attrib->getAIFSharedStringTuple(); // array of strings
-            if ( aif ) {
-                aif->compactStorage(attrib);
-                fill_vertex_str_attrib(detail, aif, attrib, tuple_size, num_vertices, mesh);
-            }
+    std::vector<StrValues> values(str_attribs.size());
+    UTparallelFor(UT_BlockedRange<std::size_t>(0, str_attribs.size(), 1), [&](const UT_BlockedRange<std::size_t> &r) {
+        for (std::size_t i = r.begin(); i < r.end(); ++i) {
+            values[i] = read_str_values(str_attribs[i], offsets);
         }
+    });
 
-        // don't know how to handle these yet.
-        //aif = attrib->getAIFNumericArray(); // variable sized array
-        //aif = attrib->getAIFSharedStringArray(); // variable sized array of strings
+    for ( const StrValues &str_values : values ) {
+        add_attrib(mesh, where, str_values.attrib->getName().c_str(), str_values.tuple_size, str_values.strings, str_values.indices);
     }
 }
 
 template<typename M>
-void transfer_attributes(const GU_Detail& detail, M* mesh, std::size_t num_prims)
+void transfer_primitive_attributes(const GU_Detail& detail, M* mesh, const std::vector<GA_Offset>& prims) {
+    transfer_owner_attributes(detail, mesh, GA_ATTRIB_PRIMITIVE, to_offset_list(prims), mesh_prim_attrib_location<M>());
+}
+
+// Transfer attributes from points marked in the given pt_grp
+template<typename M>
+void transfer_point_attributes(const GU_Detail& detail, M* mesh, const std::vector<bool>& pt_grp)
 {
-    transfer_primitive_attributes(detail, mesh, num_prims);
+    transfer_owner_attributes(detail, mesh, GA_ATTRIB_POINT, marked_points(detail, pt_grp), AttribLocation::VERTEX);
+}
 
-    std::size_t num_vertices = 0;
-    std::vector<bool> pt_grp;
-    std::tie(pt_grp, num_vertices) = mark_points_and_count_vertices<M>(detail);
+// Transfer attributes from the given vertices in order
+template<typename M>
+void transfer_vertex_attributes(const GU_Detail& detail, M* mesh, const std::vector<GA_Offset>& vertices)
+{
+    transfer_owner_attributes(detail, mesh, GA_ATTRIB_VERTEX, to_offset_list(vertices), mesh_vertex_attrib_location<M>());
+}
 
-    transfer_point_attributes(detail, mesh, pt_grp);
-    transfer_vertex_attributes(detail, mesh, num_vertices);
+template<typename M>
+void transfer_attributes(const GU_Detail& detail, M* mesh, const Extracted& extracted)
+{
+    transfer_primitive_attributes(detail, mesh, extracted.prims);
+    transfer_point_attributes(detail, mesh, extracted.points);
+    transfer_vertex_attributes(detail, mesh, extracted.vertices);
 }
 
+// Flat attribute data borrowed from a mesh, along with its tuple size.
+template<typename T>
+struct TupleSlice {
+    rust::Slice<const T> vec;
+    std::size_t tuple_size;
+};
+
+// Maps element indices to a contiguous block of offsets starting at `start`.
+struct BlockOffsets {
+    GA_Offset start;
+    GA_Offset operator()(std::size_t i) const { return start + GA_Offset(i); }
+};
+
+// Write a contiguous block of elements in bulk, one tuple component at a time.
+//
+// Pages are written in parallel, and each page is written by a single task.
 template<typename HandleType, typename ArrayType>
-void fill_attrib(HandleType h, ArrayType arr, GA_Offset startoff) {
+void fill_attrib(HandleType h, ArrayType arr, BlockOffsets offset) {
     if (h.isInvalid()) return;
-    std::size_t i = 0;
-    auto n = startoff + (arr.vec.size()/arr.tuple_size);
-    for ( GA_Offset off = startoff; off < n; ++off, ++i ) {
+    GA_Size n = arr.vec.size()/arr.tuple_size;
+    GA_SplittableRange range(GA_Range(h.getAttribute()->getIndexMap(), offset.start, offset.start + n));
+    UTparallelFor(range, [&](const GA_SplittableRange &r) {
+        GA_Offset start, end;
+        for (GA_Iterator it(r); it.blockAdvance(start, end); ) {
+            auto src = arr.vec.data() + arr.tuple_size*(start - offset.start);
+            for ( int j = 0; j < arr.tuple_size; ++j ) {
+                h.setBlock(start, end - start, src + j, arr.tuple_size, j);
+            }
+        }
+    });
+}
+
+template<typename HandleType, typename ArrayType, typename OffsetFn>
+void fill_attrib(HandleType h, ArrayType arr, OffsetFn offset) {
+    if (h.isInvalid()) return;
+    auto n = arr.vec.size()/arr.tuple_size;
+    for ( std::size_t i = 0; i < n; ++i ) {
+        GA_Offset off = offset(i);
         for ( int j = 0; j < arr.tuple_size; ++j ) {
             h.set(off, j, arr.vec[arr.tuple_size*i + j]);
         }
     }
 }
 
-void fill_str_attrib(GA_RWHandleS h, const rust::box<TupleVecStr> &arr, GA_Offset startoff) {
+template<typename OffsetFn>
+void fill_str_attrib(GA_RWHandleS h, const rust::box<TupleVecStr> &arr, OffsetFn offset) {
     if (h.isInvalid()) return;
-    std::size_t i = 0;
-    auto n = startoff + (arr->len()/arr->tuple_size());
-    for ( GA_Offset off = startoff; off < n; ++off, ++i ) {
+    auto n = arr->len()/arr->tuple_size();
+    for ( std::size_t i = 0; i < n; ++i ) {
+        GA_Offset off = offset(i);
         for ( int j = 0; j < arr->tuple_size(); ++j ) {
             auto rust_str = arr->at(arr->tuple_size()*i + j);
             h.set(off, j, std::string(rust_str.begin(), rust_str.end()));
@@ -554,44 +741,63 @@ void fill_str_attrib(GA_RWHandleS h, const rust::box<TupleVecStr> &arr, GA_Offse
 }
 
 /** Retrieve attributes from the mesh using the given iterator.
+ *
+ * The `offset` function maps the index of each mesh element to its offset in the detail.
+ * Reserved attributes are skipped.
  */
-void retrieve_attributes(GU_Detail &detail, GA_Offset startoff, rust::box<AttribIter> it, GA_AttributeOwner owner) {
+template<typename OffsetFn>
+void retrieve_attributes_with(GU_Detail &detail, OffsetFn offset, rust::box<AttribIter> it, GA_AttributeOwner owner) {
     for ( ;; ) {
         if (!it->has_next()) break;
         auto attrib = it->next();
         // SAFETY: name_str is immediately copied into a UT_String
         auto name_str = attrib->name();
-        auto name = UT_String(std::string(name_str.begin(), name_str.end()));
+        auto name_std = std::string(name_str.begin(), name_str.end());
+        if (name_std.rfind(RESERVED_ATTRIB_PREFIX, 0) == 0) continue;
+        auto name = UT_String(name_std);
         name.forceValidVariableName();
         auto type = attrib->data_type();
         if (type == DataType::I8 ) {
-            auto arr = attrib->get_data_i8();
+            auto arr = TupleSlice<int8_t>{attrib->data_i8(), attrib->tuple_size()};
             auto h = GA_RWHandleC(detail.addTuple(GA_STORE_INT8, owner, name, arr.tuple_size));
-            fill_attrib(h, arr, startoff);
+            fill_attrib(h, arr, offset);
         } else if (type == DataType::I32 ) {
-            auto arr = attrib->get_data_i32();
+            auto arr = TupleSlice<int32_t>{attrib->data_i32(), attrib->tuple_size()};
             auto h = GA_RWHandleI(detail.addTuple(GA_STORE_INT32, owner, name, arr.tuple_size));
-            fill_attrib(h, arr, startoff);
+            fill_attrib(h, arr, offset);
         } else if (type == DataType::I64 ) {
-            auto arr = attrib->get_data_i64();
+            auto arr = TupleSlice<int64_t>{attrib->data_i64(), attrib->tuple_size()};
             auto h = GA_RWHandleID(detail.addTuple(GA_STORE_INT64, owner, name, arr.tuple_size));
-            fill_attrib(h, arr, startoff);
+            fill_attrib(h, arr, offset);
         } else if (type == DataType::F32 ) {
-            auto arr = attrib->get_data_f32();
+            auto arr = TupleSlice<float>{attrib->data_f32(), attrib->tuple_size()};
             auto h = GA_RWHandleF(detail.addTuple(GA_STORE_REAL32, owner, name, arr.tuple_size));
-            fill_attrib(h, arr, startoff);
+            fill_attrib(h, arr, offset);
         } else if (type == DataType::F64 ) {
-            auto arr = attrib->get_data_f64();
+            auto arr = TupleSlice<double>{attrib->data_f64(), attrib->tuple_size()};
             auto h = GA_RWHandleD(detail.addTuple(GA_STORE_REAL64, owner, name, arr.tuple_size));
-            fill_attrib(h, arr, startoff);
+            fill_attrib(h, arr, offset);
         } else if (type == DataType::STR ) {
             auto box_arr = attrib->get_data_str();
             auto h = GA_RWHandleS(detail.addTuple(GA_STORE_STRING, owner, name, box_arr->tuple_size()));
-            fill_str_attrib(h, box_arr, startoff);
+            fill_str_attrib(h, box_arr, offset);
         }
     }
 }
 
+/** Retrieve attributes from the mesh into contiguous elements starting at `startoff`.
+ */
+void retrieve_attributes(GU_Detail &detail, GA_Offset startoff, rust::box<AttribIter> it, GA_AttributeOwner owner) {
+    retrieve_attributes_with(detail, BlockOffsets{startoff}, std::move(it), owner);
+}
+
+/** Retrieve attributes from the mesh into the elements at the given offsets.
+ */
+void retrieve_attributes_at(GU_Detail &detail, const std::vector<GA_Offset> &offsets, rust::box<AttribIter> it, GA_AttributeOwner owner) {
+    auto offset = [&offsets](std::size_t i) { return offsets[i]; };
+    retrieve_attributes_with(detail, offset, std::move(it), owner);
+}
+
 
 template<typename HandleType, typename ArrayType>
 void update_attrib(HandleType h, ArrayType arr) {
@@ -614,71 +820,102 @@ void update_attributes(GU_Detail& detail, rust::box<AttribIter> it, GA_Attribute
         name.forceValidVariableName();
         auto type = attrib->data_type();
         if (type == DataType::I8 ) {
-            auto arr = attrib->get_data_i8();
+            auto arr = TupleSlice<int8_t>{attrib->data_i8(), attrib->tuple_size()};
             auto h = GA_RWHandleC(detail.addTuple(GA_STORE_INT8, owner, name, arr.tuple_size));
             update_attrib(h, arr);
         } else if (type == DataType::I32 ) {
-            auto arr = attrib->get_data_i32();
+            auto arr = TupleSlice<int32_t>{attrib->data_i32(), attrib->tuple_size()};
             auto h = GA_RWHandleI(detail.addTuple(GA_STORE_INT32, owner, name, arr.tuple_size));
             update_attrib(h, arr);
         } else if (type == DataType::I64 ) {
-            auto arr = attrib->get_data_i64();
+            auto arr = TupleSlice<int64_t>{attrib->data_i64(), attrib->tuple_size()};
             auto h = GA_RWHandleID(detail.addTuple(GA_STORE_INT64, owner, name, arr.tuple_size));
             update_attrib(h, arr);
         } else if (type == DataType::F32 ) {
-            auto arr = attrib->get_data_f32();
+            auto arr = TupleSlice<float>{attrib->data_f32(), attrib->tuple_size()};
             auto h = GA_RWHandleF(detail.addTuple(GA_STORE_REAL32, owner, name, arr.tuple_size));
             update_attrib(h, arr);
         } else if (type == DataType::F64 ) {
-            auto arr = attrib->get_data_f64();
+            auto arr = TupleSlice<double>{attrib->data_f64(), attrib->tuple_size()};
             auto h = GA_RWHandleD(detail.addTuple(GA_STORE_REAL64, owner, name, arr.tuple_size));
             update_attrib(h, arr);
         } // String attributes are not yet supported by updates
     }
 }
 
+// Check if point positions of the given detail are stored in double precision.
+bool is_p64(const GU_Detail &detail) {
+    const GA_Attribute *P = detail.getP();
+    return P->getAIFTuple()->getStorage(P) == GA_STORE_REAL64;
+}
+
+// Write positions of a contiguous block of points through a page handle of type `PageHandle`
+// holding vectors of type `Vector`.
+template<typename PageHandle, typename Vector, typename T>
+void write_block_positions(GU_Detail &detail, GA_Offset startptoff, rust::Slice<const T> point_coords) {
+    GA_Offset endptoff = startptoff + GA_Offset(point_coords.size()/3);
+    GA_SplittableRange range(GA_Range(detail.getPointMap(), startptoff, endptoff));
+    UTparallelFor(range, [&](const GA_SplittableRange &r) {
+        PageHandle P_ph(detail.getP());
+        GA_Offset start, end;
+        for (GA_Iterator it(r); it.blockAdvance(start, end); ) {
+            P_ph.setPage(start);
+            for (GA_Offset ptoff = start; ptoff < end; ++ptoff) {
+                const T *pos = &point_coords[3*(ptoff - startptoff)];
+                P_ph.set(ptoff, Vector(pos[0], pos[1], pos[2]));
+            }
+        }
+    });
+}
+
 /**
- * Add an unstructured mesh to the current detail.
+ * Set positions of a contiguous block of points starting at `startptoff` from flat coordinates.
+ *
+ * Positions are written in double precision if P is stored with 64-bit floats.
  */
-void hdkrs::add_unstructured_mesh(GU_Detail& detail, const hdkrs::UnstructuredMesh& mesh) {
+template<typename T>
+void set_block_positions(GU_Detail &detail, GA_Offset startptoff, rust::Slice<const T> point_coords) {
+    if (is_p64(detail)) {
+        write_block_positions<GA_RWPageHandleV3D, UT_Vector3D>(detail, startptoff, point_coords);
+    } else {
+        write_block_positions<GA_RWPageHandleV3, UT_Vector3>(detail, startptoff, point_coords);
+    }
+}
+
+/**
+ * Add cells from the given unstructured mesh to the current detail.
+ */
+template<typename M>
+void add_cells(GU_Detail& detail, const M& mesh) {
     GA_Offset startvtxoff = GA_Offset(detail.getNumVertexOffsets());
     GA_Offset startprimoff = GA_Offset(0);
-    auto point_coords = mesh.get_point_coords();
+    auto point_coords = mesh.point_coords();
     auto num_points = point_coords.size()/3;
-    auto indices = mesh.get_indices();
-    auto counts = mesh.get_counts();
-    auto sizes = mesh.get_sizes();
-    auto cell_types = mesh.get_cell_types();
+    auto indices = mesh.indices();
+    auto cell_offsets = mesh.cell_offsets();
+    auto block_offsets = mesh.block_offsets();
 
     if (indices.size() > 0) {
         GA_Offset startptoff = detail.appendPointBlock(num_points);
-        for (exint pt_idx = 0; pt_idx < num_points; ++pt_idx) {
-            GA_Offset ptoff = startptoff + pt_idx;
-            detail.setPos3(ptoff, UT_Vector3(&point_coords[3*pt_idx]));
-        }
-
-        std::vector<int> pt_numbers;
-        std::size_t offset = 0;
-        for (std::size_t block_idx = 0; block_idx < cell_types.size(); ++block_idx) {
-            auto count = counts[block_idx];
-            auto size = sizes[block_idx];
-            auto num_indices_in_block = count * size;
-            pt_numbers.clear();
-            for (std::size_t j = 0; j < num_indices_in_block; ++j) {
-                pt_numbers.push_back(indices[offset + j]);
-            }
-            offset += num_indices_in_block;
+        set_block_positions(detail, startptoff, point_coords);
+
+        std::vector<int> pt_numbers(indices.begin(), indices.end());
+        for (std::size_t block_idx = 0; block_idx + 1 < cell_offsets.size(); ++block_idx) {
+            auto count = cell_offsets[block_idx + 1] - cell_offsets[block_idx];
+            auto size = (block_offsets[block_idx + 1] - block_offsets[block_idx]) / count;
+            const int *block_pt_numbers = pt_numbers.data() + (block_offsets[block_idx] - block_offsets[0]);
+            CellType cell_type = mesh.cell_type(block_idx);
             GA_Offset spo;
-            if (cell_types[block_idx] == CellType::Tetrahedron) {
+            if (cell_type == CellType::Tetrahedron) {
                 spo = GEO_PrimTetrahedron::buildBlock(
                         &detail, startptoff, detail.getNumPointOffsets(),
-                        count, pt_numbers.data());
-            } else if (cell_types[block_idx] == CellType::Triangle) {
+                        count, block_pt_numbers);
+            } else if (cell_type == CellType::Triangle) {
                 GEO_PolyCounts polycounts;
                 polycounts.append(size, count);
                 spo = GEO_PrimPoly::buildBlock(
                         &detail, startptoff, detail.getNumPointOffsets(),
-                        polycounts, pt_numbers.data());
+                        polycounts, block_pt_numbers);
             }
             if (block_idx == 0) {
                 startprimoff = spo;
@@ -691,24 +928,35 @@ void hdkrs::add_unstructured_mesh(GU_Detail& detail, const hdkrs::UnstructuredMe
     }
 }
 
+/**
+ * Add an unstructured mesh to the current detail.
+ */
+void hdkrs::add_unstructured_mesh(GU_Detail& detail, const hdkrs::UnstructuredMesh& mesh) {
+    add_cells(detail, mesh);
+}
+
+/**
+ * Add a single precision unstructured mesh to the current detail.
+ */
+void hdkrs::add_unstructured_mesh_f32(GU_Detail& detail, const hdkrs::UnstructuredMeshF32& mesh) {
+    add_cells(detail, mesh);
+}
 
 /**
- * Add a tetmesh to the current detail.
+ * Add tetrahedra from the given mesh to the current detail.
  */
-void hdkrs::add_tetmesh(GU_Detail& detail, const hdkrs::TetMesh& tetmesh) {
+template<typename M>
+void add_tets(GU_Detail& detail, const M& tetmesh) {
     try {
         GA_Offset startvtxoff = GA_Offset(detail.getNumVertexOffsets());
-        auto point_coords = tetmesh.get_point_coords();
+        auto point_coords = tetmesh.point_coords();
         auto num_points = point_coords.size()/3;
-        auto unsigned_indices = tetmesh.get_indices();
+        auto unsigned_indices = tetmesh.indices();
         if (unsigned_indices.size() > 0) {
             std::vector<int> indices(unsigned_indices.begin(), unsigned_indices.end());
 
             GA_Offset startptoff = detail.appendPointBlock(num_points);
-            for (exint pt_idx = 0; pt_idx < num_points; ++pt_idx) {
-                GA_Offset ptoff = startptoff + pt_idx;
-                detail.setPos3(ptoff, UT_Vector3(&point_coords[3*pt_idx]));
-            }
+            set_block_positions(detail, startptoff, point_coords);
 
             GA_Offset startprimoff = GEO_PrimTetrahedron::buildBlock(
                     &detail, startptoff, detail.getNumPointOffsets(),
@@ -724,259 +972,869 @@ void hdkrs::add_tetmesh(GU_Detail& detail, const hdkrs::TetMesh& tetmesh) {
 }
 
 /**
- * Add a polymesh to the current detail.
+ * Add a tetmesh to the current detail.
  */
-void hdkrs::add_polymesh(GU_Detail& detail, const hdkrs::PolyMesh& polymesh) {
+void hdkrs::add_tetmesh(GU_Detail& detail, const hdkrs::TetMesh& tetmesh) {
+    add_tets(detail, tetmesh);
+}
+
+/**
+ * Add a single precision tetmesh to the current detail.
+ */
+void hdkrs::add_tetmesh_f32(GU_Detail& detail, const hdkrs::TetMeshF32& tetmesh) {
+    add_tets(detail, tetmesh);
+}
+
+/**
+ * Add polygons from the given mesh to the current detail.
+ *
+ * Polygons are closed unless `closed` is false, in which case they are built as open curves.
+ * Returns the range of added primitives.
+ */
+template<typename M>
+GA_Range add_polygons(
+        GU_Detail& detail,
+        const M& polymesh,
+        bool closed,
+        AttribLocation prim_loc,
+        AttribLocation vertex_loc)
+{
     GA_Offset startvtxoff = GA_Offset(detail.getNumVertexOffsets());
-    auto point_coords = polymesh.get_point_coords();
+    auto point_coords = polymesh.point_coords();
     auto num_points = point_coords.size()/3;
-    auto test_indices = polymesh.get_indices();
-    if (test_indices.size() > 0) {
+    auto indices = polymesh.indices();
+    auto offsets = polymesh.offsets();
+    if (indices.size() > 0) {
         GA_Offset startptoff = detail.appendPointBlock(num_points);
-        for (exint pt_idx = 0; pt_idx < num_points; ++pt_idx) {
-            GA_Offset ptoff = startptoff + pt_idx;
-            detail.setPos3(ptoff, UT_Vector3(&point_coords[3*pt_idx]));
-        }
+        set_block_positions(detail, startptoff, point_coords);
 
+        // Consecutive polygons with the same number of sides are merged into a single run.
         GEO_PolyCounts polycounts;
-        std::vector<int> poly_pt_numbers;
-        int prev_n = test_indices[0];
-        int num_polys_with_same_shape = 0;
-        for (std::size_t i = 0; i < test_indices.size(); ) {
-            auto n = test_indices[i++];
-            if (n != prev_n) {
-                polycounts.append(prev_n, num_polys_with_same_shape);
-                num_polys_with_same_shape = 0;
-                prev_n = n;
-            }
-            num_polys_with_same_shape += 1;
-            for (std::size_t j = 0; j < n; ++j, ++i) {
-                poly_pt_numbers.push_back(test_indices[i]);
-            }
+        for (std::size_t i = 0; i + 1 < offsets.size(); ++i) {
+            polycounts.append(offsets[i + 1] - offsets[i]);
         }
-        polycounts.append(prev_n, num_polys_with_same_shape); // append last set
+        std::vector<int> poly_pt_numbers(indices.begin(), indices.end());
 
         GA_Offset startprimoff = GEO_PrimPoly::buildBlock(
                 &detail, startptoff, detail.getNumPointOffsets(),
-                polycounts, poly_pt_numbers.data());
+                polycounts, poly_pt_numbers.data(), closed);
 
-        retrieve_attributes(detail, startprimoff, polymesh.attrib_iter(AttribLocation::FACE), GA_ATTRIB_PRIMITIVE);
-        retrieve_attributes(detail, startvtxoff, polymesh.attrib_iter(AttribLocation::FACEVERTEX), GA_ATTRIB_VERTEX);
+        retrieve_attributes(detail, startprimoff, polymesh.attrib_iter(prim_loc), GA_ATTRIB_PRIMITIVE);
+        retrieve_attributes(detail, startvtxoff, polymesh.attrib_iter(vertex_loc), GA_ATTRIB_VERTEX);
         retrieve_attributes(detail, startptoff, polymesh.attrib_iter(AttribLocation::VERTEX), GA_ATTRIB_POINT);
+
+        return GA_Range(detail.getPrimitiveMap(), startprimoff, startprimoff + polycounts.getNumPolygons());
     }
+    return GA_Range();
 }
 
 /**
- * Add a ptcloud to the current detail.
+ * Add a polymesh to the current detail.
  */
-void hdkrs::add_pointcloud(GU_Detail& detail, const hdkrs::PointCloud& ptcloud) {
-    auto point_coords = ptcloud.get_point_coords();
-    auto num_points = point_coords.size()/3;
+void hdkrs::add_polymesh(GU_Detail& detail, const hdkrs::PolyMesh& polymesh) {
+    add_polymesh_with_options(detail, polymesh, AddOptions{});
+}
 
-    GA_Offset startptoff = detail.appendPointBlock(num_points);
+/**
+ * Add polygons from the given mesh to the current detail, merging them into polygon soups if
+ * `polysoup` is enabled.
+ *
+ * Polygons with different primitive attribute values end up in separate soups.
+ */
+template<typename M>
+void add_polygons_with_options(GU_Detail& detail, const M& polymesh, const hdkrs::AddOptions& options) {
+    GA_Range prims = add_polygons(detail, polymesh, true, AttribLocation::FACE, AttribLocation::FACEVERTEX);
+    if (options.polysoup && !prims.empty()) {
+        GA_PrimitiveGroup *group = detail.newInternalPrimitiveGroup();
+        group->addRange(prims);
+        GEO_PolySoupParms parms;
+        parms.primGroup = group;
+        detail.polySoup(parms, &detail);
+        detail.destroyPrimitiveGroup(group);
+    }
+}
 
-    for (exint pt_idx = 0; pt_idx < num_points; ++pt_idx) {
-        GA_Offset ptoff = startptoff + pt_idx;
-        detail.setPos3(ptoff, UT_Vector3(&point_coords[3*pt_idx]));
+/**
+ * Add a polymesh to the current detail with the given options.
+ *
+ * With `polysoup` enabled, the added polygons are merged into polygon soups. Polygons with
+ * different primitive attribute values end up in separate soups.
+ */
+void hdkrs::add_polymesh_with_options(GU_Detail& detail, const hdkrs::PolyMesh& polymesh, const hdkrs::AddOptions& options) {
+    add_polygons_with_options(detail, polymesh, options);
+}
+
+/**
+ * Add the given mesh to the detail, packing primitives with the same `name` into a packed
+ * primitive per name.
+ *
+ * Meshes without primitives or without a `name` primitive attribute are added unpacked.
+ */
+void hdkrs::add_packed_by_name(GU_Detail& detail, const hdkrs::Mesh& mesh, const hdkrs::AddOptions& options) {
+    AddOptions unpacked_options = options;
+    unpacked_options.pack_by_name = false;
+
+    GU_Detail unpacked;
+    mesh.add_to_detail_with_options(unpacked, unpacked_options);
+
+    GA_ROHandleS name_h(&unpacked, GA_ATTRIB_PRIMITIVE, "name");
+    if (name_h.isInvalid() || unpacked.getNumPrimitives() == 0) {
+        detail.merge(unpacked);
+        return;
+    }
+
+    // Group primitives by name, keeping the order in which names first appear.
+    UT_StringArray names;
+    UT_StringMap<GA_OffsetList> pieces;
+    for ( GA_Offset prim_off : unpacked.getPrimitiveRange() ) {
+        UT_StringHolder name = name_h.get(prim_off);
+        if (!pieces.contains(name)) {
+            names.append(name);
+        }
+        pieces[name].append(prim_off);
     }
 
+    GA_RWHandleS packed_name_h(detail.addStringTuple(GA_ATTRIB_PRIMITIVE, "name", 1));
+    for ( const UT_StringHolder &name : names ) {
+        GA_PrimitiveGroup *group = unpacked.newInternalPrimitiveGroup();
+        for ( GA_Offset prim_off : pieces[name] ) {
+            group->addOffset(prim_off);
+        }
+        GU_Detail *piece = new GU_Detail;
+        GU_DetailHandle piece_handle;
+        piece_handle.allocateAndSet(piece);
+        piece->merge(unpacked, group, false);
+        unpacked.destroyPrimitiveGroup(group);
+
+        GU_PrimPacked *packed = GU_PackedGeometry::packGeometry(detail, piece_handle);
+        if (packed) {
+            packed_name_h.set(packed->getMapOffset(), name);
+        }
+    }
+}
+
+/**
+ * Add a single precision polymesh to the current detail.
+ */
+void hdkrs::add_polymesh_f32(GU_Detail& detail, const hdkrs::PolyMeshF32& polymesh) {
+    add_polymesh_f32_with_options(detail, polymesh, AddOptions{});
+}
+
+/**
+ * Add a single precision polymesh to the current detail with the given options as in
+ * `add_polymesh_with_options`.
+ */
+void hdkrs::add_polymesh_f32_with_options(GU_Detail& detail, const hdkrs::PolyMeshF32& polymesh, const hdkrs::AddOptions& options) {
+    add_polygons_with_options(detail, polymesh, options);
+}
+
+/**
+ * Add polylines to the current detail as open polygons.
+ */
+void hdkrs::add_polylines(GU_Detail& detail, const hdkrs::PolylineMesh& polylines) {
+    add_polygons(detail, polylines, false, AttribLocation::CURVE, AttribLocation::CURVEVERTEX);
+}
+
+/**
+ * Add single precision polylines to the current detail as open polygons.
+ */
+void hdkrs::add_polylines_f32(GU_Detail& detail, const hdkrs::PolylineMeshF32& polylines) {
+    add_polygons(detail, polylines, false, AttribLocation::CURVE, AttribLocation::CURVEVERTEX);
+}
+
+/**
+ * Add points from the given point cloud to the current detail.
+ */
+template<typename M>
+void add_points(GU_Detail& detail, const M& ptcloud) {
+    auto point_coords = ptcloud.point_coords();
+    auto num_points = point_coords.size()/3;
+
+    GA_Offset startptoff = detail.appendPointBlock(num_points);
+    set_block_positions(detail, startptoff, point_coords);
+
     retrieve_attributes(detail, startptoff, ptcloud.attrib_iter(AttribLocation::VERTEX), GA_ATTRIB_POINT);
 }
 
+/**
+ * Add a ptcloud to the current detail.
+ */
+void hdkrs::add_pointcloud(GU_Detail& detail, const hdkrs::PointCloud& ptcloud) {
+    add_points(detail, ptcloud);
+}
+
+/**
+ * Add a single precision ptcloud to the current detail.
+ */
+void hdkrs::add_pointcloud_f32(GU_Detail& detail, const hdkrs::PointCloudF32& ptcloud) {
+    add_points(detail, ptcloud);
+}
+
 /**
  * Update points in the detail according to what's in the ptcloud.
  */
 void hdkrs::update_points(GU_Detail& detail, const hdkrs::PointCloud& ptcloud) {
-    auto point_coords = ptcloud.get_point_coords();
+    auto point_coords = ptcloud.point_coords();
     auto num_points = point_coords.size()/3;
 
     for (exint pt_idx = 0; pt_idx < num_points; ++pt_idx) {
         GA_Offset ptoff = detail.pointOffset(pt_idx);
-        detail.setPos3(ptoff, UT_Vector3(&point_coords[3*pt_idx]));
+        detail.setPos3(ptoff, UT_Vector3D(&point_coords[3*pt_idx]));
     }	
 
     update_attributes(detail, ptcloud.attrib_iter(AttribLocation::VERTEX), GA_ATTRIB_POINT);
 }
 
-rust::box<hdkrs::UnstructuredMesh> hdkrs::build_unstructured_mesh(const GU_Detail& detail) {
-    std::vector<double> vertices;
-    vertices.reserve(3*detail.getNumPointOffsets());
-    std::vector<std::size_t> indices;
-    indices.reserve(3*detail.getNumVertexOffsets());
-    std::vector<CellType> cell_types;
-    cell_types.reserve(detail.getNumPrimitives());
+// Offsets of the elements with the given source indices.
+std::vector<GA_Offset> source_offsets(const GA_IndexMap &index_map, const rust::Vec<int64_t> &indices) {
+    std::vector<GA_Offset> offsets;
+    offsets.reserve(indices.size());
+    for ( int64_t idx : indices ) {
+        offsets.push_back(index_map.offsetFromIndex(GA_Index(idx)));
+    }
+    return offsets;
+}
 
-    for ( GA_Offset pt_off : detail.getPointRange() )
-    {
-        UT_Vector3 pos = detail.getPos3(pt_off);
-        vertices.push_back( static_cast<double>(pos[0]) );
-        vertices.push_back( static_cast<double>(pos[1]) );
-        vertices.push_back( static_cast<double>(pos[2]) );
+/**
+ * Write positions and attributes of the given mesh back onto the source points, primitives
+ * and vertices recorded in its reserved index attributes.
+ *
+ * Elements without recorded source indices are left untouched.
+ */
+void hdkrs::scatter_to_source(GU_Detail& detail, const hdkrs::Mesh& mesh) {
+    auto point_indices = mesh.source_indices(AttribLocation::VERTEX);
+    if (!point_indices.empty()) {
+        auto point_coords = mesh.point_coords();
+        auto point_coords_f32 = mesh.point_coords_f32();
+        auto offsets = source_offsets(detail.getPointMap(), point_indices);
+        for (std::size_t i = 0; i < offsets.size(); ++i) {
+            if (point_coords_f32.size() > 0) {
+                detail.setPos3(offsets[i], UT_Vector3(&point_coords_f32[3*i]));
+            } else {
+                detail.setPos3(offsets[i], UT_Vector3D(&point_coords[3*i]));
+            }
+        }
+        retrieve_attributes_at(detail, offsets, mesh.attrib_iter(AttribLocation::VERTEX), GA_ATTRIB_POINT);
     }
 
-    std::size_t num_cells = 0;
-    for ( GA_Offset prim_off : detail.getPrimitiveRange() )
-    {
-        const GEO_Primitive *prim = detail.getGEOPrimitive(prim_off);
-        GA_Size num_prim_verts = detail.getPrimitiveVertexCount(prim_off);
-        if (!is_valid_prim_type<UnstructuredMesh>(prim->getTypeId(), num_prim_verts)) continue;
-        if (prim->getTypeId() == GA_PRIMPOLY) {
-            // General polygons are not supported, so we break these down into a
-            // triangle fan, which is the simplest.
-            const GEO_PrimPoly *poly = static_cast<const GEO_PrimPoly*>(prim);
-            std::size_t num_verts = poly->getVertexCount();
-            // Skip non-triangles.
-            if (num_verts != 3) {
-                continue;
+    // Only one of these locations is populated for any given mesh type.
+    const AttribLocation prim_locs[] = { AttribLocation::FACE, AttribLocation::CELL, AttribLocation::CURVE };
+    const AttribLocation vertex_locs[] = { AttribLocation::FACEVERTEX, AttribLocation::CELLVERTEX, AttribLocation::CURVEVERTEX };
+    for ( AttribLocation loc : prim_locs ) {
+        auto prim_indices = mesh.source_indices(loc);
+        if (!prim_indices.empty()) {
+            auto offsets = source_offsets(detail.getPrimitiveMap(), prim_indices);
+            retrieve_attributes_at(detail, offsets, mesh.attrib_iter(loc), GA_ATTRIB_PRIMITIVE);
+        }
+    }
+    for ( AttribLocation loc : vertex_locs ) {
+        auto vertex_indices = mesh.source_indices(loc);
+        if (!vertex_indices.empty()) {
+            auto offsets = source_offsets(detail.getVertexMap(), vertex_indices);
+            retrieve_attributes_at(detail, offsets, mesh.attrib_iter(loc), GA_ATTRIB_VERTEX);
+        }
+    }
+}
+
+/**
+ * Unpack all packed primitives, including packed fragments, into a copy of the given detail.
+ *
+ * Packed transforms are applied to the unpacked geometry. Unpacked primitives are tagged with
+ * the index of the source packed primitive in a `piece` attribute, and inherit its `name` if
+ * they don't already have one. Nested packed primitives are unpacked recursively, keeping the
+ * piece index of the outermost packed primitive.
+ *
+ * Returns null if the detail has no packed primitives. Throws if a packed primitive cannot be
+ * unpacked, rather than dropping its geometry.
+ */
+std::unique_ptr<GU_Detail> unpack_packed_prims(const GU_Detail &input) {
+    std::unique_ptr<GU_Detail> result;
+    const GU_Detail *detail = &input;
+    exint num_pieces = 0;
+    while (true) {
+        GA_OffsetList packed_prims;
+        for ( GA_Offset prim_off : detail->getPrimitiveRange() ) {
+            if (GU_PrimPacked::isPackedPrimitive(detail->getPrimitiveTypeId(prim_off))) {
+                packed_prims.append(prim_off);
             }
-            cell_types.push_back(CellType::Triangle);
-            indices.push_back(3); // Number of vertices
-            indices.push_back(detail.pointIndex(detail.vertexPoint(poly->getVertexOffset(0))));
-            indices.push_back(detail.pointIndex(detail.vertexPoint(poly->getVertexOffset(1))));
-            indices.push_back(detail.pointIndex(detail.vertexPoint(poly->getVertexOffset(2))));
-            num_cells += 1;
-        } else if (prim->getTypeId() == GA_PRIMTETRAHEDRON) {
-            const GEO_PrimTetrahedron *tet = static_cast<const GEO_PrimTetrahedron*>(prim);
-            cell_types.push_back(CellType::Tetrahedron);
-            indices.push_back(4); // Number of vertices
-            indices.push_back(detail.pointIndex(detail.vertexPoint(tet->fastVertexOffset(0))));
-            indices.push_back(detail.pointIndex(detail.vertexPoint(tet->fastVertexOffset(1))));
-            indices.push_back(detail.pointIndex(detail.vertexPoint(tet->fastVertexOffset(2))));
-            indices.push_back(detail.pointIndex(detail.vertexPoint(tet->fastVertexOffset(3))));
-            num_cells += 1;
         }
+        if (packed_prims.isEmpty()) {
+            break;
+        }
+
+        // Packed primitives found after the first pass were nested in other packed primitives,
+        // so they already carry the piece index of the outermost packed primitive.
+        bool nested = result != nullptr;
+        if (!result) {
+            result = std::make_unique<GU_Detail>();
+            result->duplicate(input);
+        }
+
+        GA_ROHandleS name_h(result.get(), GA_ATTRIB_PRIMITIVE, "name");
+        GA_ROHandleI outer_piece_h(result.get(), GA_ATTRIB_PRIMITIVE, "piece");
+        GA_PrimitiveGroup *packed_group = result->newInternalPrimitiveGroup();
+        for ( GA_Offset prim_off : packed_prims ) {
+            packed_group->addOffset(prim_off);
+            auto packed = static_cast<const GU_PrimPacked *>(result->getPrimitive(prim_off));
+            GU_Detail piece;
+            if (!packed->unpack(piece, nullptr)) {
+                throw std::runtime_error(
+                        "Failed to unpack packed primitive " + std::to_string(result->primitiveIndex(prim_off)));
+            }
+            exint piece_index = nested ? outer_piece_h.get(prim_off) : num_pieces++;
+
+            GA_RWHandleI piece_h(piece.addIntTuple(GA_ATTRIB_PRIMITIVE, "piece", 1));
+            GA_RWHandleS piece_name_h(&piece, GA_ATTRIB_PRIMITIVE, "name");
+            bool set_name = name_h.isValid() && piece_name_h.isInvalid();
+            if (set_name) {
+                piece_name_h = GA_RWHandleS(piece.addStringTuple(GA_ATTRIB_PRIMITIVE, "name", 1));
+            }
+            for ( GA_Offset piece_prim_off : piece.getPrimitiveRange() ) {
+                piece_h.set(piece_prim_off, piece_index);
+                if (set_name) {
+                    piece_name_h.set(piece_prim_off, name_h.get(prim_off));
+                }
+            }
+            result->merge(piece);
+        }
+        result->destroyPrimitives(result->getPrimitiveRange(packed_group), true);
+        result->destroyPrimitiveGroup(packed_group);
+        detail = result.get();
+    }
+    return result;
+}
+
+// Store source primitive and vertex indices of the extracted elements in reserved attributes.
+template<typename M>
+void add_source_indices(const GU_Detail &detail, M *mesh, const Extracted &extracted)
+{
+    std::vector<int64_t> prim_indices;
+    prim_indices.reserve(extracted.prims.size());
+    for ( GA_Offset prim_off : extracted.prims ) {
+        prim_indices.push_back(detail.primitiveIndex(prim_off));
+    }
+    add_attrib(mesh, mesh_prim_attrib_location<M>(), PRIM_INDEX_ATTRIB, 1, prim_indices);
+    std::vector<int64_t> vertex_indices;
+    vertex_indices.reserve(extracted.vertices.size());
+    for ( GA_Offset vtx_off : extracted.vertices ) {
+        vertex_indices.push_back(detail.vertexIndex(vtx_off));
+    }
+    add_attrib(mesh, mesh_vertex_attrib_location<M>(), VERTEX_INDEX_ATTRIB, 1, vertex_indices);
+}
+
+// Store the index of the primitive each face or cell was extracted from in a public primitive
+// attribute, which gives the polygon each triangle was split from.
+//
+// Attributes of the same name on the detail take precedence.
+template<typename M>
+void add_source_polygons(const GU_Detail &detail, M *mesh, const Extracted &extracted)
+{
+    if (detail.findPrimitiveAttribute(SOURCE_POLYGON_ATTRIB)) {
+        return;
     }
+    std::vector<int64_t> prim_indices;
+    prim_indices.reserve(extracted.prims.size());
+    for ( GA_Offset prim_off : extracted.prims ) {
+        prim_indices.push_back(detail.primitiveIndex(prim_off));
+    }
+    add_attrib(mesh, mesh_prim_attrib_location<M>(), SOURCE_POLYGON_ATTRIB, 1, prim_indices);
+}
 
-    // Only creating a mesh if there are cells.
-    if (num_cells == 0) {
-        throw std::runtime_error("No primitivies found");
+// Tetrahedron indices of the extracted topology, which are not prefixed by the number of
+// vertices in each cell.
+std::vector<std::size_t> tet_indices(const Extracted &extracted) {
+    std::vector<std::size_t> indices;
+    indices.reserve(4*extracted.prims.size());
+    for ( std::size_t i = 0; i < extracted.indices.size(); i += 5 ) {
+        indices.insert(indices.end(), &extracted.indices[i+1], &extracted.indices[i+5]);
     }
+    return indices;
+}
 
-    rust::Slice vertices_slice(static_cast<const double *>(vertices.data()), vertices.size());
-    rust::Slice indices_slice(static_cast<const size_t *>(indices.data()), indices.size());
-    rust::Slice types_slice(static_cast<const CellType *>(cell_types.data()), cell_types.size());
-    rust::box<UnstructuredMesh> mesh = make_unstructured_mesh(vertices_slice, indices_slice, types_slice);
+// Create a mesh of the given type from extracted topology.
+template<typename M>
+rust::box<M> make_mesh(const GU_Detail &detail, const Extracted &extracted);
 
-    auto mesh_ptr = mesh.into_raw();
-    transfer_attributes(detail, mesh_ptr, num_cells);
-    return rust::box<UnstructuredMesh>::from_raw(mesh_ptr);
+template<>
+rust::box<PolyMesh> make_mesh<PolyMesh>(const GU_Detail &detail, const Extracted &extracted) {
+    auto coords = mesh_point_coords<PolyMesh>(detail, extracted.point_offsets);
+    return make_polymesh(
+            rust::Slice(static_cast<const double *>(coords.data()), coords.size()),
+            rust::Slice(static_cast<const size_t *>(extracted.indices.data()), extracted.indices.size()));
 }
 
-rust::box<hdkrs::TetMesh> hdkrs::build_tetmesh(const GU_Detail& detail) {
-    // Get tets for the solid from the first input
-    std::vector<double> tet_vertices;
-    tet_vertices.reserve(3*detail.getNumPointOffsets());
-    std::vector<std::size_t> tet_indices;
-    tet_indices.reserve(3*detail.getNumVertexOffsets());
+template<>
+rust::box<PolyMeshF32> make_mesh<PolyMeshF32>(const GU_Detail &detail, const Extracted &extracted) {
+    auto coords = mesh_point_coords<PolyMeshF32>(detail, extracted.point_offsets);
+    return make_polymesh_f32(
+            rust::Slice(static_cast<const float *>(coords.data()), coords.size()),
+            rust::Slice(static_cast<const size_t *>(extracted.indices.data()), extracted.indices.size()));
+}
 
-    for ( GA_Offset pt_off : detail.getPointRange() )
-    {
-        UT_Vector3 pt = detail.getPos3(pt_off);
-        tet_vertices.push_back( static_cast<double>(pt[0]) );
-        tet_vertices.push_back( static_cast<double>(pt[1]) );
-        tet_vertices.push_back( static_cast<double>(pt[2]) );
+template<>
+rust::box<PolylineMesh> make_mesh<PolylineMesh>(const GU_Detail &detail, const Extracted &extracted) {
+    auto coords = mesh_point_coords<PolylineMesh>(detail, extracted.point_offsets);
+    return make_polylines(
+            rust::Slice(static_cast<const double *>(coords.data()), coords.size()),
+            rust::Slice(static_cast<const size_t *>(extracted.indices.data()), extracted.indices.size()));
+}
+
+template<>
+rust::box<PolylineMeshF32> make_mesh<PolylineMeshF32>(const GU_Detail &detail, const Extracted &extracted) {
+    auto coords = mesh_point_coords<PolylineMeshF32>(detail, extracted.point_offsets);
+    return make_polylines_f32(
+            rust::Slice(static_cast<const float *>(coords.data()), coords.size()),
+            rust::Slice(static_cast<const size_t *>(extracted.indices.data()), extracted.indices.size()));
+}
+
+template<>
+rust::box<TetMesh> make_mesh<TetMesh>(const GU_Detail &detail, const Extracted &extracted) {
+    auto coords = mesh_point_coords<TetMesh>(detail, extracted.point_offsets);
+    auto indices = tet_indices(extracted);
+    return make_tetmesh(
+            rust::Slice(static_cast<const double *>(coords.data()), coords.size()),
+            rust::Slice(static_cast<const size_t *>(indices.data()), indices.size()));
+}
+
+template<>
+rust::box<TetMeshF32> make_mesh<TetMeshF32>(const GU_Detail &detail, const Extracted &extracted) {
+    auto coords = mesh_point_coords<TetMeshF32>(detail, extracted.point_offsets);
+    auto indices = tet_indices(extracted);
+    return make_tetmesh_f32(
+            rust::Slice(static_cast<const float *>(coords.data()), coords.size()),
+            rust::Slice(static_cast<const size_t *>(indices.data()), indices.size()));
+}
+
+// Type of each cell of the extracted topology, which only contains tetrahedra and triangles.
+std::vector<CellType> cell_types(const GU_Detail &detail, const Extracted &extracted) {
+    std::vector<CellType> cell_types;
+    cell_types.reserve(extracted.prims.size());
+    for ( GA_Offset prim_off : extracted.prims ) {
+        if (detail.getPrimitiveTypeId(prim_off) == GA_PRIMTETRAHEDRON) {
+            cell_types.push_back(CellType::Tetrahedron);
+        } else {
+            cell_types.push_back(CellType::Triangle);
+        }
     }
+    return cell_types;
+}
 
-    std::size_t num_tets = 0;
-    for ( GA_Offset prim_off : detail.getPrimitiveRange() )
-    {
-        const GEO_Primitive *prim = detail.getGEOPrimitive(prim_off);
-        if (prim->getTypeId() == GA_PRIMTETRAHEDRON) {
-            num_tets += 1;
-            const GEO_PrimTetrahedron *tet = static_cast<const GEO_PrimTetrahedron*>(prim);
-            tet_indices.push_back(detail.pointIndex(detail.vertexPoint(tet->fastVertexOffset(0))));
-            tet_indices.push_back(detail.pointIndex(detail.vertexPoint(tet->fastVertexOffset(1))));
-            tet_indices.push_back(detail.pointIndex(detail.vertexPoint(tet->fastVertexOffset(2))));
-            tet_indices.push_back(detail.pointIndex(detail.vertexPoint(tet->fastVertexOffset(3))));
+template<>
+rust::box<UnstructuredMesh> make_mesh<UnstructuredMesh>(const GU_Detail &detail, const Extracted &extracted) {
+    auto types = cell_types(detail, extracted);
+    auto coords = mesh_point_coords<UnstructuredMesh>(detail, extracted.point_offsets);
+    return make_unstructured_mesh(
+            rust::Slice(static_cast<const double *>(coords.data()), coords.size()),
+            rust::Slice(static_cast<const size_t *>(extracted.indices.data()), extracted.indices.size()),
+            rust::Slice(static_cast<const CellType *>(types.data()), types.size()));
+}
+
+template<>
+rust::box<UnstructuredMeshF32> make_mesh<UnstructuredMeshF32>(const GU_Detail &detail, const Extracted &extracted) {
+    auto types = cell_types(detail, extracted);
+    auto coords = mesh_point_coords<UnstructuredMeshF32>(detail, extracted.point_offsets);
+    return make_unstructured_mesh_f32(
+            rust::Slice(static_cast<const float *>(coords.data()), coords.size()),
+            rust::Slice(static_cast<const size_t *>(extracted.indices.data()), extracted.indices.size()),
+            rust::Slice(static_cast<const CellType *>(types.data()), types.size()));
+}
+
+// Create a point cloud of the given type from flat point coordinates.
+template<typename M>
+rust::box<M> make_points(const std::vector<typename MeshScalar<M>::type> &coords);
+
+template<>
+rust::box<PointCloud> make_points<PointCloud>(const std::vector<double> &coords) {
+    return make_pointcloud(rust::Slice(static_cast<const double *>(coords.data()), coords.size()));
+}
+
+template<>
+rust::box<PointCloudF32> make_points<PointCloudF32>(const std::vector<float> &coords) {
+    return make_pointcloud_f32(rust::Slice(static_cast<const float *>(coords.data()), coords.size()));
+}
+
+// Extract a mesh of the given type from an unpacked detail.
+//
+// Returns nothing if the detail has no primitives of the given type.
+template<typename M>
+std::optional<rust::box<M>> extract_mesh(const GU_Detail &detail, const ExtractFlags &flags, std::vector<bool> *used_points = nullptr)
+{
+    Extracted extracted = extract_topology<M>(detail, flags);
+    if (extracted.prims.empty()) {
+        return std::nullopt;
+    }
+    if (used_points) {
+        for ( GA_Offset vtx_off : extracted.vertices ) {
+            (*used_points)[detail.vertexPoint(vtx_off)] = true;
+        }
+    }
+
+    M *mesh_ptr = make_mesh<M>(detail, extracted).into_raw();
+    transfer_attributes(detail, mesh_ptr, extracted);
+    if (flags.source_polygons && triangulates_polygons<M>()) {
+        add_source_polygons(detail, mesh_ptr, extracted);
+    }
+    // Compacted points keep their original point numbers, unless those belong to an unpacked
+    // copy rather than the source detail.
+    if (flags.source_indices || (flags.compact_points && !flags.unpacked)) {
+        add_attrib(mesh_ptr, AttribLocation::VERTEX, POINT_INDEX_ATTRIB, 1, extracted.point_indices);
+    }
+    if (flags.source_indices) {
+        add_source_indices(detail, mesh_ptr, extracted);
+    }
+    return rust::box<M>::from_raw(mesh_ptr);
+}
+
+// Check if the given detail contains any packed primitives.
+bool has_packed_prims(const GU_Detail &detail)
+{
+    for ( GA_Offset prim_off : detail.getPrimitiveRange() ) {
+        if (GU_PrimPacked::isPackedPrimitive(detail.getPrimitiveTypeId(prim_off))) {
+            return true;
         }
     }
+    return false;
+}
 
-    // Only creating a mesh if there are tets.
-    if (num_tets == 0) {
-        throw std::runtime_error("No tetrahedra found");
+// Extract a mesh of the given type from the detail, unpacking any packed primitives first.
+//
+// Source indices recorded on an unpacked copy would not refer to the elements of the given
+// detail, so keeping source indices of a detail with packed primitives is an error.
+template<typename M>
+rust::box<M> build_mesh(const GU_Detail &input, const ExtractFlags &flags, const char *missing_error)
+{
+    if (flags.source_indices && has_packed_prims(input)) {
+        throw std::runtime_error("Cannot keep source indices of packed primitives");
     }
+    auto unpacked = unpack_packed_prims(input);
+    const GU_Detail &detail = unpacked ? *unpacked : input;
+    ExtractFlags detail_flags = flags;
+    detail_flags.unpacked = unpacked != nullptr;
+    auto mesh = extract_mesh<M>(detail, detail_flags);
+    if (!mesh) {
+        throw std::runtime_error(missing_error);
+    }
+    return std::move(*mesh);
+}
+
+ExtractFlags extract_flags(const ExtractOptions &options) {
+    ExtractFlags flags;
+    flags.source_indices = options.keep_source_indices;
+    flags.compact_points = options.compact_points;
+    flags.triangulation = options.triangulation;
+    flags.source_polygons = options.source_polygons;
+    return flags;
+}
+
+rust::box<hdkrs::UnstructuredMesh> hdkrs::build_unstructured_mesh(const GU_Detail& detail) {
+    return build_unstructured_mesh_with_options(detail, ExtractOptions{});
+}
+
+rust::box<hdkrs::UnstructuredMesh> hdkrs::build_unstructured_mesh_with_options(const GU_Detail& detail, const hdkrs::ExtractOptions& options) {
+    // Only triangles and tetrahedra are valid cells, so larger polygons, including those inside
+    // polygon soups, are split into triangles.
+    return build_mesh<UnstructuredMesh>(detail, extract_flags(options), "No primitivies found");
+}
+
+rust::box<hdkrs::UnstructuredMeshF32> hdkrs::build_unstructured_mesh_f32(const GU_Detail& detail) {
+    return build_unstructured_mesh_f32_with_options(detail, ExtractOptions{});
+}
+
+rust::box<hdkrs::UnstructuredMeshF32> hdkrs::build_unstructured_mesh_f32_with_options(const GU_Detail& detail, const hdkrs::ExtractOptions& options) {
+    return build_mesh<UnstructuredMeshF32>(detail, extract_flags(options), "No primitivies found");
+}
+
+rust::box<hdkrs::TetMesh> hdkrs::build_tetmesh(const GU_Detail& detail) {
+    return build_tetmesh_with_options(detail, ExtractOptions{});
+}
+
+rust::box<hdkrs::TetMesh> hdkrs::build_tetmesh_with_options(const GU_Detail& detail, const hdkrs::ExtractOptions& options) {
+    return build_mesh<TetMesh>(detail, extract_flags(options), "No tetrahedra found");
+}
 
-    rust::box<TetMesh> tetmesh = make_tetmesh(
-            rust::Slice(static_cast<const double *>(tet_vertices.data()), tet_vertices.size()),
-            rust::Slice(static_cast<const size_t *>(tet_indices.data()), tet_indices.size()));
+rust::box<hdkrs::TetMeshF32> hdkrs::build_tetmesh_f32(const GU_Detail& detail) {
+    return build_tetmesh_f32_with_options(detail, ExtractOptions{});
+}
 
-    TetMesh* tetmesh_ptr = tetmesh.into_raw();
-    transfer_attributes(detail, tetmesh_ptr, num_tets);
-    return rust::box<TetMesh>::from_raw(tetmesh_ptr);
+rust::box<hdkrs::TetMeshF32> hdkrs::build_tetmesh_f32_with_options(const GU_Detail& detail, const hdkrs::ExtractOptions& options) {
+    return build_mesh<TetMeshF32>(detail, extract_flags(options), "No tetrahedra found");
 }
 
 rust::box<hdkrs::PolyMesh> hdkrs::build_polymesh(const GU_Detail& detail) {
-    std::vector<double> poly_vertices;
-    poly_vertices.reserve(3*detail.getNumPointOffsets());
-    std::vector<std::size_t> poly_indices;
-    poly_indices.reserve(3*detail.getNumVertexOffsets());
+    return build_polymesh_with_options(detail, ExtractOptions{});
+}
 
-    for ( GA_Offset pt_off : detail.getPointRange() )
-    {
-        UT_Vector3 pos = detail.getPos3(pt_off);
-        poly_vertices.push_back( static_cast<double>(pos[0]) );
-        poly_vertices.push_back( static_cast<double>(pos[1]) );
-        poly_vertices.push_back( static_cast<double>(pos[2]) );
+rust::box<hdkrs::PolyMesh> hdkrs::build_polymesh_with_options(const GU_Detail& detail, const hdkrs::ExtractOptions& options) {
+    return build_mesh<PolyMesh>(detail, extract_flags(options), "No polygons found");
+}
+
+rust::box<hdkrs::PolyMeshF32> hdkrs::build_polymesh_f32(const GU_Detail& detail) {
+    return build_polymesh_f32_with_options(detail, ExtractOptions{});
+}
+
+rust::box<hdkrs::PolyMeshF32> hdkrs::build_polymesh_f32_with_options(const GU_Detail& detail, const hdkrs::ExtractOptions& options) {
+    return build_mesh<PolyMeshF32>(detail, extract_flags(options), "No polygons found");
+}
+
+rust::box<hdkrs::PolylineMesh> hdkrs::build_polylines(const GU_Detail& detail) {
+    return build_polylines_with_options(detail, ExtractOptions{});
+}
+
+rust::box<hdkrs::PolylineMesh> hdkrs::build_polylines_with_options(const GU_Detail& detail, const hdkrs::ExtractOptions& options) {
+    return build_mesh<PolylineMesh>(detail, extract_flags(options), "No polylines found");
+}
+
+rust::box<hdkrs::PolylineMeshF32> hdkrs::build_polylines_f32(const GU_Detail& detail) {
+    return build_polylines_f32_with_options(detail, ExtractOptions{});
+}
+
+rust::box<hdkrs::PolylineMeshF32> hdkrs::build_polylines_f32_with_options(const GU_Detail& detail, const hdkrs::ExtractOptions& options) {
+    return build_mesh<PolylineMeshF32>(detail, extract_flags(options), "No polylines found");
+}
+
+// Extract all points of the given detail into a point cloud of the given type.
+template<typename M>
+rust::box<M> build_points(const GU_Detail &input)
+{
+    auto unpacked = unpack_packed_prims(input);
+    const GU_Detail &detail = unpacked ? *unpacked : input;
+
+    std::vector<bool> pt_grp(detail.getNumPointOffsets(), false);
+    for ( GA_Offset pt_off : detail.getPointRange() ) {
+        pt_grp[pt_off] = true;
     }
+    auto coords = mesh_point_coords<M>(detail, marked_points(detail, pt_grp));
 
-    std::size_t num_polys = 0;
-    for ( GA_Offset prim_off : detail.getPrimitiveRange() )
-    {
-        const GEO_Primitive *prim = detail.getGEOPrimitive(prim_off);
-        if (prim->getTypeId() == GA_PRIMPOLY) {
-            num_polys += 1;
-            const GEO_PrimPoly *poly = static_cast<const GEO_PrimPoly*>(prim);
-            std::size_t num_verts = poly->getVertexCount();
-            poly_indices.push_back(num_verts);
-            for ( std::size_t i = 0; i < num_verts; ++i ) {
-                GA_Index idx = detail.pointIndex(detail.vertexPoint(poly->getVertexOffset(i)));
-                assert(GAisValid(idx));
-                poly_indices.push_back(static_cast<std::size_t>(idx));
-            }
-        }
+    auto ptcloud_ptr = make_points<M>(coords).into_raw();
+    transfer_point_attributes(detail, ptcloud_ptr, pt_grp);
+    return rust::box<M>::from_raw(ptcloud_ptr);
+}
+
+rust::box<hdkrs::PointCloud> hdkrs::build_pointcloud(const GU_Detail& detail) {
+    return build_points<PointCloud>(detail);
+}
+
+rust::box<hdkrs::PointCloudF32> hdkrs::build_pointcloud_f32(const GU_Detail& detail) {
+    return build_points<PointCloudF32>(detail);
+}
+
+/**
+ * Check whether point positions of the given detail are stored in double precision.
+ */
+bool hdkrs::has_f64_positions(const GU_Detail& detail) {
+    return is_p64(detail);
+}
+
+/**
+ * Store point positions of the given detail in double precision.
+ *
+ * Existing positions are converted, so this is a no-op for details already storing 64-bit P.
+ */
+void hdkrs::promote_p64(GU_Detail& detail) {
+    if (!is_p64(detail)) {
+        GA_Attribute *P = detail.getP();
+        P->getAIFTuple()->setStorage(P, GA_STORE_REAL64);
+    }
+}
+
+/**
+ * Extract all polygons, tetrahedra, polylines and loose points from the given detail.
+ *
+ * Each part keeps the source index of each point and primitive in reserved attributes, which
+ * allows `add_collection` to rebuild the detail with the original ordering. Source indices of
+ * unpacked geometry would not refer to the given detail, so packed primitives are rejected with
+ * an error as in `build_mesh`.
+ *
+ * The extraction is not lossless:
+ *  - packed primitives are not supported and must be unpacked beforehand,
+ *  - polygon soups come back as individual polygons,
+ *  - other primitive types are dropped, while their points are kept as loose points,
+ *  - detail attributes and groups are not transferred.
+ */
+rust::box<hdkrs::MeshCollection> hdkrs::build_all(const GU_Detail& detail) {
+    if (has_packed_prims(detail)) {
+        throw std::runtime_error("Cannot keep source indices of packed primitives");
+    }
+
+    ExtractFlags flags;
+    flags.compact_points = true;
+    flags.source_indices = true;
+
+    rust::box<MeshCollection> collection = new_mesh_collection(detail.getNumPoints());
+    std::vector<bool> used_points(detail.getNumPointOffsets(), false);
+    if (auto polymesh = extract_mesh<PolyMesh>(detail, flags, &used_points)) {
+        collection->set_polymesh(std::move(*polymesh));
+    }
+    if (auto tetmesh = extract_mesh<TetMesh>(detail, flags, &used_points)) {
+        collection->set_tetmesh(std::move(*tetmesh));
+    }
+    if (auto polylines = extract_mesh<PolylineMesh>(detail, flags, &used_points)) {
+        collection->set_polylines(std::move(*polylines));
     }
 
-    // Only creating a mesh if there are polys.
-    if (num_polys == 0) {
-        throw std::runtime_error("No polygons found");
+    // Points not referenced by any of the extracted primitives.
+    std::vector<bool> loose_points(detail.getNumPointOffsets(), false);
+    for ( GA_Offset pt_off : detail.getPointRange() ) {
+        loose_points[pt_off] = !used_points[pt_off];
+    }
+    GA_OffsetList loose_offsets = marked_points(detail, loose_points);
+    std::vector<double> coords = point_coords<double>(detail, loose_offsets);
+    std::vector<int64_t> point_indices;
+    for ( exint i = 0; i < loose_offsets.size(); ++i ) {
+        point_indices.push_back(detail.pointIndex(loose_offsets(i)));
     }
 
-    rust::Slice vertices_slice(static_cast<const double *>(poly_vertices.data()), poly_vertices.size());
-    rust::Slice indices_slice(static_cast<const size_t *>(poly_indices.data()), poly_indices.size());
-    rust::box<PolyMesh> polymesh = make_polymesh(vertices_slice, indices_slice);
+    if (!point_indices.empty()) {
+        auto ptcloud_ptr = make_points<PointCloud>(coords).into_raw();
+        transfer_point_attributes(detail, ptcloud_ptr, loose_points);
+        add_attrib(ptcloud_ptr, AttribLocation::VERTEX, POINT_INDEX_ATTRIB, 1, point_indices);
+        collection->set_points(rust::box<PointCloud>::from_raw(ptcloud_ptr));
+    }
 
-    auto polymesh_ptr = polymesh.into_raw();
-    transfer_attributes(detail, polymesh_ptr, num_polys);
-    return rust::box<PolyMesh>::from_raw(polymesh_ptr);
+    return collection;
 }
 
-rust::box<hdkrs::PointCloud> hdkrs::build_pointcloud(const GU_Detail& detail) {
-    std::vector<double> vertex_coords(3*detail.getNumPoints());
-    std::vector<bool> pt_grp(detail.getNumPointOffsets(), false);
+// Topology of a single part of a mesh collection being added to a detail.
+struct CollectionPart {
+    // Source point index of each point in the part.
+    rust::Vec<std::size_t> point_indices;
+    // Flat point indices of all faces or cells in the part, borrowed from the mesh.
+    rust::Slice<const std::size_t> indices;
+    // Offsets into `indices` of each face followed by the number of indices, or empty if every
+    // cell has `cell_size` points.
+    rust::Slice<const std::size_t> offsets;
+    std::size_t cell_size = 0;
+    // Added primitive for each face or cell.
+    std::vector<GA_Offset> prims;
+
+    std::size_t num_faces() const {
+        return offsets.empty() ? indices.size() / cell_size : offsets.size() - 1;
+    }
+    // Position of the first point index of the given face or cell in `indices`.
+    std::size_t start(std::size_t face) const {
+        return offsets.empty() ? face * cell_size : offsets[face];
+    }
+    // Number of points in the given face or cell.
+    std::size_t size(std::size_t face) const {
+        return offsets.empty() ? cell_size : offsets[face + 1] - offsets[face];
+    }
+};
 
-    // We are gonna be smarter here and use block access to point data.
-    GA_ROPageHandleV3 P_ph(detail.getP());
+// Set positions and point attributes of the given part at their source indices.
+template<typename M>
+void add_collection_points(GU_Detail &detail, GA_Offset startptoff, const M &mesh, const rust::Vec<std::size_t> &point_indices)
+{
+    auto point_coords = mesh.point_coords();
+    std::vector<GA_Offset> offsets;
+    offsets.reserve(point_indices.size());
+    for (std::size_t i = 0; i < point_indices.size(); ++i) {
+        GA_Offset ptoff = startptoff + GA_Offset(point_indices[i]);
+        offsets.push_back(ptoff);
+        detail.setPos3(ptoff, UT_Vector3D(&point_coords[3*i]));
+    }
+    retrieve_attributes_at(detail, offsets, mesh.attrib_iter(AttribLocation::VERTEX), GA_ATTRIB_POINT);
+}
 
-    if (P_ph.isValid()) {
-        GA_Offset start, end;
-        for (GA_Iterator it(detail.getPointRange()); it.blockAdvance(start, end); ) {
-            P_ph.setPage(start);
-            for (GA_Offset offset = start; offset < end; ++offset) {
-                pt_grp[offset] = true;
-                auto pos = P_ph.get(offset);
-                vertex_coords[3*detail.pointIndex(offset)] = static_cast<double>( pos[0]);
-                vertex_coords[3*detail.pointIndex(offset)+1] = static_cast<double>( pos[1]);
-                vertex_coords[3*detail.pointIndex(offset)+2] = static_cast<double>( pos[2]);
-            }
+// Prepare a polygon or polyline part.
+template<typename M>
+CollectionPart polygon_part(GU_Detail &detail, GA_Offset startptoff, const MeshCollection &collection, const M &mesh, MeshTag tag)
+{
+    CollectionPart part{collection.point_indices(tag), mesh.indices(), mesh.offsets()};
+    part.prims.resize(part.num_faces(), GA_INVALID_OFFSET);
+    add_collection_points(detail, startptoff, mesh, part.point_indices);
+    return part;
+}
+
+// Transfer primitive and vertex attributes of the given part onto the added primitives.
+template<typename M>
+void add_collection_prim_attributes(GU_Detail &detail, const M &mesh, const CollectionPart &part)
+{
+    std::vector<GA_Offset> vertices;
+    for ( GA_Offset prim_off : part.prims ) {
+        for ( GA_Offset vtx_off : detail.getPrimitiveVertexList(prim_off) ) {
+            vertices.push_back(vtx_off);
         }
     }
+    retrieve_attributes_at(detail, part.prims, mesh.attrib_iter(mesh_prim_attrib_location<M>()), GA_ATTRIB_PRIMITIVE);
+    retrieve_attributes_at(detail, vertices, mesh.attrib_iter(mesh_vertex_attrib_location<M>()), GA_ATTRIB_VERTEX);
+}
 
-    rust::Slice vertex_coords_slice(static_cast<const double *>(vertex_coords.data()), vertex_coords.size());
-    rust::box<PointCloud> ptcloud = make_pointcloud(vertex_coords_slice);
+/**
+ * Add all parts of the given collection to the detail.
+ *
+ * Points and primitives are created in the order given by their source indices, so a
+ * collection produced by `build_all` rebuilds the points and the supported primitives of the
+ * original detail in their original order. See `build_all` for what is not preserved.
+ */
+void hdkrs::add_collection(GU_Detail& detail, const hdkrs::MeshCollection& collection) {
+    std::size_t num_points = collection.num_points();
+    GA_Offset startptoff = detail.appendPointBlock(num_points);
 
-    auto ptcloud_ptr = ptcloud.into_raw();
-    transfer_point_attributes(detail, ptcloud_ptr, pt_grp);
-    return rust::box<PointCloud>::from_raw(ptcloud_ptr);
+    CollectionPart polys, tets, lines;
+    if (collection.has_polymesh()) {
+        polys = polygon_part(detail, startptoff, collection, collection.polymesh(), MeshTag::PolyMesh);
+    }
+    if (collection.has_polylines()) {
+        lines = polygon_part(detail, startptoff, collection, collection.polylines(), MeshTag::PolylineMesh);
+    }
+    if (collection.has_tetmesh()) {
+        const TetMesh &tetmesh = collection.tetmesh();
+        tets = CollectionPart{collection.point_indices(MeshTag::TetMesh), tetmesh.indices(), {}, 4};
+        tets.prims.resize(tets.num_faces(), GA_INVALID_OFFSET);
+        add_collection_points(detail, startptoff, tetmesh, tets.point_indices);
+    }
+    if (collection.has_points()) {
+        const PointCloud &ptcloud = collection.points();
+        add_collection_points(detail, startptoff, ptcloud, collection.point_indices(MeshTag::PointCloud));
+    }
+
+    // Build primitives in runs of consecutive primitives from the same part.
+    auto order = collection.prim_order();
+    std::vector<int> pt_numbers;
+    for (std::size_t run_start = 0; run_start < order.size(); ) {
+        MeshTag tag = order[run_start].part;
+        std::size_t run_end = run_start;
+        while (run_end < order.size() && order[run_end].part == tag) {
+            ++run_end;
+        }
+
+        CollectionPart &part = tag == MeshTag::TetMesh ? tets : (tag == MeshTag::PolylineMesh ? lines : polys);
+        GEO_PolyCounts polycounts;
+        pt_numbers.clear();
+        for (std::size_t i = run_start; i < run_end; ++i) {
+            std::size_t face = order[i].index;
+            std::size_t start = part.start(face);
+            std::size_t size = part.size(face);
+            polycounts.append(size);
+            for (std::size_t j = 0; j < size; ++j) {
+                pt_numbers.push_back(part.point_indices[part.indices[start + j]]);
+            }
+        }
+
+        GA_Offset startprimoff;
+        if (tag == MeshTag::TetMesh) {
+            startprimoff = GEO_PrimTetrahedron::buildBlock(
+                    &detail, startptoff, num_points, run_end - run_start, pt_numbers.data());
+        } else {
+            startprimoff = GEO_PrimPoly::buildBlock(
+                    &detail, startptoff, num_points,
+                    polycounts, pt_numbers.data(), tag == MeshTag::PolyMesh);
+        }
+        for (std::size_t i = run_start; i < run_end; ++i) {
+            part.prims[order[i].index] = startprimoff + GA_Offset(i - run_start);
+        }
+        run_start = run_end;
+    }
+
+    if (collection.has_polymesh()) {
+        add_collection_prim_attributes(detail, collection.polymesh(), polys);
+    }
+    if (collection.has_tetmesh()) {
+        add_collection_prim_attributes(detail, collection.tetmesh(), tets);
+    }
+    if (collection.has_polylines()) {
+        add_collection_prim_attributes(detail, collection.polylines(), lines);
+    }
 }
 
-void impl_shared_ptr(std::shared_ptr<GU_Detail> detail) {}
\ No newline at end of file
+void impl_shared_ptr(std::shared_ptr<GU_Detail> detail) {}
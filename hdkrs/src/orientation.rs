@@ -0,0 +1,445 @@
+//!
+//! This module converts the vertex order of polygons and tetrahedra between the conventions of
+//! geometry files and Houdini.
+//!
+//! Houdini winds polygons clockwise when viewed from the front, and orders tetrahedron vertices
+//! so that the signed volume `(p1 - p0) · ((p2 - p0) × (p3 - p0)) / 6` is negative. VTK, Gmsh and
+//! OBJ files wind polygons counter-clockwise and use positively oriented tetrahedra.
+//!
+//! Polygons are reversed keeping their first vertex in place and tetrahedra are inverted by
+//! swapping their last two vertices, as done when reversing primitives in Houdini. Vertex
+//! attributes are reordered along with the vertices.
+//!
+
+use meshx::attrib::Attrib;
+use meshx::mesh::topology::*;
+
+use crate::ffi::{AttribLocation, CellType, TetOrientation, VertexOrder, Winding};
+use crate::{make_polymesh, make_tetmesh, make_unstructured_mesh};
+use crate::{make_polymesh_f32, make_tetmesh_f32, make_unstructured_mesh_f32};
+use crate::{
+    Mesh, PolyMesh, PolyMeshF32, TetMesh, TetMeshF32, UnstructuredMesh, UnstructuredMeshF32,
+};
+
+/// Environment variable suffix selecting the polygon winding of files, either `cw` or `ccw`.
+pub const WINDING_ENV_SUFFIX: &str = "_WINDING";
+/// Environment variable suffix selecting the orientation of tetrahedra in files, either
+/// `positive` or `negative`.
+pub const TET_ORIENTATION_ENV_SUFFIX: &str = "_TET_ORIENTATION";
+
+impl VertexOrder {
+    /// The vertex order of polygons and tetrahedra in Houdini.
+    pub const HOUDINI: VertexOrder = VertexOrder {
+        winding: Winding::Clockwise,
+        tet_orientation: TetOrientation::Negative,
+    };
+    /// Counter-clockwise polygons and positively oriented tetrahedra, used by VTK, Gmsh and OBJ.
+    pub const STANDARD: VertexOrder = VertexOrder {
+        winding: Winding::CounterClockwise,
+        tet_orientation: TetOrientation::Positive,
+    };
+
+    /// Read the vertex order of files from environment variables with the given prefix, falling
+    /// back to the given default for unset or invalid variables.
+    ///
+    /// For instance, with the prefix `MSHIO`, setting `MSHIO_TET_ORIENTATION=negative` loads
+    /// tetrahedra as they are stored in the file.
+    pub fn from_env(prefix: &str, default: &VertexOrder) -> Self {
+        let var = |suffix: &str| {
+            std::env::var(format!("{}{}", prefix, suffix))
+                .map(|v| v.trim().to_ascii_lowercase())
+                .unwrap_or_default()
+        };
+        let mut order = *default;
+        match var(WINDING_ENV_SUFFIX).as_str() {
+            "cw" => order.winding = Winding::Clockwise,
+            "ccw" => order.winding = Winding::CounterClockwise,
+            _ => {}
+        }
+        match var(TET_ORIENTATION_ENV_SUFFIX).as_str() {
+            "negative" => order.tet_orientation = TetOrientation::Negative,
+            "positive" => order.tet_orientation = TetOrientation::Positive,
+            _ => {}
+        }
+        order
+    }
+
+    /// Check if polygons are reversed when converting between this order and Houdini.
+    pub fn reverses_polygons(&self) -> bool {
+        self.winding != VertexOrder::HOUDINI.winding
+    }
+
+    /// Check if tetrahedra are inverted when converting between this order and Houdini.
+    pub fn inverts_tets(&self) -> bool {
+        self.tet_orientation != VertexOrder::HOUDINI.tet_orientation
+    }
+}
+
+/// Read the vertex order of files from environment variables with the given prefix.
+pub fn vertex_order_from_env(prefix: &str, default: &VertexOrder) -> VertexOrder {
+    VertexOrder::from_env(prefix, default)
+}
+
+/// Reverse the given polygon vertices in place, keeping the first vertex.
+fn reverse_polygon<T>(poly: &mut [T]) {
+    if poly.len() > 2 {
+        poly[1..].reverse();
+    }
+}
+
+/// Reorder tuples of the given flat data so that tuple `i` is taken from tuple `order[i]`.
+pub(crate) fn reorder_tuples<T: Clone>(data: &[T], tuple_size: usize, order: &[usize]) -> Vec<T> {
+    order
+        .iter()
+        .flat_map(|&i| data[i * tuple_size..(i + 1) * tuple_size].iter().cloned())
+        .collect()
+}
+
+/// Copy attributes from the given iterator to `mesh` at `loc`, reordering their tuples.
+///
+/// Tuple `i` of each copied attribute is taken from tuple `order[i]` of the source attribute, so
+/// the order may also drop or repeat tuples.
+macro_rules! copy_reordered_attribs {
+    ($iter:expr, $mesh:ident, $loc:expr, $order:expr) => {{
+        use $crate::ffi::DataType;
+        use $crate::orientation::reorder_tuples;
+        let mut iter: Box<$crate::AttribIter> = $iter;
+        while iter.has_next() {
+            let attrib: Box<$crate::Attribute> = match iter.next() {
+                Ok(attrib) => attrib,
+                Err(_) => break,
+            };
+            let (name, n) = (attrib.name(), attrib.tuple_size());
+            match attrib.data_type() {
+                DataType::I8 => {
+                    let data = reorder_tuples(attrib.data_i8(), n, $order);
+                    $mesh.add_attrib_i8($loc, name, n, &data);
+                }
+                DataType::I32 => {
+                    let data = reorder_tuples(attrib.data_i32(), n, $order);
+                    $mesh.add_attrib_i32($loc, name, n, &data);
+                }
+                DataType::I64 => {
+                    let data = reorder_tuples(attrib.data_i64(), n, $order);
+                    $mesh.add_attrib_i64($loc, name, n, &data);
+                }
+                DataType::F32 => {
+                    let data = reorder_tuples(attrib.data_f32(), n, $order);
+                    $mesh.add_attrib_f32($loc, name, n, &data);
+                }
+                DataType::F64 => {
+                    let data = reorder_tuples(attrib.data_f64(), n, $order);
+                    $mesh.add_attrib_f64($loc, name, n, &data);
+                }
+                DataType::STR => {
+                    let strings = attrib.get_data_str();
+                    let strings: Vec<&str> = (0..strings.len()).map(|i| strings.at(i)).collect();
+                    let strings = reorder_tuples(&strings, n, $order);
+                    let indices: Vec<i64> = (0..strings.len() as i64).collect();
+                    $mesh.add_attrib_str($loc, name, n, &strings, &indices);
+                }
+                _ => {}
+            }
+        }
+    }};
+}
+pub(crate) use copy_reordered_attribs;
+
+/// Move point and primitive attributes of `src` to `dst`, which has the same points and
+/// primitives.
+fn move_attribs<M: Attrib, P>(src: &mut M, dst: &mut M)
+where
+    VertexIndex: meshx::attrib::AttribIndex<M>,
+    P: meshx::attrib::AttribIndex<M>,
+{
+    *dst.attrib_dict_mut::<VertexIndex>() = std::mem::take(src.attrib_dict_mut::<VertexIndex>());
+    *dst.attrib_dict_mut::<P>() = std::mem::take(src.attrib_dict_mut::<P>());
+}
+
+macro_rules! impl_reverse {
+    ($mesh_type:ident, $make_mesh:ident) => {
+        impl $mesh_type {
+            /// Reverse the winding of all polygons in this mesh.
+            pub fn reverse(&mut self) {
+                let mut indices = self.get_indices();
+                let mut order = Vec::with_capacity(indices.len());
+                let mut i = 0;
+                while i < indices.len() {
+                    let n = indices[i];
+                    let start = order.len();
+                    order.extend(start..start + n);
+                    reverse_polygon(&mut order[start..]);
+                    reverse_polygon(&mut indices[i + 1..i + 1 + n]);
+                    i += n + 1;
+                }
+                let mut mesh = *$make_mesh(self.point_coords(), &indices);
+                copy_reordered_attribs!(
+                    self.attrib_iter(AttribLocation::FACEVERTEX),
+                    mesh,
+                    AttribLocation::FACEVERTEX,
+                    &order
+                );
+                move_attribs::<_, FaceIndex>(&mut self.0, &mut mesh.0);
+                *self = mesh;
+            }
+        }
+    };
+}
+
+impl_reverse!(PolyMesh, make_polymesh);
+impl_reverse!(PolyMeshF32, make_polymesh_f32);
+
+macro_rules! impl_invert {
+    ($mesh_type:ident, $make_mesh:ident) => {
+        impl $mesh_type {
+            /// Invert all tetrahedra in this mesh.
+            pub fn invert(&mut self) {
+                let mut indices = self.get_indices();
+                let order: Vec<usize> = (0..indices.len())
+                    .map(|i| match i % 4 {
+                        2 => i + 1,
+                        3 => i - 1,
+                        _ => i,
+                    })
+                    .collect();
+                for tet in indices.chunks_exact_mut(4) {
+                    tet.swap(2, 3);
+                }
+                let mut mesh = *$make_mesh(self.point_coords(), &indices);
+                copy_reordered_attribs!(
+                    self.attrib_iter(AttribLocation::CELLVERTEX),
+                    mesh,
+                    AttribLocation::CELLVERTEX,
+                    &order
+                );
+                move_attribs::<_, CellIndex>(&mut self.0, &mut mesh.0);
+                *self = mesh;
+            }
+        }
+    };
+}
+
+impl_invert!(TetMesh, make_tetmesh);
+impl_invert!(TetMeshF32, make_tetmesh_f32);
+
+macro_rules! impl_reorder {
+    ($mesh_type:ident, $make_mesh:ident) => {
+        impl $mesh_type {
+            /// Convert polygon cells and tetrahedra in this mesh between the given order and
+            /// Houdini.
+            pub fn reorder(&mut self, vertex_order: &VertexOrder) {
+                let (reverse, invert) = (
+                    vertex_order.reverses_polygons(),
+                    vertex_order.inverts_tets(),
+                );
+                if !reverse && !invert {
+                    return;
+                }
+                let flat = self.get_indices();
+                let mut indices = Vec::with_capacity(flat.len());
+                let mut order = Vec::with_capacity(flat.len());
+                let mut types = Vec::new();
+                let mut offset = 0;
+                let chunks = self.get_counts().into_iter().zip(self.get_sizes());
+                for ((count, size), cell_type) in chunks.zip(self.get_cell_types()) {
+                    for _ in 0..count {
+                        let start = order.len();
+                        order.extend(offset..offset + size);
+                        match cell_type {
+                            CellType::Tetrahedron => {
+                                if invert {
+                                    order.swap(start + 2, start + 3);
+                                }
+                            }
+                            // All other cells are polygons, such as triangles, of any size.
+                            _ if reverse => reverse_polygon(&mut order[start..]),
+                            _ => {}
+                        }
+                        indices.push(size);
+                        indices.extend(order[start..].iter().map(|&i| flat[i]));
+                        types.push(cell_type);
+                        offset += size;
+                    }
+                }
+                let mut mesh = *$make_mesh(self.point_coords(), &indices, &types);
+                copy_reordered_attribs!(
+                    self.attrib_iter(AttribLocation::CELLVERTEX),
+                    mesh,
+                    AttribLocation::CELLVERTEX,
+                    &order
+                );
+                move_attribs::<_, CellIndex>(&mut self.0, &mut mesh.0);
+                *self = mesh;
+            }
+        }
+    };
+}
+
+impl_reorder!(UnstructuredMesh, make_unstructured_mesh);
+impl_reorder!(UnstructuredMeshF32, make_unstructured_mesh_f32);
+
+impl Mesh {
+    /// Convert the vertex order of polygons and tetrahedra in this mesh between the given order
+    /// and Houdini.
+    ///
+    /// The conversion is its own inverse, so the same order is used on import and export.
+    pub fn reorder(&mut self, vertex_order: &VertexOrder) {
+        match self {
+            Mesh::PolyMesh(m) if vertex_order.reverses_polygons() => m.reverse(),
+            Mesh::TetMesh(m) if vertex_order.inverts_tets() => m.invert(),
+            Mesh::UnstructuredMesh(m) => m.reorder(vertex_order),
+            Mesh::PolyMeshF32(m) if vertex_order.reverses_polygons() => m.reverse(),
+            Mesh::TetMeshF32(m) if vertex_order.inverts_tets() => m.invert(),
+            Mesh::UnstructuredMeshF32(m) => m.reorder(vertex_order),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use meshx::mesh::VertexPositions;
+
+    const TET_COORDS: [f64; 15] = [
+        0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0,
+    ];
+
+    fn signed_volumes(positions: &[[f64; 3]], indices: &[usize]) -> Vec<f64> {
+        indices
+            .chunks_exact(4)
+            .map(|tet| {
+                let [p0, p1, p2, p3] = [0, 1, 2, 3].map(|i| positions[tet[i]]);
+                let d = |p: [f64; 3]| [p[0] - p0[0], p[1] - p0[1], p[2] - p0[2]];
+                let (a, b, c) = (d(p1), d(p2), d(p3));
+                (a[0] * (b[1] * c[2] - b[2] * c[1]) - a[1] * (b[0] * c[2] - b[2] * c[0])
+                    + a[2] * (b[0] * c[1] - b[1] * c[0]))
+                    / 6.0
+            })
+            .collect()
+    }
+
+    #[test]
+    fn tet_volume_sign_round_trip() {
+        let tetmesh = *make_tetmesh(&TET_COORDS, &[0, 1, 2, 3, 1, 2, 3, 4]);
+        let volumes = signed_volumes(tetmesh.0.vertex_positions(), &tetmesh.get_indices());
+        assert!(volumes[0] > 0.0);
+
+        let mut mesh = Mesh::TetMesh(tetmesh.clone());
+        mesh.reorder(&VertexOrder::STANDARD);
+        let houdini = match &mesh {
+            Mesh::TetMesh(m) => signed_volumes(m.0.vertex_positions(), &m.get_indices()),
+            _ => panic!("expected a tetmesh"),
+        };
+        for (v, h) in volumes.iter().zip(houdini.iter()) {
+            assert_eq!(*h, -v);
+        }
+
+        mesh.reorder(&VertexOrder::STANDARD);
+        assert_eq!(mesh, Mesh::TetMesh(tetmesh));
+    }
+
+    #[test]
+    fn tet_invert_reorders_attribs() {
+        let mut mesh = *make_tetmesh(&TET_COORDS, &[0, 1, 2, 3, 1, 2, 3, 4]);
+        let corners: Vec<i32> = (0..8).collect();
+        mesh.add_attrib_i32(AttribLocation::CELLVERTEX, "corner", 1, &corners);
+        mesh.add_attrib_f64(AttribLocation::CELL, "id", 1, &[10.0, 20.0]);
+        mesh.add_attrib_f32(AttribLocation::VERTEX, "w", 1, &[0.0, 1.0, 2.0, 3.0, 4.0]);
+
+        mesh.invert();
+        assert_eq!(mesh.get_indices(), vec![0, 1, 3, 2, 1, 2, 4, 3]);
+        assert_eq!(
+            mesh.0
+                .attrib_as_slice::<i32, CellVertexIndex>("corner")
+                .unwrap(),
+            &[0, 1, 3, 2, 4, 5, 7, 6]
+        );
+        assert_eq!(
+            mesh.0.attrib_as_slice::<f64, CellIndex>("id").unwrap(),
+            &[10.0, 20.0]
+        );
+        assert_eq!(
+            mesh.0.attrib_as_slice::<f32, VertexIndex>("w").unwrap(),
+            &[0.0, 1.0, 2.0, 3.0, 4.0]
+        );
+    }
+
+    #[test]
+    fn polymesh_reverse_reorders_attribs() {
+        let coords = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0];
+        let mut mesh = *make_polymesh(&coords, &[4, 0, 1, 2, 3, 3, 0, 2, 3]);
+        let corners: Vec<i64> = (0..7).collect();
+        mesh.add_attrib_i64(AttribLocation::FACEVERTEX, "corner", 1, &corners);
+        let names = ["a", "b", "c", "d", "e", "f", "g"];
+        let name_indices: Vec<i64> = (0..7).collect();
+        mesh.add_attrib_str(AttribLocation::FACEVERTEX, "name", 1, &names, &name_indices);
+        mesh.add_attrib_i32(AttribLocation::FACE, "id", 1, &[1, 2]);
+
+        mesh.reverse();
+        assert_eq!(mesh.get_indices(), vec![4, 0, 3, 2, 1, 3, 0, 3, 2]);
+        assert_eq!(
+            mesh.0
+                .attrib_as_slice::<i64, FaceVertexIndex>("corner")
+                .unwrap(),
+            &[0, 3, 2, 1, 4, 6, 5]
+        );
+        let reversed: Vec<String> = mesh
+            .0
+            .attrib::<FaceVertexIndex>("name")
+            .unwrap()
+            .data
+            .indirect_data()
+            .unwrap()
+            .as_rc_slice::<String>()
+            .unwrap()
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(reversed, ["a", "d", "c", "b", "e", "g", "f"]);
+        assert_eq!(
+            mesh.0.attrib_as_slice::<i32, FaceIndex>("id").unwrap(),
+            &[1, 2]
+        );
+    }
+
+    #[test]
+    fn unstructured_reorder_reorders_attribs() {
+        let indices = [3, 0, 1, 2, 4, 0, 1, 2, 3, 4, 1, 2, 3, 4];
+        let types = [
+            CellType::Triangle,
+            CellType::Tetrahedron,
+            CellType::Tetrahedron,
+        ];
+        let mut mesh = *make_unstructured_mesh(&TET_COORDS, &indices, &types);
+        let corners: Vec<i32> = (0..11).collect();
+        mesh.add_attrib_i32(AttribLocation::CELLVERTEX, "corner", 1, &corners);
+        mesh.add_attrib_i32(AttribLocation::CELL, "id", 1, &[1, 2, 3]);
+
+        let mut reversed = mesh.clone();
+        reversed.reorder(&VertexOrder {
+            winding: Winding::CounterClockwise,
+            tet_orientation: TetOrientation::Negative,
+        });
+        assert_eq!(
+            reversed
+                .0
+                .attrib_as_slice::<i32, CellVertexIndex>("corner")
+                .unwrap(),
+            &[0, 2, 1, 3, 4, 5, 6, 7, 8, 9, 10]
+        );
+
+        mesh.reorder(&VertexOrder::STANDARD);
+        assert_eq!(mesh.get_indices(), vec![0, 2, 1, 0, 1, 3, 2, 1, 2, 4, 3]);
+        assert_eq!(
+            mesh.0
+                .attrib_as_slice::<i32, CellVertexIndex>("corner")
+                .unwrap(),
+            &[0, 2, 1, 3, 4, 6, 5, 7, 8, 10, 9]
+        );
+        assert_eq!(
+            mesh.0.attrib_as_slice::<i32, CellIndex>("id").unwrap(),
+            &[1, 2, 3]
+        );
+    }
+}
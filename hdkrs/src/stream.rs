@@ -0,0 +1,142 @@
+//!
+//! This module adapts Houdini input streams to `std::io::Read`, which allows geometry
+//! translators to parse files incrementally instead of loading them into memory first.
+//!
+//! Compressed geometry files are handled transparently: gzip and zstd streams are detected on
+//! import and chosen by file suffix on export.
+//!
+
+use std::borrow::Cow;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::pin::Pin;
+
+use crate::ffi::IStreamReader;
+
+/// A `Read` implementation pulling data from a Houdini input stream.
+///
+/// Reading fails once the user interrupts the operation.
+pub struct StreamReader<'a>(Pin<&'a mut IStreamReader>);
+
+impl<'a> StreamReader<'a> {
+    pub fn new(reader: Pin<&'a mut IStreamReader>) -> Self {
+        StreamReader(reader)
+    }
+}
+
+impl Read for StreamReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0
+            .as_mut()
+            .read(buf)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e.what().to_string()))
+    }
+}
+
+/// Compression format of a geometry file.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Zstd,
+}
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+impl Compression {
+    /// Determine the compression format from the suffix of the given file name.
+    pub fn from_path(path: &str) -> Compression {
+        if path.ends_with(".gz") {
+            Compression::Gzip
+        } else if path.ends_with(".zst") {
+            Compression::Zstd
+        } else {
+            Compression::None
+        }
+    }
+
+    /// Detect the compression format from the leading bytes of a file.
+    pub fn from_magic(header: &[u8]) -> Compression {
+        if header.starts_with(&GZIP_MAGIC) {
+            Compression::Gzip
+        } else if header.starts_with(&ZSTD_MAGIC) {
+            Compression::Zstd
+        } else {
+            Compression::None
+        }
+    }
+}
+
+/// Wrap the given reader to decompress its contents if they are gzip or zstd compressed.
+///
+/// Uncompressed data is passed through unchanged.
+pub fn decompress<'a>(reader: impl Read + 'a) -> io::Result<Box<dyn BufRead + 'a>> {
+    let mut reader = BufReader::new(reader);
+    Ok(match Compression::from_magic(reader.fill_buf()?) {
+        Compression::Gzip => Box::new(BufReader::new(flate2::read::MultiGzDecoder::new(reader))),
+        Compression::Zstd => Box::new(BufReader::new(zstd::stream::read::Decoder::with_buffer(
+            reader,
+        )?)),
+        Compression::None => Box::new(reader),
+    })
+}
+
+/// Decompress the given file contents if they are gzip or zstd compressed.
+pub fn decompress_bytes(data: &[u8]) -> io::Result<Cow<'_, [u8]>> {
+    if Compression::from_magic(data) == Compression::None {
+        return Ok(Cow::Borrowed(data));
+    }
+    let mut out = Vec::new();
+    decompress(data)?.read_to_end(&mut out)?;
+    Ok(Cow::Owned(out))
+}
+
+/// Compress the given file contents with the given format.
+pub fn compress(data: &[u8], compression: Compression) -> io::Result<Vec<u8>> {
+    match compression {
+        Compression::Gzip => {
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(data)?;
+            encoder.finish()
+        }
+        Compression::Zstd => zstd::stream::encode_all(data, 0),
+        Compression::None => Ok(data.to_vec()),
+    }
+}
+
+/// Compress the given file contents according to the suffix of the given file name.
+pub fn compress_for_path(data: &[u8], path: &str) -> io::Result<Vec<u8>> {
+    compress(data, Compression::from_path(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compression_round_trip() {
+        let data = b"# vtk DataFile Version 2.0\nhdkrs\nASCII\n".repeat(100);
+        for &compression in &[Compression::None, Compression::Gzip, Compression::Zstd] {
+            let compressed = compress(&data, compression).unwrap();
+            assert_eq!(Compression::from_magic(&compressed), compression);
+            assert_eq!(decompress_bytes(&compressed).unwrap(), &data[..]);
+
+            let mut streamed = Vec::new();
+            decompress(compressed.as_slice())
+                .unwrap()
+                .read_to_end(&mut streamed)
+                .unwrap();
+            assert_eq!(streamed, data);
+        }
+    }
+
+    #[test]
+    fn compression_from_path() {
+        assert_eq!(Compression::from_path("mesh.vtu.gz"), Compression::Gzip);
+        assert_eq!(Compression::from_path("mesh.msh.zst"), Compression::Zstd);
+        assert_eq!(Compression::from_path("mesh.obj"), Compression::None);
+        let data = compress_for_path(b"v 0 0 0\n", "mesh.obj.gz").unwrap();
+        assert_eq!(decompress_bytes(&data).unwrap(), &b"v 0 0 0\n"[..]);
+    }
+}
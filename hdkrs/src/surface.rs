@@ -0,0 +1,349 @@
+//!
+//! This module converts between volume meshes and the surfaces bounding them.
+//!
+//! The boundary of a tetrahedral mesh consists of the tetrahedron faces not shared by any other
+//! tetrahedron. Faces are wound to face outwards whenever tetrahedra and polygons follow the same
+//! convention, which holds for both Houdini and standard conventions (see the `orientation`
+//! module). Conversely, polygons are embedded into unstructured meshes as triangle cells.
+//!
+
+use std::collections::HashMap;
+
+use meshx::attrib::Attrib;
+use meshx::mesh::topology::*;
+
+use crate::ffi::{AttribLocation, CellType};
+use crate::orientation::copy_reordered_attribs;
+use crate::{make_polymesh, make_unstructured_mesh, AttribIter, Error};
+use crate::{Mesh, PolyMesh, TetMesh, UnstructuredMesh};
+use crate::{PRIM_INDEX_ATTRIB, SOURCE_POLYGON_ATTRIB, VERTEX_INDEX_ATTRIB};
+
+/// Faces of a tetrahedron given by local vertex indices.
+///
+/// Faces are wound counter-clockwise when viewed from outside a positively oriented tetrahedron,
+/// and clockwise when viewed from outside a negatively oriented one.
+const TET_FACES: [[usize; 3]; 4] = [[1, 2, 3], [0, 3, 2], [0, 1, 3], [0, 2, 1]];
+
+/// A triangle of a volume mesh, given by the cell containing it and indices of its vertices
+/// into the flat cell vertex array.
+struct CellFace {
+    cell: usize,
+    vertices: [usize; 3],
+}
+
+/// Sorted point indices identifying a triangle regardless of its winding.
+fn face_key(points: [usize; 3]) -> [usize; 3] {
+    let mut key = points;
+    key.sort_unstable();
+    key
+}
+
+/// Build a polygon mesh from the given triangles of a volume mesh with the given point
+/// coordinates and attributes.
+///
+/// Only points referenced by the triangles are kept. Cell and cell vertex attributes are
+/// transferred onto faces and face vertices if `keep_cell_attribs` is set.
+fn faces_to_polymesh<'a>(
+    coords: &[f64],
+    attrib_iter: impl Fn(AttribLocation) -> Box<AttribIter<'a>>,
+    cell_indices: &[usize],
+    faces: &[CellFace],
+    keep_cell_attribs: bool,
+) -> Box<PolyMesh> {
+    let num_points = coords.len() / 3;
+    let mut used = vec![false; num_points];
+    for face in faces.iter() {
+        for &v in face.vertices.iter() {
+            used[cell_indices[v]] = true;
+        }
+    }
+    let point_order: Vec<usize> = (0..num_points).filter(|&i| used[i]).collect();
+    let mut new_index = vec![0; num_points];
+    for (i, &p) in point_order.iter().enumerate() {
+        new_index[p] = i;
+    }
+    let new_coords: Vec<f64> = point_order
+        .iter()
+        .flat_map(|&p| coords[3 * p..3 * p + 3].iter().copied())
+        .collect();
+    let mut indices = Vec::with_capacity(4 * faces.len());
+    for face in faces.iter() {
+        indices.push(3);
+        indices.extend(face.vertices.iter().map(|&v| new_index[cell_indices[v]]));
+    }
+
+    let mut surface = *make_polymesh(&new_coords, &indices);
+    copy_reordered_attribs!(
+        attrib_iter(AttribLocation::VERTEX),
+        surface,
+        AttribLocation::VERTEX,
+        &point_order
+    );
+    if keep_cell_attribs {
+        let face_order: Vec<usize> = faces.iter().map(|face| face.cell).collect();
+        let vertex_order: Vec<usize> = faces.iter().flat_map(|face| face.vertices).collect();
+        copy_reordered_attribs!(
+            attrib_iter(AttribLocation::CELL),
+            surface,
+            AttribLocation::FACE,
+            &face_order
+        );
+        copy_reordered_attribs!(
+            attrib_iter(AttribLocation::CELLVERTEX),
+            surface,
+            AttribLocation::FACEVERTEX,
+            &vertex_order
+        );
+        // Source primitive and vertex indices no longer refer to the elements of the surface.
+        surface.0.remove_attrib::<FaceIndex>(PRIM_INDEX_ATTRIB).ok();
+        surface
+            .0
+            .remove_attrib::<FaceVertexIndex>(VERTEX_INDEX_ATTRIB)
+            .ok();
+    }
+    Box::new(surface)
+}
+
+/// Collect faces of the given tetrahedra that aren't shared with any other tetrahedron.
+///
+/// Each tetrahedron is given by the cell containing it and the position of its first vertex in
+/// the flat cell vertex array `cell_indices`. Faces are returned in the order of their cells.
+fn boundary_faces(
+    cell_indices: &[usize],
+    tets: impl Iterator<Item = (usize, usize)>,
+) -> (Vec<CellFace>, HashMap<[usize; 3], usize>) {
+    let mut faces = Vec::new();
+    let mut counts: HashMap<[usize; 3], usize> = HashMap::new();
+    for (cell, start) in tets {
+        for local in TET_FACES.iter() {
+            let vertices = [start + local[0], start + local[1], start + local[2]];
+            let points = vertices.map(|v| cell_indices[v]);
+            *counts.entry(face_key(points)).or_insert(0) += 1;
+            faces.push(CellFace { cell, vertices });
+        }
+    }
+    let boundary = faces
+        .into_iter()
+        .filter(|face| {
+            let points = face.vertices.map(|v| cell_indices[v]);
+            counts[&face_key(points)] == 1
+        })
+        .collect();
+    (boundary, counts)
+}
+
+impl TetMesh {
+    /// Boundary triangles of this tetrahedral mesh as a polygon mesh.
+    ///
+    /// Point attributes are transferred onto the boundary points. Cell and cell vertex
+    /// attributes are transferred onto faces and face vertices if `keep_cell_attribs` is set.
+    pub fn boundary_polymesh(&self, keep_cell_attribs: bool) -> Box<PolyMesh> {
+        let cell_indices = self.get_indices();
+        let tets = (0..cell_indices.len() / 4).map(|cell| (cell, 4 * cell));
+        let (faces, _) = boundary_faces(&cell_indices, tets);
+        faces_to_polymesh(
+            self.point_coords(),
+            |loc| self.attrib_iter(loc),
+            &cell_indices,
+            &faces,
+            keep_cell_attribs,
+        )
+    }
+}
+
+impl UnstructuredMesh {
+    /// Boundary triangles of the tetrahedra in this mesh along with its triangle cells as a
+    /// polygon mesh.
+    ///
+    /// Triangle cells coinciding with a tetrahedron face are skipped. Attributes are transferred
+    /// as in `TetMesh::boundary_polymesh`.
+    ///
+    /// Returns `Error::MeshMismatch` if the mesh has cells other than triangles and tetrahedra,
+    /// whose boundary can't be determined.
+    pub fn boundary_polymesh(&self, keep_cell_attribs: bool) -> Result<Box<PolyMesh>, Error> {
+        let cell_indices = self.get_indices();
+        let mut tets = Vec::new();
+        let mut triangles = Vec::new();
+        let (mut cell, mut offset) = (0, 0);
+        let chunks = self.get_counts().into_iter().zip(self.get_sizes());
+        for ((count, size), cell_type) in chunks.zip(self.get_cell_types()) {
+            for _ in 0..count {
+                match cell_type {
+                    CellType::Tetrahedron => tets.push((cell, offset)),
+                    CellType::Triangle => triangles.push((cell, offset)),
+                    _ => return Err(Error::MeshMismatch),
+                }
+                cell += 1;
+                offset += size;
+            }
+        }
+        let (mut faces, counts) = boundary_faces(&cell_indices, tets.into_iter());
+        for (cell, start) in triangles {
+            let vertices = [start, start + 1, start + 2];
+            let points = vertices.map(|v| cell_indices[v]);
+            if !counts.contains_key(&face_key(points)) {
+                faces.push(CellFace { cell, vertices });
+            }
+        }
+        faces.sort_by_key(|face| face.cell);
+        Ok(faces_to_polymesh(
+            self.point_coords(),
+            |loc| self.attrib_iter(loc),
+            &cell_indices,
+            &faces,
+            keep_cell_attribs,
+        ))
+    }
+}
+
+impl PolyMesh {
+    /// Embed this surface into an unstructured mesh of triangle cells.
+    ///
+    /// Polygons with more than three vertices are split into triangle fans, and degenerate
+    /// polygons are skipped. Point attributes are kept, while face and face vertex attributes
+    /// are duplicated onto cells and cell vertices of each triangle. If `source_polygons` is set,
+    /// the index of the polygon each triangle came from is stored in the `SOURCE_POLYGON_ATTRIB`
+    /// cell attribute, unless this mesh already has a face attribute with that name.
+    pub fn to_triangle_cells(&self, source_polygons: bool) -> Box<UnstructuredMesh> {
+        let flat = self.get_indices();
+        let mut indices = Vec::new();
+        let mut face_order = Vec::new();
+        let mut vertex_order = Vec::new();
+        let (mut i, mut face, mut offset) = (0, 0, 0);
+        while i < flat.len() {
+            let n = flat[i];
+            for k in 1..n.saturating_sub(1) {
+                indices.push(3);
+                for &v in [0, k, k + 1].iter() {
+                    indices.push(flat[i + 1 + v]);
+                    vertex_order.push(offset + v);
+                }
+                face_order.push(face);
+            }
+            i += n + 1;
+            offset += n;
+            face += 1;
+        }
+        let types = vec![CellType::Triangle; face_order.len()];
+
+        let mut mesh = *make_unstructured_mesh(self.point_coords(), &indices, &types);
+        let point_order: Vec<usize> = (0..self.point_coords().len() / 3).collect();
+        copy_reordered_attribs!(
+            self.attrib_iter(AttribLocation::VERTEX),
+            mesh,
+            AttribLocation::VERTEX,
+            &point_order
+        );
+        copy_reordered_attribs!(
+            self.attrib_iter(AttribLocation::FACE),
+            mesh,
+            AttribLocation::CELL,
+            &face_order
+        );
+        copy_reordered_attribs!(
+            self.attrib_iter(AttribLocation::FACEVERTEX),
+            mesh,
+            AttribLocation::CELLVERTEX,
+            &vertex_order
+        );
+        if source_polygons && self.0.attrib::<FaceIndex>(SOURCE_POLYGON_ATTRIB).is_err() {
+            let sources: Vec<i64> = face_order.iter().map(|&f| f as i64).collect();
+            mesh.add_attrib_i64(AttribLocation::CELL, SOURCE_POLYGON_ATTRIB, 1, &sources);
+        }
+        Box::new(mesh)
+    }
+}
+
+impl Mesh {
+    /// Boundary triangles of a tetrahedral or unstructured mesh as a polygon mesh.
+    ///
+    /// Point attributes are always transferred, while cell and cell vertex attributes are
+    /// transferred onto faces and face vertices if `keep_cell_attribs` is set. Other meshes, and
+    /// unstructured meshes with cells other than triangles and tetrahedra, return
+    /// `Error::MeshMismatch`.
+    pub fn boundary_polymesh(&self, keep_cell_attribs: bool) -> Result<Box<PolyMesh>, Error> {
+        match self {
+            Mesh::TetMesh(m) => Ok(m.boundary_polymesh(keep_cell_attribs)),
+            Mesh::UnstructuredMesh(m) => m.boundary_polymesh(keep_cell_attribs),
+            _ => Err(Error::MeshMismatch),
+        }
+    }
+
+    /// Embed a polygon mesh into an unstructured mesh of triangle cells as in
+    /// `PolyMesh::to_triangle_cells`.
+    ///
+    /// Other meshes return `Error::MeshMismatch`.
+    pub fn to_triangle_cells(&self, source_polygons: bool) -> Result<Box<UnstructuredMesh>, Error> {
+        match self {
+            Mesh::PolyMesh(m) => Ok(m.to_triangle_cells(source_polygons)),
+            _ => Err(Error::MeshMismatch),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::make_tetmesh;
+
+    /// Two positively oriented tetrahedra sharing the face `1 2 3`.
+    fn two_tets() -> (Vec<f64>, Vec<usize>) {
+        let coords = vec![
+            0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0,
+        ];
+        (coords, vec![0, 1, 2, 3, 4, 3, 2, 1])
+    }
+
+    #[test]
+    fn boundary_faces_of_shared_tets() {
+        let (coords, indices) = two_tets();
+        let tets = (0..2).map(|cell| (cell, 4 * cell));
+        let (faces, counts) = boundary_faces(&indices, tets);
+
+        // The shared face is culled from both tetrahedra.
+        assert_eq!(faces.len(), 6);
+        assert_eq!(counts[&face_key([3, 2, 1])], 2);
+        assert!(faces
+            .iter()
+            .all(|face| face_key(face.vertices.map(|v| indices[v])) != [1, 2, 3]));
+        assert_eq!(faces.iter().filter(|face| face.cell == 0).count(), 3);
+
+        // Faces are wound counter-clockwise when viewed from outside, so their normals point
+        // away from the centroid of their tetrahedron.
+        let p = |i: usize| [coords[3 * i], coords[3 * i + 1], coords[3 * i + 2]];
+        let sub = |a: [f64; 3], b: [f64; 3]| [a[0] - b[0], a[1] - b[1], a[2] - b[2]];
+        for face in faces.iter() {
+            let [a, b, c] = face.vertices.map(|v| p(indices[v]));
+            let (u, v) = (sub(b, a), sub(c, a));
+            let normal = [
+                u[1] * v[2] - u[2] * v[1],
+                u[2] * v[0] - u[0] * v[2],
+                u[0] * v[1] - u[1] * v[0],
+            ];
+            let tet = &indices[4 * face.cell..4 * face.cell + 4];
+            let centroid = tet.iter().fold([0.0; 3], |acc, &i| {
+                let q = p(i);
+                [
+                    acc[0] + q[0] / 4.0,
+                    acc[1] + q[1] / 4.0,
+                    acc[2] + q[2] / 4.0,
+                ]
+            });
+            let out = sub(a, centroid);
+            assert!(normal[0] * out[0] + normal[1] * out[1] + normal[2] * out[2] > 0.0);
+        }
+    }
+
+    #[test]
+    fn boundary_polymesh_keeps_cell_attribs() {
+        let (coords, indices) = two_tets();
+        let mut tetmesh = *make_tetmesh(&coords, &indices);
+        tetmesh.add_attrib_i32(AttribLocation::CELL, "id", 1, &[1, 2]);
+        let surface = Mesh::TetMesh(tetmesh).boundary_polymesh(true).unwrap();
+        assert_eq!(surface.0.num_faces(), 6);
+        assert_eq!(
+            surface.0.attrib_as_slice::<i32, FaceIndex>("id").unwrap(),
+            &[1, 1, 1, 2, 2, 2]
+        );
+    }
+}
@@ -0,0 +1,445 @@
+//!
+//! This module converts geometry between the coordinate system of a file and the right-handed,
+//! Y-up coordinate system used by Houdini.
+//!
+//! Positions and velocities (`v`) are rotated, mirrored and scaled to scene units. Normals (`N`)
+//! are only rotated and mirrored, and orientations (`orient`) are conjugated by the change of
+//! axes, so that they rotate the transformed geometry in the same way.
+//!
+//! Changes of axes only swap and negate axes, so regular grids stay aligned with the axes. Their
+//! samples are reindexed along the new axes instead of being resampled.
+//!
+
+use meshx::attrib::{self, Attrib};
+use meshx::mesh::topology::*;
+use meshx::mesh::VertexPositions;
+
+use crate::ffi::{CoordTransform, TransformDirection, UpAxis, VertexOrder};
+use crate::orientation::reorder_tuples;
+use crate::{GridMesh, Mesh, PolyMesh, PolyMeshF32, PolylineMesh, PolylineMeshF32};
+
+/// Names of attributes transformed like positions, including the unit scale.
+pub const VECTOR_ATTRIBS: &[&str] = &["v"];
+/// Names of attributes transformed like directions, ignoring the unit scale.
+pub const NORMAL_ATTRIBS: &[&str] = &["N"];
+/// Names of quaternion attributes stored as `(x, y, z, w)`.
+pub const QUATERNION_ATTRIBS: &[&str] = &["orient"];
+
+/// Environment variable suffix selecting the up axis of files, either `y` or `z`.
+pub const UP_AXIS_ENV_SUFFIX: &str = "_UP_AXIS";
+/// Environment variable suffix selecting left-handed files when set to anything other than `0`.
+pub const FLIP_HANDEDNESS_ENV_SUFFIX: &str = "_FLIP_HANDEDNESS";
+/// Environment variable suffix giving the size of one file unit in scene units.
+pub const UNIT_SCALE_ENV_SUFFIX: &str = "_UNIT_SCALE";
+
+type Matrix3 = [[f64; 3]; 3];
+
+impl Default for CoordTransform {
+    fn default() -> Self {
+        CoordTransform {
+            up_axis: UpAxis::Y,
+            flip_handedness: false,
+            scale: 1.0,
+        }
+    }
+}
+
+impl CoordTransform {
+    /// Read the coordinate system of files from environment variables with the given prefix.
+    ///
+    /// For instance, with the prefix `MSHIO`, setting `MSHIO_UP_AXIS=z` and
+    /// `MSHIO_UNIT_SCALE=0.001` converts Z-up files in millimetres to Y-up scenes in metres.
+    /// Unset or invalid variables keep the default, which leaves coordinates untouched.
+    pub fn from_env(prefix: &str) -> Self {
+        let var = |suffix: &str| std::env::var(format!("{}{}", prefix, suffix)).ok();
+        let mut transform = CoordTransform::default();
+        if let Some(axis) = var(UP_AXIS_ENV_SUFFIX) {
+            if axis.trim().eq_ignore_ascii_case("z") {
+                transform.up_axis = UpAxis::Z;
+            }
+        }
+        if let Some(flip) = var(FLIP_HANDEDNESS_ENV_SUFFIX) {
+            transform.flip_handedness = !flip.is_empty() && flip != "0";
+        }
+        if let Some(scale) = var(UNIT_SCALE_ENV_SUFFIX).and_then(|s| s.trim().parse::<f64>().ok()) {
+            if scale.is_finite() && scale > 0.0 {
+                transform.scale = scale;
+            }
+        }
+        transform
+    }
+
+    /// Check if this transform leaves coordinates untouched.
+    pub fn is_identity(&self) -> bool {
+        self.up_axis != UpAxis::Z && !self.flip_handedness && self.scale == 1.0
+    }
+
+    /// Rotation or reflection taking file axes to Houdini axes, ignoring the unit scale.
+    fn import_axes(&self) -> Matrix3 {
+        let mut m = if self.up_axis == UpAxis::Z {
+            // Rotate Z-up to Y-up about the X axis.
+            [[1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, -1.0, 0.0]]
+        } else {
+            [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]]
+        };
+        if self.flip_handedness {
+            // Mirror the horizontal file X axis before changing the up axis, keeping the up
+            // axis pointing up.
+            for row in m.iter_mut() {
+                row[0] = -row[0];
+            }
+        }
+        m
+    }
+
+    /// Rotation or reflection of axes in the given direction, ignoring the unit scale.
+    ///
+    /// Each row and column has a single nonzero entry of `1` or `-1`.
+    pub fn axes_matrix(&self, direction: TransformDirection) -> Matrix3 {
+        let m = self.import_axes();
+        if direction == TransformDirection::Export {
+            // The inverse of an orthogonal matrix is its transpose.
+            let mut t = m;
+            for (i, row) in t.iter_mut().enumerate() {
+                for (j, x) in row.iter_mut().enumerate() {
+                    *x = m[j][i];
+                }
+            }
+            t
+        } else {
+            m
+        }
+    }
+
+    /// Rotation or reflection of axes in the given direction as a row-major array.
+    pub fn axes(&self, direction: TransformDirection) -> Vec<f64> {
+        self.axes_matrix(direction)
+            .iter()
+            .flatten()
+            .copied()
+            .collect()
+    }
+
+    /// Scale applied to positions and vectors in the given direction.
+    pub fn unit_scale(&self, direction: TransformDirection) -> f64 {
+        if direction == TransformDirection::Export {
+            1.0 / self.scale
+        } else {
+            self.scale
+        }
+    }
+}
+
+/// Read the coordinate system of files from environment variables with the given prefix.
+pub fn coord_transform_from_env(prefix: &str) -> CoordTransform {
+    CoordTransform::from_env(prefix)
+}
+
+fn mul(m: &Matrix3, v: [f64; 3]) -> [f64; 3] {
+    let mut out = [0.0; 3];
+    for (o, row) in out.iter_mut().zip(m.iter()) {
+        *o = row[0] * v[0] + row[1] * v[1] + row[2] * v[2];
+    }
+    out
+}
+
+fn det(m: &Matrix3) -> f64 {
+    m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+        - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+        + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+}
+
+/// Transform vectors, normals and quaternions stored at one location of the given mesh.
+fn transform_attribs<M, I>(mesh: &mut M, axes: &Matrix3, scale: f64)
+where
+    M: Attrib,
+    I: attrib::AttribIndex<M>,
+{
+    let sign = det(axes);
+    let transform3 = |v: [f64; 3], s: f64| {
+        let v = mul(axes, v);
+        [v[0] * s, v[1] * s, v[2] * s]
+    };
+    let names = VECTOR_ATTRIBS
+        .iter()
+        .map(|&name| (name, scale))
+        .chain(NORMAL_ATTRIBS.iter().map(|&name| (name, 1.0)));
+    for (name, s) in names {
+        if let Ok(data) = mesh.attrib_as_mut_slice::<[f32; 3], I>(name) {
+            for v in data.iter_mut() {
+                let t = transform3([v[0].into(), v[1].into(), v[2].into()], s);
+                *v = [t[0] as f32, t[1] as f32, t[2] as f32];
+            }
+        } else if let Ok(data) = mesh.attrib_as_mut_slice::<[f64; 3], I>(name) {
+            for v in data.iter_mut() {
+                *v = transform3(*v, s);
+            }
+        }
+    }
+    // The rotation axis of a quaternion is a pseudovector, which is negated by reflections.
+    for &name in QUATERNION_ATTRIBS.iter() {
+        if let Ok(data) = mesh.attrib_as_mut_slice::<[f32; 4], I>(name) {
+            for q in data.iter_mut() {
+                let t = transform3([q[0].into(), q[1].into(), q[2].into()], sign);
+                *q = [t[0] as f32, t[1] as f32, t[2] as f32, q[3]];
+            }
+        } else if let Ok(data) = mesh.attrib_as_mut_slice::<[f64; 4], I>(name) {
+            for q in data.iter_mut() {
+                let t = transform3([q[0], q[1], q[2]], sign);
+                *q = [t[0], t[1], t[2], q[3]];
+            }
+        }
+    }
+}
+
+/// Scalar type of point positions, which are transformed in double precision.
+trait Coord: Copy {
+    fn to_f64(self) -> f64;
+    fn from_f64(x: f64) -> Self;
+}
+
+impl Coord for f32 {
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+    fn from_f64(x: f64) -> Self {
+        x as f32
+    }
+}
+
+impl Coord for f64 {
+    fn to_f64(self) -> f64 {
+        self
+    }
+    fn from_f64(x: f64) -> Self {
+        x
+    }
+}
+
+/// Transform positions and point attributes of the given mesh.
+fn transform_points<M, T>(mesh: &mut M, axes: &Matrix3, scale: f64)
+where
+    M: Attrib + VertexPositions<Element = [T; 3]>,
+    T: Coord,
+    VertexIndex: attrib::AttribIndex<M>,
+{
+    for p in mesh.vertex_positions_mut() {
+        let t = mul(axes, [p[0].to_f64(), p[1].to_f64(), p[2].to_f64()]);
+        *p = [
+            T::from_f64(t[0] * scale),
+            T::from_f64(t[1] * scale),
+            T::from_f64(t[2] * scale),
+        ];
+    }
+    transform_attribs::<M, VertexIndex>(mesh, axes, scale);
+}
+
+/// Transform the samples of a field at grid points or cells into the new axes of a grid.
+///
+/// `source[r]` gives the old axis mapped onto new axis `r` and whether it is reversed, and
+/// `counts` gives the number of samples along each old axis.
+fn reindex_field(
+    data: &[f32],
+    tuple_size: usize,
+    counts: [usize; 3],
+    source: &[(usize, bool); 3],
+) -> Vec<f32> {
+    let new_counts = [
+        counts[source[0].0],
+        counts[source[1].0],
+        counts[source[2].0],
+    ];
+    let strides = [1, counts[0], counts[0] * counts[1]];
+    let mut order = Vec::with_capacity(new_counts.iter().product());
+    for k in 0..new_counts[2] {
+        for j in 0..new_counts[1] {
+            for i in 0..new_counts[0] {
+                let index = [i, j, k];
+                let old: usize = (0..3)
+                    .map(|r| {
+                        let (axis, reversed) = source[r];
+                        let idx = if reversed {
+                            new_counts[r] - 1 - index[r]
+                        } else {
+                            index[r]
+                        };
+                        idx * strides[axis]
+                    })
+                    .sum();
+                order.push(old);
+            }
+        }
+    }
+    reorder_tuples(data, tuple_size, &order)
+}
+
+impl GridMesh {
+    /// Convert this grid between the coordinate system of a file and Houdini.
+    ///
+    /// Grid axes are swapped and reversed along with the coordinate axes, reordering samples of
+    /// all fields, so the grid stays aligned with the axes. Vector fields named like the
+    /// transformed mesh attributes are converted in the same way.
+    pub fn transform(&mut self, transform: &CoordTransform, direction: TransformDirection) {
+        if transform.is_identity() {
+            return;
+        }
+        let axes = transform.axes_matrix(direction);
+        let scale = transform.unit_scale(direction);
+        self.transform_axes(&axes, scale);
+    }
+
+    fn transform_axes(&mut self, axes: &Matrix3, scale: f64) {
+        let mut source = [(0, false); 3];
+        for (r, row) in axes.iter().enumerate() {
+            let axis = (0..3).find(|&c| row[c] != 0.0).unwrap_or(r);
+            source[r] = (axis, row[axis] < 0.0);
+        }
+
+        let (origin, spacing, dims) = (self.origin, self.spacing, self.dims);
+        for (r, &(axis, reversed)) in source.iter().enumerate() {
+            let last = spacing[axis] * dims[axis].saturating_sub(1) as f64;
+            self.origin[r] = if reversed {
+                -scale * (origin[axis] + last)
+            } else {
+                scale * origin[axis]
+            };
+            self.spacing[r] = scale * spacing[axis];
+            self.dims[r] = dims[axis];
+        }
+
+        let sign = det(axes);
+        for field in self.fields.iter_mut() {
+            let counts = if field.cell {
+                [dims[0].max(2) - 1, dims[1].max(2) - 1, dims[2].max(2) - 1]
+            } else {
+                dims
+            };
+            field.data = reindex_field(&field.data, field.tuple_size, counts, &source);
+
+            let name = field.name.as_str();
+            let s = if VECTOR_ATTRIBS.contains(&name) && field.tuple_size == 3 {
+                scale
+            } else if NORMAL_ATTRIBS.contains(&name) && field.tuple_size == 3 {
+                1.0
+            } else if QUATERNION_ATTRIBS.contains(&name) && field.tuple_size == 4 {
+                sign
+            } else {
+                continue;
+            };
+            for v in field.data.chunks_exact_mut(field.tuple_size) {
+                let t = mul(axes, [v[0].into(), v[1].into(), v[2].into()]);
+                v[0] = (t[0] * s) as f32;
+                v[1] = (t[1] * s) as f32;
+                v[2] = (t[2] * s) as f32;
+            }
+        }
+    }
+}
+
+impl Mesh {
+    /// Convert this mesh between the coordinate system of a file and Houdini.
+    ///
+    /// Use `TransformDirection::Import` for meshes read from files and
+    /// `TransformDirection::Export` for meshes about to be written. Polygons and tetrahedra are
+    /// reversed when mirroring, so that they keep facing outwards. Grids are reindexed along the
+    /// new axes as in `GridMesh::transform`.
+    pub fn transform(&mut self, transform: &CoordTransform, direction: TransformDirection) {
+        if transform.is_identity() {
+            return;
+        }
+        let axes = transform.axes_matrix(direction);
+        let scale = transform.unit_scale(direction);
+        match self {
+            Mesh::TetMesh(m) => {
+                transform_points(&mut m.0, &axes, scale);
+                transform_attribs::<_, CellIndex>(&mut m.0, &axes, scale);
+                transform_attribs::<_, CellVertexIndex>(&mut m.0, &axes, scale);
+            }
+            Mesh::PolyMesh(PolyMesh(m)) | Mesh::PolylineMesh(PolylineMesh(m)) => {
+                transform_points(m, &axes, scale);
+                transform_attribs::<_, FaceIndex>(m, &axes, scale);
+                transform_attribs::<_, FaceVertexIndex>(m, &axes, scale);
+            }
+            Mesh::PointCloud(m) => transform_points(&mut m.0, &axes, scale),
+            Mesh::UnstructuredMesh(m) => {
+                transform_points(&mut m.0, &axes, scale);
+                transform_attribs::<_, CellIndex>(&mut m.0, &axes, scale);
+                transform_attribs::<_, CellVertexIndex>(&mut m.0, &axes, scale);
+            }
+            Mesh::TetMeshF32(m) => {
+                transform_points(&mut m.0, &axes, scale);
+                transform_attribs::<_, CellIndex>(&mut m.0, &axes, scale);
+                transform_attribs::<_, CellVertexIndex>(&mut m.0, &axes, scale);
+            }
+            Mesh::PolyMeshF32(PolyMeshF32(m)) | Mesh::PolylineMeshF32(PolylineMeshF32(m)) => {
+                transform_points(m, &axes, scale);
+                transform_attribs::<_, FaceIndex>(m, &axes, scale);
+                transform_attribs::<_, FaceVertexIndex>(m, &axes, scale);
+            }
+            Mesh::PointCloudF32(m) => transform_points(&mut m.0, &axes, scale),
+            Mesh::UnstructuredMeshF32(m) => {
+                transform_points(&mut m.0, &axes, scale);
+                transform_attribs::<_, CellIndex>(&mut m.0, &axes, scale);
+                transform_attribs::<_, CellVertexIndex>(&mut m.0, &axes, scale);
+            }
+            Mesh::GridMesh(m) => m.transform_axes(&axes, scale),
+            Mesh::None => {}
+        }
+        if transform.flip_handedness {
+            self.reorder(&VertexOrder::STANDARD);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GridField;
+
+    fn z_up_mirrored() -> CoordTransform {
+        CoordTransform {
+            up_axis: UpAxis::Z,
+            flip_handedness: true,
+            scale: 2.0,
+        }
+    }
+
+    #[test]
+    fn mirroring_keeps_up_axis() {
+        let axes = z_up_mirrored().axes_matrix(TransformDirection::Import);
+        assert_eq!(mul(&axes, [0.0, 0.0, 1.0]), [0.0, 1.0, 0.0]);
+        assert_eq!(mul(&axes, [1.0, 0.0, 0.0]), [-1.0, 0.0, 0.0]);
+        assert_eq!(det(&axes), -1.0);
+    }
+
+    #[test]
+    fn grid_samples_follow_axes() {
+        let transform = z_up_mirrored();
+        let mut grid = GridMesh::new([1.0, 2.0, 3.0], [0.5, 1.0, 2.0], [2, 3, 4]);
+        let n = grid.num_samples(false);
+        let positions: Vec<[f64; 3]> = (0..n)
+            .map(|i| {
+                let idx = [i % 2, (i / 2) % 3, i / 6];
+                [0, 1, 2].map(|a| grid.origin[a] + grid.spacing[a] * idx[a] as f64)
+            })
+            .collect();
+        let data: Vec<f32> = (0..n).map(|i| i as f32).collect();
+        grid.add_field("id", 1, false, &data).unwrap();
+        let original = grid.clone();
+
+        grid.transform(&transform, TransformDirection::Import);
+        assert_eq!(grid.dims, [2, 4, 3]);
+        let axes = transform.axes_matrix(TransformDirection::Import);
+        for (i, &id) in grid.fields[0].data.iter().enumerate() {
+            let idx = [i % 2, (i / 2) % 4, i / 8];
+            let p = [0, 1, 2].map(|a| grid.origin[a] + grid.spacing[a] * idx[a] as f64);
+            let expected = mul(&axes, positions[id as usize]).map(|x| x * transform.scale);
+            for (x, y) in p.iter().zip(expected.iter()) {
+                assert!((x - y).abs() < 1e-12);
+            }
+        }
+
+        grid.transform(&transform, TransformDirection::Export);
+        assert_eq!(grid, original);
+    }
+}
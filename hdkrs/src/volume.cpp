@@ -0,0 +1,251 @@
+#include <optional>
+#include <vector>
+#include <string>
+#include <stdexcept>
+
+#include <UT/UT_Debug.h>
+#include <UT/UT_VoxelArray.h>
+#include <GU/GU_Detail.h>
+#include <GU/GU_PrimVolume.h>
+#include <GEO/GEO_PrimVolume.h>
+#include <GEO/GEO_PrimVDB.h>
+
+#include <rust/cxx.h>
+#include "hdkrs/src/lib.rs.h"
+
+#include "mesh.h"
+
+using namespace hdkrs;
+
+namespace {
+
+// Layout of a regular grid of samples taken at voxel centers.
+struct GridLayout {
+    int res[3];
+    UT_Vector3 origin;
+    UT_Vector3 spacing;
+
+    bool matches(const GridLayout &other) const {
+        for (int a = 0; a < 3; ++a) {
+            if (res[a] != other.res[a]) return false;
+        }
+        return origin.isEqual(other.origin, 1e-5) && spacing.isEqual(other.spacing, 1e-5);
+    }
+};
+
+template<typename V>
+GridLayout grid_layout(const V *vol, const int min[3], const int res[3]) {
+    GridLayout layout;
+    UT_Vector3 p0, px, py, pz;
+    vol->indexToPos(min[0], min[1], min[2], p0);
+    vol->indexToPos(min[0] + 1, min[1], min[2], px);
+    vol->indexToPos(min[0], min[1] + 1, min[2], py);
+    vol->indexToPos(min[0], min[1], min[2] + 1, pz);
+    for (int a = 0; a < 3; ++a) {
+        layout.res[a] = res[a];
+    }
+    layout.origin = p0;
+    // Only axis aligned volumes are supported, so we ignore any rotation here.
+    layout.spacing = UT_Vector3((px - p0).length(), (py - p0).length(), (pz - p0).length());
+    return layout;
+}
+
+std::string volume_name(const GU_Detail &detail, GA_Offset prim_off, std::size_t idx) {
+    GA_ROHandleS name_h(detail.findPrimitiveAttribute("name"));
+    if (name_h.isValid()) {
+        const char *name = name_h.get(prim_off);
+        if (name && *name) {
+            return std::string(name);
+        }
+    }
+    return "volume" + std::to_string(idx);
+}
+
+// Sample the values of a VDB grid over the given bounding box into a flat array of tuples.
+//
+// Values are read through an accessor, which caches the path to the last visited leaf so that
+// neighbouring voxels don't each traverse the tree from the root.
+struct VdbSampler {
+    openvdb::CoordBBox bbox;
+    std::vector<float> &data;
+
+    template<typename T>
+    void push(const T &value) {
+        data.push_back(float(value));
+    }
+    template<typename T>
+    void push(const openvdb::math::Vec3<T> &value) {
+        for (int i = 0; i < 3; ++i) {
+            data.push_back(float(value[i]));
+        }
+    }
+
+    template<typename GridT>
+    void operator()(const GridT &grid) {
+        auto acc = grid.getConstAccessor();
+        openvdb::Coord ijk;
+        for (ijk[2] = bbox.min()[2]; ijk[2] <= bbox.max()[2]; ++ijk[2]) {
+            for (ijk[1] = bbox.min()[1]; ijk[1] <= bbox.max()[1]; ++ijk[1]) {
+                for (ijk[0] = bbox.min()[0]; ijk[0] <= bbox.max()[0]; ++ijk[0]) {
+                    push(acc.getValue(ijk));
+                }
+            }
+        }
+    }
+};
+
+} // namespace
+
+/**
+ * Add a regular grid to the current detail as one volume primitive per field component.
+ */
+void hdkrs::add_grid(GU_Detail& detail, const hdkrs::GridMesh& grid) {
+    auto origin = grid.get_origin();
+    auto spacing = grid.get_spacing();
+    auto dims = grid.get_dims();
+
+    GA_RWHandleS name_h(detail.addStringTuple(GA_ATTRIB_PRIMITIVE, "name", 1));
+    const char *suffixes[] = { ".x", ".y", ".z", ".w" };
+
+    for (std::size_t i = 0; i < grid.num_fields(); ++i) {
+        bool cell = grid.field_is_cell(i);
+        std::size_t tuple_size = grid.field_tuple_size(i);
+        auto data = grid.field_data(i);
+        auto name_str = grid.field_name(i);
+        std::string name(name_str.begin(), name_str.end());
+
+        // Volume voxels are centered at the grid samples, which are either the grid points or
+        // the cell centers.
+        int res[3];
+        UT_Vector3 center;
+        UT_Matrix3 xform(1.0);
+        for (int a = 0; a < 3; ++a) {
+            res[a] = cell ? std::max(int(dims[a]) - 1, 1) : int(dims[a]);
+            fpreal first = origin[a] + (cell ? 0.5*spacing[a] : 0.0);
+            center[a] = first + 0.5*(res[a] - 1)*spacing[a];
+        }
+        xform.scale(0.5*res[0]*spacing[0], 0.5*res[1]*spacing[1], 0.5*res[2]*spacing[2]);
+
+        for (std::size_t c = 0; c < tuple_size; ++c) {
+            GU_PrimVolume *vol = GU_PrimVolume::build(&detail);
+            vol->setTransform(xform);
+            detail.setPos3(vol->getPointOffset(0), center);
+
+            UT_VoxelArrayWriteHandleF h = vol->getVoxelWriteHandle();
+            h->size(res[0], res[1], res[2]);
+            std::size_t idx = 0;
+            for (int z = 0; z < res[2]; ++z) {
+                for (int y = 0; y < res[1]; ++y) {
+                    for (int x = 0; x < res[0]; ++x, ++idx) {
+                        h->setValue(x, y, z, data[tuple_size*idx + c]);
+                    }
+                }
+            }
+
+            if (name_h.isValid()) {
+                if (tuple_size == 1) {
+                    name_h.set(vol->getMapOffset(), name.c_str());
+                } else if (tuple_size <= 4) {
+                    name_h.set(vol->getMapOffset(), (name + suffixes[c]).c_str());
+                } else {
+                    // Larger tuples have no conventional component names, so use the index.
+                    name_h.set(vol->getMapOffset(), (name + "." + std::to_string(c)).c_str());
+                }
+            }
+        }
+    }
+}
+
+/**
+ * Build a regular grid from the volume and VDB primitives in the given detail.
+ *
+ * Samples are taken at voxel centers, which become the grid points. All volumes must share the
+ * layout of the first volume found, and VDBs must store scalars or 3-vectors, otherwise an
+ * error is thrown naming the offending primitive.
+ */
+rust::box<hdkrs::GridMesh> hdkrs::build_grid(const GU_Detail& detail) {
+    std::optional<GridLayout> layout;
+    std::optional<rust::box<GridMesh>> grid;
+    std::vector<float> data;
+
+    auto init_grid = [&](const GridLayout &l) -> bool {
+        if (!layout) {
+            layout = l;
+            double origin[] = { l.origin[0], l.origin[1], l.origin[2] };
+            double spacing[] = { l.spacing[0], l.spacing[1], l.spacing[2] };
+            std::size_t dims[] = { std::size_t(l.res[0]), std::size_t(l.res[1]), std::size_t(l.res[2]) };
+            grid = make_grid(
+                    rust::Slice<const double>(origin, 3),
+                    rust::Slice<const double>(spacing, 3),
+                    rust::Slice<const std::size_t>(dims, 3));
+            return true;
+        }
+        return layout->matches(l);
+    };
+
+    std::size_t num_volumes = 0;
+    for ( GA_Offset prim_off : detail.getPrimitiveRange() )
+    {
+        const GEO_Primitive *prim = detail.getGEOPrimitive(prim_off);
+        if (prim->getTypeId() == GA_PRIMVOLUME) {
+            const GEO_PrimVolume *vol = static_cast<const GEO_PrimVolume*>(prim);
+            int min[3] = { 0, 0, 0 };
+            int res[3];
+            vol->getRes(res[0], res[1], res[2]);
+            auto name = volume_name(detail, prim_off, num_volumes++);
+            if (!init_grid(grid_layout(vol, min, res))) {
+                throw std::runtime_error(
+                        "Volume \"" + name + "\" has a different resolution or placement than the first volume");
+            }
+
+            UT_VoxelArrayReadHandleF h = vol->getVoxelHandle();
+            data.clear();
+            data.reserve(res[0]*res[1]*res[2]);
+            for (int z = 0; z < res[2]; ++z) {
+                for (int y = 0; y < res[1]; ++y) {
+                    for (int x = 0; x < res[0]; ++x) {
+                        data.push_back((*h)(x, y, z));
+                    }
+                }
+            }
+            (*grid)->add_field(name, 1, false, rust::Slice<const float>(data.data(), data.size()));
+        } else if (prim->getTypeId() == GA_PRIMVDB) {
+            const GEO_PrimVDB *vdb = static_cast<const GEO_PrimVDB*>(prim);
+            int tuple_size = vdb->getTupleSize();
+            auto name = volume_name(detail, prim_off, num_volumes++);
+            if (tuple_size != 1 && tuple_size != 3) {
+                throw std::runtime_error(
+                        "VDB \"" + name + "\" has " + std::to_string(tuple_size)
+                        + " components, but only scalar and vector VDBs are supported");
+            }
+
+            // Sample the bounding box of active voxels.
+            openvdb::CoordBBox bbox = vdb->getGrid().evalActiveVoxelBoundingBox();
+            if (bbox.empty()) continue;
+            int min[3] = { bbox.min().x(), bbox.min().y(), bbox.min().z() };
+            int res[3] = { bbox.dim().x(), bbox.dim().y(), bbox.dim().z() };
+            if (!init_grid(grid_layout(vdb, min, res))) {
+                throw std::runtime_error(
+                        "VDB \"" + name + "\" has a different resolution or placement than the first volume");
+            }
+
+            data.clear();
+            data.reserve(tuple_size*res[0]*res[1]*res[2]);
+            VdbSampler sampler{bbox, data};
+            bool sampled = tuple_size == 1
+                ? UTvdbProcessTypedGridScalar(vdb->getStorageType(), vdb->getGrid(), sampler)
+                : UTvdbProcessTypedGridVec3(vdb->getStorageType(), vdb->getGrid(), sampler);
+            if (!sampled) {
+                throw std::runtime_error("VDB \"" + name + "\" has an unsupported value type");
+            }
+            (*grid)->add_field(name, tuple_size, false, rust::Slice<const float>(data.data(), data.size()));
+        }
+    }
+
+    // Only creating a grid if there are volumes.
+    if (!grid) {
+        throw std::runtime_error("No volumes found");
+    }
+
+    return std::move(*grid);
+}
@@ -0,0 +1,157 @@
+/**
+ * Test that double precision point positions survive a round trip through a detail.
+ *
+ * Meshes far from the origin, whose coordinates can't be represented in single precision, are
+ * added with the `add_*` functions to details with and without positions promoted to 64-bit
+ * storage by `promote_p64`. Positions are then read back directly and extracted again with the
+ * `build_*` functions, which must reproduce the original coordinates exactly when P is stored
+ * in double precision, and rounded to single precision otherwise.
+ *
+ * Usage:
+ *
+ *     p64
+ *
+ * Prints each failed check and exits with a non-zero status if any check fails.
+ */
+
+#include <cstdio>
+#include <exception>
+#include <iostream>
+#include <vector>
+
+#include <GU/GU_Detail.h>
+#include <UT/UT_Vector3.h>
+
+#include <hdkrs/prelude.h>
+
+namespace {
+
+int failures = 0;
+
+void check(bool condition, const char *what)
+{
+    if (!condition) {
+        std::printf("FAILED: %s\n", what);
+        ++failures;
+    }
+}
+
+// Coordinates of a small tetrahedron a million units from the origin.
+std::vector<double> far_coords()
+{
+    const double o = 1.0e6;
+    return { o, o, o,  o + 0.1, o, o,  o, o + 0.1, o,  o, o, o + 0.1 };
+}
+
+// The given coordinates, rounded to single precision if `p64` is false.
+std::vector<double> expected_coords(const std::vector<double> &coords, bool p64)
+{
+    std::vector<double> expected(coords);
+    if (!p64) {
+        for (double &x : expected) {
+            x = float(x);
+        }
+    }
+    return expected;
+}
+
+// Check that positions stored in the given detail match the given coordinates exactly.
+void check_positions(const GU_Detail &detail, const std::vector<double> &coords, const char *what)
+{
+    bool same = 3*detail.getNumPoints() == GA_Size(coords.size());
+    std::size_t i = 0;
+    for ( GA_Offset pt_off : detail.getPointRange() ) {
+        if (!same) {
+            break;
+        }
+        UT_Vector3D p = detail.getPos3D(pt_off);
+        same = p.x() == coords[i] && p.y() == coords[i + 1] && p.z() == coords[i + 2];
+        i += 3;
+    }
+    check(same, what);
+}
+
+// Check that the given extracted coordinates match the given coordinates exactly.
+void check_coords(rust::Slice<const double> actual, const std::vector<double> &coords, const char *what)
+{
+    check(std::vector<double>(actual.begin(), actual.end()) == coords, what);
+}
+
+template<typename T>
+rust::Slice<const T> slice(const std::vector<T> &v)
+{
+    return rust::Slice<const T>(v.data(), v.size());
+}
+
+// Add each type of mesh to a detail and extract it again, with 64-bit positions if `p64`.
+void round_trip(bool p64)
+{
+    std::vector<double> coords = far_coords();
+    std::vector<double> expected = expected_coords(coords, p64);
+    std::vector<std::size_t> triangles = { 3, 0, 1, 2,  3, 0, 3, 1 };
+    std::vector<std::size_t> lines = { 4, 0, 1, 2, 3 };
+    std::vector<std::size_t> tets = { 0, 1, 2, 3 };
+
+    auto new_detail = [p64](GU_Detail &detail) {
+        if (p64) {
+            hdkrs::promote_p64(detail);
+        }
+        check(hdkrs::has_f64_positions(detail) == p64, "has_f64_positions reports P storage");
+    };
+
+    {
+        GU_Detail detail;
+        new_detail(detail);
+        hdkrs::add_polymesh(detail, *hdkrs::make_polymesh(slice(coords), slice(triangles)));
+        check_positions(detail, expected, "add_polymesh positions");
+        check_coords(hdkrs::build_polymesh(detail)->point_coords(), expected, "build_polymesh positions");
+    }
+    {
+        GU_Detail detail;
+        new_detail(detail);
+        hdkrs::add_polylines(detail, *hdkrs::make_polylines(slice(coords), slice(lines)));
+        check_positions(detail, expected, "add_polylines positions");
+        check_coords(hdkrs::build_polylines(detail)->point_coords(), expected, "build_polylines positions");
+    }
+    {
+        GU_Detail detail;
+        new_detail(detail);
+        hdkrs::add_tetmesh(detail, *hdkrs::make_tetmesh(slice(coords), slice(tets)));
+        check_positions(detail, expected, "add_tetmesh positions");
+        check_coords(hdkrs::build_tetmesh(detail)->point_coords(), expected, "build_tetmesh positions");
+    }
+    {
+        GU_Detail detail;
+        new_detail(detail);
+        hdkrs::add_pointcloud(detail, *hdkrs::make_pointcloud(slice(coords)));
+        check_positions(detail, expected, "add_pointcloud positions");
+        check_coords(hdkrs::build_pointcloud(detail)->point_coords(), expected, "build_pointcloud positions");
+
+        // Move the points by a distance below single precision resolution at this scale.
+        std::vector<double> moved(coords);
+        for (double &x : moved) {
+            x += 0.01;
+        }
+        hdkrs::update_points(detail, *hdkrs::make_pointcloud(slice(moved)));
+        check_positions(detail, expected_coords(moved, p64), "update_points positions");
+    }
+}
+
+} // namespace
+
+int main()
+{
+    try {
+        round_trip(true);
+        round_trip(false);
+    } catch (const std::exception &e) {
+        std::cerr << e.what() << std::endl;
+        return 1;
+    }
+    if (failures > 0) {
+        std::printf("%d checks failed\n", failures);
+        return 1;
+    }
+    std::printf("All checks passed\n");
+    return 0;
+}
@@ -20,12 +20,29 @@
 
 using namespace std;
 
+// Prefix of environment variables selecting the coordinate system and vertex order of MSH
+// files.
+static const char *ENV_PREFIX = "MSHIO";
+
+// Vertex order of files, which defaults to the convention of the format.
+static hdkrs::VertexOrder
+vertexOrder()
+{
+    return hdkrs::vertex_order_from_env(ENV_PREFIX, mshio::default_vertex_order());
+}
+
 GEO_IOTranslator *
 GEO_MshIO::duplicate() const
 {
     return new GEO_MshIO(*this);
 }
 
+hdkrs::CoordTransform
+GEO_MshIO::coordTransform() const
+{
+    return hdkrs::coord_transform_from_env(ENV_PREFIX);
+}
+
 const char *
 GEO_MshIO::formatName() const
 {
@@ -35,10 +52,7 @@ GEO_MshIO::formatName() const
 int
 GEO_MshIO::checkExtension(const char *name) 
 {
-    UT_String sname(name);
-    if (sname.fileExtension() && !strcmp(sname.fileExtension(), ".msh"))
-        return true;
-    return false;
+    return hdkrs::has_extension(name, ".msh");
 }
 
 int
@@ -53,12 +67,12 @@ GEO_MshIO::fileLoad(GEO_Detail *detail, UT_IStream &is, bool)
     if (!detail) // nothing to do
         return GA_Detail::IOStatus(true);
 
-    UT_WorkBuffer buf;
-    bool success = is.getAll(buf);
-    if (!success)
-        return GA_Detail::IOStatus(success);
-    rust::Slice<const uint8_t> slice(reinterpret_cast<const unsigned char*>(buf.buffer()), buf.length());
-    mshio::add_msh_mesh(static_cast<GU_Detail &>(*detail), slice);
+    GU_Detail &gdp = static_cast<GU_Detail &>(*detail);
+    hdkrs::IStreamReader reader(is, "Loading MSH");
+    hdkrs::CookResult result = mshio::add_msh_mesh_from_stream(gdp, reader);
+    bool success = hdkrs::report_cook_result(result);
+    if (success)
+        hdkrs::import_detail(gdp, coordTransform(), vertexOrder());
     return GA_Detail::IOStatus(success);
 }
 
@@ -75,6 +89,8 @@ newGeometryIO(void *)
     GU_Detail::registerIOTranslator(new GEO_MshIO());
     UT_ExtensionList *geoextension;
     geoextension = UTgetGeoExtensions();
-    if (!geoextension->findExtension("msh"))
-        geoextension->addExtension("msh");
+    for (auto ext : {"msh", "msh.gz", "msh.zst"}) {
+        if (!geoextension->findExtension(ext))
+            geoextension->addExtension(ext);
+    }
 }
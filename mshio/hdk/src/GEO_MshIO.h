@@ -3,6 +3,7 @@
 #include <GU/GU_Detail.h>
 #include <GEO/GEO_IOTranslator.h>
 #include <UT/UT_IStream.h>
+#include <hdkrs/src/lib.rs.h>
 #include <iostream>
 
 class GEO_MshIO : public GEO_IOTranslator
@@ -17,4 +18,6 @@ public:
 	virtual int checkMagicNumber(unsigned);
 	virtual GA_Detail::IOStatus fileLoad(GEO_Detail*, UT_IStream&, bool);
 	virtual GA_Detail::IOStatus fileSave(const GEO_Detail*, std::ostream&);
+	// Coordinate system of files, read from the environment on each use.
+	hdkrs::CoordTransform coordTransform() const;
 };
@@ -11,26 +11,98 @@ mod ffi {
         include!("rust/cxx.h");
         type GU_Detail = hdkrs::ffi::GU_Detail;
     }
+    #[namespace = "hdkrs"]
+    extern "C++" {
+        type IStreamReader = hdkrs::ffi::IStreamReader;
+        type VertexOrder = hdkrs::ffi::VertexOrder;
+        type CookResult = hdkrs::ffi::CookResult;
+    }
     extern "Rust" {
+        fn default_vertex_order() -> VertexOrder;
+
         // Export currently unsupported.
 
         fn add_msh_mesh(detail: Pin<&mut GU_Detail>, data: &[u8]);
+        fn add_msh_mesh_from_stream(
+            detail: Pin<&mut GU_Detail>,
+            reader: Pin<&mut IStreamReader>,
+        ) -> CookResult;
     }
 }
 
-use hdkrs::ffi::GU_Detail;
+use hdkrs::ffi::{CookResult, GU_Detail, IStreamReader, VertexOrder};
+use hdkrs::interop;
+
+/// Vertex order of polygons and tetrahedra in Gmsh files.
+pub const VERTEX_ORDER: VertexOrder = VertexOrder::STANDARD;
+
+/// Vertex order of Gmsh files used when no convention is given in the environment.
+pub fn default_vertex_order() -> VertexOrder {
+    VERTEX_ORDER
+}
 
 /// Parse a given byte array into a Mesh assuming msh format.
+///
+/// Gzip and zstd compressed data is decompressed transparently.
 pub fn parse_msh_mesh(data: &[u8]) -> Box<hdkrs::Mesh> {
-    if let Ok(msh) = parse_msh_bytes(data) {
-        if let Ok(mesh) = <MshFile<u64, i32, f64> as MeshExtractor<f64>>::extract_mesh(&msh) {
-            return Box::new(hdkrs::Mesh::from(mesh));
-        }
+    Box::new(load_msh_mesh(data).unwrap_or(hdkrs::Mesh::None))
+}
+
+/// Parse a given byte array into a Mesh assuming msh format, describing why it could not be
+/// loaded on failure.
+///
+/// Gzip and zstd compressed data is decompressed transparently.
+pub fn load_msh_mesh(data: &[u8]) -> Result<hdkrs::Mesh, String> {
+    let data = hdkrs::stream::decompress_bytes(data).map_err(|e| e.to_string())?;
+    load_decompressed_msh_mesh(&data)
+}
+
+/// Parse the given uncompressed msh data into a Mesh as in `load_msh_mesh`.
+///
+/// Gmsh always stores node coordinates in double precision, so the mesh is kept in double
+/// precision only if rounding its positions to single precision would lose accuracy.
+fn load_decompressed_msh_mesh(data: &[u8]) -> Result<hdkrs::Mesh, String> {
+    let msh = parse_msh_bytes(data).map_err(|e| e.to_string())?;
+    let mesh = <MshFile<u64, i32, f64> as MeshExtractor<f64>>::extract_mesh(&msh)
+        .map(hdkrs::Mesh::from)
+        .map_err(|_| String::from("No supported elements found"))?;
+    if mesh.needs_f64_positions() {
+        return Ok(mesh);
     }
-    Box::new(hdkrs::Mesh::None)
+    drop(mesh);
+    <MshFile<u64, i32, f64> as MeshExtractor<f32>>::extract_mesh(&msh)
+        .map(hdkrs::Mesh::from)
+        .map_err(|_| String::from("No supported elements found"))
 }
 
 /// Parse a given byte array into a Mesh and add it to the given detail.
 pub fn add_msh_mesh(detail: Pin<&mut GU_Detail>, data: &[u8]) {
     parse_msh_mesh(data).add_to_detail(detail);
 }
+
+/// Read msh data from the given stream and add the resulting mesh to the given detail.
+///
+/// The msh parser works on a contiguous buffer, so the whole file is read into memory before
+/// parsing. Compressed streams are decompressed as they are read, straight into that buffer.
+/// Returns an error if the stream could not be read or parsed, including when the user
+/// interrupts the load.
+pub fn add_msh_mesh_from_stream(
+    detail: Pin<&mut GU_Detail>,
+    reader: Pin<&mut IStreamReader>,
+) -> CookResult {
+    use std::io::Read;
+    let mut data = Vec::new();
+    if let Err(err) = hdkrs::stream::decompress(hdkrs::stream::StreamReader::new(reader))
+        .and_then(|mut reader| reader.read_to_end(&mut data))
+    {
+        return interop::CookResult::Error(format!("Failed to read msh file: {}", err)).into();
+    }
+    match load_decompressed_msh_mesh(&data) {
+        Ok(mesh) => {
+            mesh.add_to_detail(detail);
+            interop::CookResult::Success(String::new())
+        }
+        Err(err) => interop::CookResult::Error(format!("Failed to load msh file: {}", err)),
+    }
+    .into()
+}
@@ -1,5 +1,9 @@
+#include <cstdlib>
+#include <cstring>
+#include <fstream>
 #include <iostream>
 #include <optional>
+#include <string>
 
 // Required for proper loading.
 #include <UT/UT_DSOVersion.h>
@@ -9,6 +13,7 @@
 #include <UT/UT_IStream.h>
 #include <SOP/SOP_Node.h>
 #include <UT/UT_IOTable.h>
+#include <UT/UT_ErrorManager.h>
 
 // All relevant hdkrs headers. This includes HDK specific headers and the cxx bridge headers.
 #include <hdkrs/prelude.h>
@@ -20,12 +25,225 @@
 
 using namespace std;
 
+// Primitive attribute storing material assignments.
+static const char *MATERIAL_ATTRIB = "shop_materialpath";
+
+// Primitive attribute storing object names.
+static const char *OBJECT_ATTRIB = "name";
+
+// Prefix of primitive attributes used to pass group membership to and from objio.
+static const char *GROUP_ATTRIB_PREFIX = "group_";
+
+// Primitive attribute storing smoothing groups.
+static const char *SMOOTHING_ATTRIB = "smoothing_group";
+
+// Environment variable disabling non-standard extensions, such as vertex colors, on export.
+static const char *STRICT_ENV_VAR = "OBJIO_STRICT";
+
+// Prefix of environment variables selecting the coordinate system and vertex order of obj
+// files.
+static const char *ENV_PREFIX = "OBJIO";
+
+// Vertex order of files, which defaults to the convention of the format.
+static hdkrs::VertexOrder
+vertexOrder()
+{
+    return hdkrs::vertex_order_from_env(ENV_PREFIX, objio::default_vertex_order());
+}
+
+// Prefix of detail attributes storing material library parameters.
+static const char *MTL_ATTRIB_PREFIX = "mtl_";
+
+// Check if strictly standard obj files are requested.
+static bool
+isStrict()
+{
+    const char *strict = std::getenv(STRICT_ENV_VAR);
+    return strict && *strict && std::strcmp(strict, "0") != 0;
+}
+
+// Options for loading obj files into Houdini attributes.
+static objio::LoadOptions
+loadOptions()
+{
+    objio::LoadOptions options;
+    options.material_attrib = MATERIAL_ATTRIB;
+    options.object_attrib = OBJECT_ATTRIB;
+    options.group_attrib_prefix = GROUP_ATTRIB_PREFIX;
+    options.smoothing_attrib = SMOOTHING_ATTRIB;
+    options.vertex_colors = true;
+    return options;
+}
+
+// Options for saving obj files from Houdini attributes.
+static objio::SaveOptions
+saveOptions()
+{
+    objio::SaveOptions options;
+    options.material_attrib = MATERIAL_ATTRIB;
+    options.object_attrib = OBJECT_ATTRIB;
+    options.group_attrib_prefix = GROUP_ATTRIB_PREFIX;
+    options.smoothing_attrib = SMOOTHING_ATTRIB;
+    options.vertex_colors = !isStrict();
+    return options;
+}
+
+// Name of the primitive attribute marking membership in the given group, shared with objio.
+static UT_String
+groupAttribName(const std::string &group)
+{
+    return UT_String(std::string(objio::group_attrib_name(GROUP_ATTRIB_PREFIX, group)));
+}
+
+// Convert primitive attributes marking membership in the given groups into primitive groups.
+static void
+convertGroupAttribs(GU_Detail &detail, const rust::Vec<rust::String> &groups)
+{
+    for (const auto &group : groups) {
+        auto attrib_name = groupAttribName(std::string(group));
+        GA_ROHandleI h(detail.findPrimitiveAttribute(attrib_name));
+        if (h.isInvalid()) continue;
+        UT_String group_name(std::string(group));
+        group_name.forceValidGroupName();
+        GA_PrimitiveGroup *prim_group = detail.findPrimitiveGroup(group_name);
+        if (!prim_group)
+            prim_group = detail.newPrimitiveGroup(group_name);
+        GA_Offset primoff;
+        GA_FOR_ALL_PRIMOFF(&detail, primoff) {
+            if (h.get(primoff) != 0)
+                prim_group->addOffset(primoff);
+        }
+        detail.destroyPrimitiveAttribute(attrib_name);
+    }
+}
+
+// Copy the source detail into `detail` with its primitive groups stored in attributes marking
+// group membership, and add the group names to `options` to be written by objio.
+//
+// Returns false without copying if the source has no primitive groups.
+static bool
+copyWithGroupAttribs(GU_Detail &detail, const GU_Detail &src, objio::SaveOptions &options)
+{
+    bool has_groups = false;
+    for (auto it = src.primitiveGroups().beginTraverse(); !it.atEnd(); ++it) {
+        if (!it.group()->isInternal()) {
+            has_groups = true;
+            break;
+        }
+    }
+    if (!has_groups)
+        return false;
+
+    detail.duplicate(src);
+    for (auto it = detail.primitiveGroups().beginTraverse(); !it.atEnd(); ++it) {
+        const GA_PrimitiveGroup *group = it.group();
+        if (group->isInternal()) continue;
+        auto attrib_name = groupAttribName(group->getName().toStdString());
+        GA_RWHandleI h(detail.addTuple(GA_STORE_INT32, GA_ATTRIB_PRIMITIVE, attrib_name, 1));
+        if (h.isInvalid()) continue;
+        for (GA_Iterator prim_it(detail.getPrimitiveRange(group)); !prim_it.atEnd(); ++prim_it)
+            h.set(*prim_it, 1);
+        options.groups.push_back(rust::String(group->getName().toStdString()));
+    }
+    return true;
+}
+
+// Name of the detail attribute storing the given material library parameter.
+static std::string
+mtlAttribName(const std::string &material, const std::string &key)
+{
+    UT_String name(MTL_ATTRIB_PREFIX + material + "_" + key);
+    name.forceValidVariableName();
+    return name.toStdString();
+}
+
+// Store material library parameters in detail attributes named `mtl_<material>_<key>`.
+static void
+addMaterialAttribs(GU_Detail &detail, const rust::Vec<objio::MtlParam> &params)
+{
+    for (const auto &param : params) {
+        auto name = mtlAttribName(std::string(param.material), std::string(param.key));
+        if (!param.values.empty()) {
+            auto h = GA_RWHandleD(detail.addTuple(GA_STORE_REAL64, GA_ATTRIB_DETAIL, name.c_str(), param.values.size()));
+            if (h.isInvalid()) continue;
+            for (std::size_t i = 0; i < param.values.size(); ++i)
+                h.set(GA_Offset(0), i, param.values[i]);
+        } else {
+            auto h = GA_RWHandleS(detail.addTuple(GA_STORE_STRING, GA_ATTRIB_DETAIL, name.c_str(), 1));
+            if (h.isInvalid()) continue;
+            h.set(GA_Offset(0), 0, std::string(param.text));
+        }
+    }
+}
+
+// Collect material library parameters of the given materials from detail attributes written
+// by `addMaterialAttribs`.
+static rust::Vec<objio::MtlParam>
+getMaterialParams(const GU_Detail &detail, const rust::Vec<rust::String> &materials)
+{
+    rust::Vec<objio::MtlParam> params;
+    for (auto it = detail.getAttributeDict(GA_ATTRIB_DETAIL).begin(GA_SCOPE_PUBLIC); !it.atEnd(); ++it) {
+        const GA_Attribute *attrib = it.attrib();
+        std::string name = attrib->getName().toStdString();
+        // Pick the longest matching material name since material names may contain underscores.
+        const rust::String *material = nullptr;
+        std::size_t prefix_len = 0;
+        for (const auto &m : materials) {
+            auto prefix = mtlAttribName(std::string(m), "");
+            if (prefix.size() > prefix_len && name.size() > prefix.size() && name.rfind(prefix, 0) == 0) {
+                material = &m;
+                prefix_len = prefix.size();
+            }
+        }
+        if (!material)
+            continue;
+
+        objio::MtlParam param;
+        param.material = *material;
+        param.key = name.substr(prefix_len);
+        GA_ROHandleS str_h(attrib);
+        GA_ROHandleD num_h(attrib);
+        if (str_h.isValid()) {
+            param.text = str_h.get(GA_Offset(0)).toStdString();
+        } else if (num_h.isValid()) {
+            for (int i = 0; i < attrib->getTupleSize(); ++i)
+                param.values.push_back(num_h.get(GA_Offset(0), i));
+        } else {
+            continue;
+        }
+        params.push_back(std::move(param));
+    }
+    return params;
+}
+
+// Path of the material library written alongside the given obj file.
+static std::string
+mtlPath(const char *fname)
+{
+    std::string path(fname);
+    for (const char *suffix : hdkrs::COMPRESSION_SUFFIXES) {
+        if (hdkrs::ends_with(path, suffix)) {
+            path.resize(path.size() - std::strlen(suffix));
+            break;
+        }
+    }
+    if (hdkrs::ends_with(path, ".obj"))
+        path.resize(path.size() - 4);
+    return path + ".mtl";
+}
+
 GEO_IOTranslator *
 GEO_ObjIO::duplicate() const
 {
     return new GEO_ObjIO(*this);
 }
 
+hdkrs::CoordTransform
+GEO_ObjIO::coordTransform() const
+{
+    return hdkrs::coord_transform_from_env(ENV_PREFIX);
+}
+
 const char *
 GEO_ObjIO::formatName() const
 {
@@ -35,10 +253,7 @@ GEO_ObjIO::formatName() const
 int
 GEO_ObjIO::checkExtension(const char *name) 
 {
-    UT_String sname(name);
-    if (sname.fileExtension() && !strcmp(sname.fileExtension(), ".obj"))
-        return true;
-    return false;
+    return hdkrs::has_extension(name, ".obj");
 }
 
 int
@@ -53,12 +268,28 @@ GEO_ObjIO::fileLoad(GEO_Detail *detail, UT_IStream &is, bool)
     if (!detail) // nothing to do
         return GA_Detail::IOStatus(true);
 
-    UT_WorkBuffer buf;
-    bool success = is.getAll(buf);
-    if (!success)
-        return GA_Detail::IOStatus(success);
-    rust::Slice<const uint8_t> slice(reinterpret_cast<const unsigned char*>(buf.buffer()), buf.length());
-    objio::add_obj_mesh(static_cast<GU_Detail &>(*detail), slice);
+    GU_Detail &gdp = static_cast<GU_Detail &>(*detail);
+    objio::LoadOptions options = loadOptions();
+    // Material libraries are resolved relative to the file being loaded.
+    if (is.getLabel())
+        options.path = is.getLabel();
+    objio::ObjInfo info;
+
+    hdkrs::IStreamReader reader(is, "Loading OBJ");
+    hdkrs::CookResult result = objio::add_obj_mesh_from_stream_with_options(gdp, reader, options, info);
+    // Report skipped elements and load failures to the node reading the file.
+    std::string message(result.message);
+    if (result.tag == hdkrs::CookResultTag::WARNING)
+        UTaddWarning("SOP", SOP_MESSAGE, message.c_str());
+    else if (result.tag == hdkrs::CookResultTag::ERROR)
+        UTaddError("SOP", SOP_MESSAGE, message.c_str());
+
+    bool success = result.tag != hdkrs::CookResultTag::ERROR;
+    if (success) {
+        addMaterialAttribs(gdp, info.materials);
+        convertGroupAttribs(gdp, info.groups);
+        hdkrs::import_detail(gdp, coordTransform(), vertexOrder());
+    }
     return GA_Detail::IOStatus(success);
 }
 
@@ -68,15 +299,26 @@ GEO_ObjIO::fileSave(const GEO_Detail *detail, std::ostream &os)
     if (!detail) // nothing to do
         return GA_Detail::IOStatus(true);
 
+    objio::SaveOptions options = saveOptions();
+    options.mtllib = myMtlLib;
+
+    const GU_Detail *gdp = static_cast<const GU_Detail*>(detail);
+    GU_Detail grouped;
+    if (copyWithGroupAttribs(grouped, *gdp, options))
+        gdp = &grouped;
+    GU_Detail transformed;
+    gdp = &hdkrs::export_detail(
+            transformed, *gdp, coordTransform(), vertexOrder());
+
     try {
-        auto buf = objio::polymesh_to_obj_buffer(static_cast<const GU_Detail&>(*detail));
+        auto buf = objio::polymesh_to_obj_buffer_with_options(*gdp, options);
         os.write(reinterpret_cast<const char *>(buf.data()), buf.size());
         return GA_Detail::IOStatus(true);
     } catch(...) {}
 
     // If no polygons are found we try to save the pointcloud
     try {
-        auto buf = objio::pointcloud_to_obj_buffer(static_cast<const GU_Detail&>(*detail));
+        auto buf = objio::pointcloud_to_obj_buffer_with_options(*gdp, options);
         os.write(reinterpret_cast<const char *>(buf.data()), buf.size());
         return GA_Detail::IOStatus(true);
     } catch(...) {}
@@ -84,12 +326,41 @@ GEO_ObjIO::fileSave(const GEO_Detail *detail, std::ostream &os)
     return GA_Detail::IOStatus(false);
 }
 
+GA_Detail::IOStatus
+GEO_ObjIO::fileSaveToFile(const GEO_Detail *detail, const char *fname)
+{
+    myMtlLib.clear();
+    if (detail && fname) {
+        // Write a companion material library for materials assigned to polygons.
+        const GU_Detail &gdp = static_cast<const GU_Detail&>(*detail);
+        auto materials = objio::obj_materials(gdp, MATERIAL_ATTRIB);
+        if (!materials.empty()) {
+            auto path = mtlPath(fname);
+            auto buf = objio::mtl_buffer(materials, getMaterialParams(gdp, materials));
+            std::ofstream os(path, std::ios::binary);
+            os.write(reinterpret_cast<const char *>(buf.data()), buf.size());
+            if (os.good())
+                myMtlLib = UT_String(path.c_str()).fileName();
+        }
+    }
+
+    GA_Detail::IOStatus status(false);
+    if (!fname || !hdkrs::is_compressed_path(fname))
+        status = GEO_IOTranslator::fileSaveToFile(detail, fname);
+    else
+        status = hdkrs::save_compressed(fname, [&](std::ostream &os) { return fileSave(detail, os); });
+    myMtlLib.clear();
+    return status;
+}
+
 void
 newGeometryIO(void *)
 {
     GU_Detail::registerIOTranslator(new GEO_ObjIO());
     UT_ExtensionList *geoextension;
     geoextension = UTgetGeoExtensions();
-    if (!geoextension->findExtension("obj"))
-        geoextension->addExtension("obj");
+    for (auto ext : {"obj", "obj.gz", "obj.zst"}) {
+        if (!geoextension->findExtension(ext))
+            geoextension->addExtension(ext);
+    }
 }
@@ -1,8 +1,14 @@
+use std::io::Read;
 use std::pin::Pin;
 
+use meshx::attrib::Attrib;
 use meshx::io::obj::*;
-use meshx::io::MeshExtractor;
 use meshx::mesh::topology::*;
+use meshx::mesh::{PointCloud, PolyMesh, VertexPositions};
+
+pub mod mtl;
+pub mod polymesh;
+pub mod scan;
 
 #[cxx::bridge(namespace = "objio")]
 mod ffi {
@@ -12,33 +18,498 @@ mod ffi {
         include!("rust/cxx.h");
         type GU_Detail = hdkrs::ffi::GU_Detail;
     }
+    #[namespace = "hdkrs"]
+    extern "C++" {
+        type IStreamReader = hdkrs::ffi::IStreamReader;
+        type VertexOrder = hdkrs::ffi::VertexOrder;
+        type CookResult = hdkrs::ffi::CookResult;
+    }
+
+    /// Options controlling how obj files are loaded.
+    #[derive(Clone, Debug, Default)]
+    pub struct LoadOptions {
+        /// Name of the primitive string attribute storing materials assigned with `usemtl`.
+        /// Materials are ignored if empty.
+        pub material_attrib: String,
+        /// Name of the primitive string attribute storing objects given by `o`.
+        /// Objects are ignored if empty.
+        pub object_attrib: String,
+        /// Prefix of the primitive integer attributes marking the faces in each group given by
+        /// `g`, named by `group_attrib_name`. Groups are ignored if empty.
+        pub group_attrib_prefix: String,
+        /// Name of the primitive integer attribute storing smoothing groups given by `s`.
+        /// Smoothing groups are ignored if empty.
+        pub smoothing_attrib: String,
+        /// Load colors given by the non-standard `v x y z r g b` extension into the `Cd` point
+        /// attribute.
+        pub vertex_colors: bool,
+        /// Path of the file being loaded, used to find material libraries referenced by
+        /// `mtllib`. Material libraries are not loaded if empty.
+        pub path: String,
+    }
+
+    /// Options controlling how obj files are written.
+    #[derive(Clone, Debug, Default)]
+    pub struct SaveOptions {
+        /// Name of the primitive string attribute written as `usemtl` statements.
+        /// Materials are not written if empty.
+        pub material_attrib: String,
+        /// Name of the primitive string attribute written as `o` statements.
+        /// Objects are not written if empty.
+        pub object_attrib: String,
+        /// Prefix of the primitive integer attributes marking faces written in `g` groups.
+        /// Groups are not written if empty.
+        pub group_attrib_prefix: String,
+        /// Names of the groups written with `g` statements, each marked by the attribute named
+        /// by `group_attrib_name`.
+        pub groups: Vec<String>,
+        /// Name of the primitive integer attribute written as `s` statements.
+        /// Smoothing groups are not written if empty.
+        pub smoothing_attrib: String,
+        /// Write the `Cd` point attribute as colors using the non-standard `v x y z r g b`
+        /// extension. Disable this for consumers expecting strictly standard obj files.
+        pub vertex_colors: bool,
+        /// File name of the material library referenced with `mtllib`, if not empty.
+        pub mtllib: String,
+    }
+
+    /// A single statement of a material definition in a material library.
+    #[derive(Clone, Debug, Default, PartialEq)]
+    pub struct MtlParam {
+        /// Name of the material given by `newmtl`.
+        pub material: String,
+        /// Statement keyword, such as `Kd` or `map_Kd`.
+        pub key: String,
+        /// Numeric arguments, empty if any of the arguments are not numeric.
+        pub values: Vec<f64>,
+        /// Unparsed arguments of non-numeric statements.
+        pub text: String,
+    }
+
+    /// Additional data loaded from an obj file alongside the meshes.
+    #[derive(Clone, Debug, Default)]
+    pub struct ObjInfo {
+        /// Parameters of materials defined in material libraries referenced by the file.
+        pub materials: Vec<MtlParam>,
+        /// Names of groups stored in attributes with the group attribute prefix.
+        pub groups: Vec<String>,
+    }
+
     extern "Rust" {
+        fn default_vertex_order() -> VertexOrder;
+
         fn polymesh_to_obj_buffer(detail: &GU_Detail) -> Result<Vec<u8>>;
+        fn polymesh_to_obj_buffer_with_options(
+            detail: &GU_Detail,
+            options: &SaveOptions,
+        ) -> Result<Vec<u8>>;
         fn pointcloud_to_obj_buffer(detail: &GU_Detail) -> Result<Vec<u8>>;
+        fn pointcloud_to_obj_buffer_with_options(
+            detail: &GU_Detail,
+            options: &SaveOptions,
+        ) -> Result<Vec<u8>>;
+        fn obj_materials(detail: &GU_Detail, material_attrib: &str) -> Vec<String>;
+        fn group_attrib_name(prefix: &str, group: &str) -> String;
+        fn mtl_buffer(materials: &[String], params: &[MtlParam]) -> Vec<u8>;
 
         fn add_obj_mesh(detail: Pin<&mut GU_Detail>, data: &[u8]);
+        fn add_obj_mesh_from_stream(
+            detail: Pin<&mut GU_Detail>,
+            reader: Pin<&mut IStreamReader>,
+        ) -> bool;
+        fn add_obj_mesh_from_stream_with_options(
+            detail: Pin<&mut GU_Detail>,
+            reader: Pin<&mut IStreamReader>,
+            options: &LoadOptions,
+            info: &mut ObjInfo,
+        ) -> CookResult;
     }
 }
 
-use hdkrs::ffi::GU_Detail;
+pub use ffi::{LoadOptions, MtlParam, ObjInfo, SaveOptions};
+use hdkrs::ffi::{
+    AttribLocation, CookResult, CookResultTag, GU_Detail, IStreamReader, VertexOrder,
+};
+use hdkrs::interop;
 
-/// Extract a `PolyMesh` from the given detail and write it in Obj format into a
-/// `ByteBuffer`.
-pub fn polymesh_to_obj_buffer(detail: &GU_Detail) -> Result<Vec<u8>, cxx::Exception> {
-    hdkrs::ffi::build_polymesh(detail).map(|mesh| {
-        convert_polymesh_to_obj_format(&mesh.0)
-            .map(write_obj)
-            .unwrap_or_else(|_| Default::default())
-    })
+/// Name of the primitive attribute storing material assignments by default.
+///
+/// This matches the attribute used by Houdini for material assignments.
+pub const MATERIAL_ATTRIB: &str = "shop_materialpath";
+
+/// Name of the primitive attribute storing object names by default.
+pub const OBJECT_ATTRIB: &str = "name";
+
+/// Prefix of the primitive attributes marking group membership by default.
+pub const GROUP_ATTRIB_PREFIX: &str = "group_";
+
+/// Name of the primitive attribute storing smoothing groups by default.
+pub const SMOOTHING_ATTRIB: &str = "smoothing_group";
+
+/// Name of the point attribute storing vertex colors.
+pub const COLOR_ATTRIB: &str = "Cd";
+
+/// Vertex order of polygons and tetrahedra in OBJ files.
+pub const VERTEX_ORDER: VertexOrder = VertexOrder::STANDARD;
+
+/// Vertex order of OBJ files used when no convention is given in the environment.
+pub fn default_vertex_order() -> VertexOrder {
+    VERTEX_ORDER
+}
+
+impl LoadOptions {
+    /// Load options using the default attributes with vertex colors enabled, without loading
+    /// material libraries.
+    pub fn new() -> Self {
+        LoadOptions {
+            material_attrib: MATERIAL_ATTRIB.to_string(),
+            object_attrib: OBJECT_ATTRIB.to_string(),
+            group_attrib_prefix: GROUP_ATTRIB_PREFIX.to_string(),
+            smoothing_attrib: SMOOTHING_ATTRIB.to_string(),
+            vertex_colors: true,
+            path: String::new(),
+        }
+    }
+}
+
+impl SaveOptions {
+    /// Save options using the default attributes with vertex colors enabled, without
+    /// referencing a material library.
+    pub fn new() -> Self {
+        SaveOptions {
+            material_attrib: MATERIAL_ATTRIB.to_string(),
+            object_attrib: OBJECT_ATTRIB.to_string(),
+            group_attrib_prefix: GROUP_ATTRIB_PREFIX.to_string(),
+            groups: Vec::new(),
+            smoothing_attrib: SMOOTHING_ATTRIB.to_string(),
+            vertex_colors: true,
+            mtllib: String::new(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Error(String);
+impl std::error::Error for Error {}
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Error: {}", &self.0)
+    }
+}
+
+/// Write the given polylines as Obj line elements.
+///
+/// If `write_positions` is false, the positions are assumed to be already written in the same
+/// order.
+fn write_obj_lines<T: meshx::Real + std::fmt::Display>(
+    buf: &mut Vec<u8>,
+    polylines: &PolyMesh<T>,
+    write_positions: bool,
+) {
+    use std::io::Write;
+    if write_positions {
+        for p in polylines.vertex_positions() {
+            writeln!(buf, "v {} {} {}", p[0], p[1], p[2]).unwrap();
+        }
+    }
+    for curve in polylines.face_iter() {
+        write!(buf, "l").unwrap();
+        for &idx in curve.iter() {
+            write!(buf, " {}", idx + 1).unwrap();
+        }
+        writeln!(buf).unwrap();
+    }
+}
+
+/// Get the value of the given face string attribute for each face of the given mesh.
+///
+/// Faces without a value are given an empty string.
+fn face_strings<'a, T: meshx::Real>(mesh: &'a PolyMesh<T>, name: &str) -> Option<Vec<&'a str>> {
+    let attrib = mesh.attrib::<FaceIndex>(name).ok()?;
+    let data = attrib
+        .data
+        .indirect_data()
+        .and_then(|d| d.as_rc_slice::<String>())
+        .ok()?;
+    Some(data.iter().map(|s| s.as_str()).collect())
+}
+
+/// Get the `s` statement argument for each face of the given mesh from the given integer face
+/// attribute.
+fn face_smoothing<T: meshx::Real>(mesh: &PolyMesh<T>, name: &str) -> Option<Vec<String>> {
+    let data = mesh
+        .attrib::<FaceIndex>(name)
+        .ok()?
+        .as_slice::<i32>()
+        .ok()?;
+    Some(
+        data.iter()
+            .map(|&group| match group {
+                0 => String::from("off"),
+                _ => group.to_string(),
+            })
+            .collect(),
+    )
+}
+
+/// Get the colors of each vertex from the given point attribute.
+fn vertex_colors(attrib: Option<&meshx::attrib::Attribute<VertexIndex>>) -> Option<&[[f32; 3]]> {
+    attrib?.as_slice::<[f32; 3]>().ok()
+}
+
+/// Append the given colors to vertex positions (`v`) in obj data written by `write_obj` using
+/// the non-standard `v x y z r g b` extension.
+fn append_vertex_colors(buf: Vec<u8>, colors: &[[f32; 3]]) -> Vec<u8> {
+    use std::io::Write;
+    let mut out = Vec::with_capacity(buf.len() + colors.len() * 24);
+    let mut colors = colors.iter();
+    for line in buf.split_inclusive(|&b| b == b'\n') {
+        if line.starts_with(b"v ") {
+            if let Some(c) = colors.next() {
+                let end = line.len() - line.ends_with(b"\n") as usize;
+                out.extend_from_slice(&line[..end]);
+                write!(out, " {} {} {}", c[0], c[1], c[2]).unwrap();
+                out.extend_from_slice(&line[end..]);
+                continue;
+            }
+        }
+        out.extend_from_slice(line);
+    }
+    out
+}
+
+/// Name of the primitive integer attribute marking faces in the given group.
+///
+/// The prefix and group name are joined into a valid variable name, as done by
+/// `UT_String::forceValidVariableName` in Houdini: characters other than ASCII letters, digits
+/// and underscores are replaced by underscores, and a leading digit is prefixed by an
+/// underscore.
+pub fn group_attrib_name(prefix: &str, group: &str) -> String {
+    let mut name = String::with_capacity(prefix.len() + group.len() + 1);
+    for c in prefix.chars().chain(group.chars()) {
+        if name.is_empty() && c.is_ascii_digit() {
+            name.push('_');
+        }
+        let valid = c.is_ascii_alphanumeric() || c == '_';
+        name.push(if valid { c } else { '_' });
+    }
+    name
+}
+
+/// Get the space separated names of the given groups containing each face of the given mesh.
+///
+/// Membership in each group is given by the integer face attribute named by
+/// `group_attrib_name`. Groups without such an attribute are skipped.
+fn face_groups<T: meshx::Real>(
+    mesh: &PolyMesh<T>,
+    prefix: &str,
+    groups: &[String],
+) -> Option<Vec<String>> {
+    if prefix.is_empty() {
+        return None;
+    }
+    let mut groups: Vec<(&str, &[i32])> = groups
+        .iter()
+        .filter(|group| !group.is_empty())
+        .filter_map(|group| {
+            let name = group_attrib_name(prefix, group);
+            let data = mesh
+                .attrib::<FaceIndex>(&name)
+                .ok()?
+                .as_slice::<i32>()
+                .ok()?;
+            Some((group.as_str(), data))
+        })
+        .collect();
+    if groups.is_empty() {
+        return None;
+    }
+    groups.sort_by_key(|&(group, _)| group);
+    let face_groups = (0..mesh.num_faces())
+        .map(|face| {
+            let names: Vec<&str> = groups
+                .iter()
+                .filter(|(_, data)| data[face] != 0)
+                .map(|&(group, _)| group)
+                .collect();
+            names.join(" ")
+        })
+        .collect();
+    Some(face_groups)
+}
+
+/// Insert a header and statements describing faces, such as `usemtl`, into obj data written by
+/// `write_obj`.
+///
+/// Each statement is given by its keyword and its arguments for each face in the order faces
+/// are written. A statement is inserted before a face whenever its arguments change.
+fn insert_face_statements(buf: Vec<u8>, header: &str, statements: &[(&str, Vec<&str>)]) -> Vec<u8> {
+    use std::io::Write;
+    let mut out = Vec::with_capacity(buf.len());
+    out.extend_from_slice(header.as_bytes());
+    let mut current = vec![""; statements.len()];
+    let mut face = 0;
+    for line in buf.split_inclusive(|&b| b == b'\n') {
+        if line.starts_with(b"f ") {
+            for ((keyword, args), current) in statements.iter().zip(current.iter_mut()) {
+                let arg = args.get(face).copied().unwrap_or("");
+                if arg != *current {
+                    // A statement without arguments clears the previous assignment.
+                    if arg.is_empty() {
+                        writeln!(out, "{}", keyword).unwrap();
+                    } else {
+                        writeln!(out, "{} {}", keyword, arg).unwrap();
+                    }
+                    *current = arg;
+                }
+            }
+            face += 1;
+        }
+        out.extend_from_slice(line);
+    }
+    out
+}
+
+/// Extract polygons and open polylines from the given detail and write them in Obj format into
+/// a `ByteBuffer`.
+///
+/// Polylines are written as line elements (`l`). Materials are written from the default
+/// material attribute.
+pub fn polymesh_to_obj_buffer(detail: &GU_Detail) -> Result<Vec<u8>, Error> {
+    polymesh_to_obj_buffer_with_options(detail, &SaveOptions::new())
+}
+
+/// Extract polygons and open polylines from the given detail and write them in Obj format into
+/// a `ByteBuffer` as in `polymesh_to_obj_buffer`.
+///
+/// Faces are written in `o` objects, `g` groups, `s` smoothing groups and with `usemtl`
+/// materials built from the primitive attributes given in `options`. Point colors are written
+/// if enabled in `options`.
+pub fn polymesh_to_obj_buffer_with_options(
+    detail: &GU_Detail,
+    options: &SaveOptions,
+) -> Result<Vec<u8>, Error> {
+    if hdkrs::ffi::has_f64_positions(detail) {
+        let polymesh = hdkrs::ffi::build_polymesh(detail).ok();
+        let polylines = hdkrs::ffi::build_polylines(detail).ok();
+        write_polygons_and_lines(
+            polymesh.as_ref().map(|mesh| &mesh.0),
+            polylines.as_ref().map(|mesh| &mesh.0),
+            options,
+        )
+    } else {
+        let polymesh = hdkrs::ffi::build_polymesh_f32(detail).ok();
+        let polylines = hdkrs::ffi::build_polylines_f32(detail).ok();
+        write_polygons_and_lines(
+            polymesh.as_ref().map(|mesh| &mesh.0),
+            polylines.as_ref().map(|mesh| &mesh.0),
+            options,
+        )
+    }
+}
+
+/// Write the given polygons and polylines in Obj format as in
+/// `polymesh_to_obj_buffer_with_options`.
+fn write_polygons_and_lines<T: meshx::Real + std::fmt::Display>(
+    polymesh: Option<&PolyMesh<T>>,
+    polylines: Option<&PolyMesh<T>>,
+    options: &SaveOptions,
+) -> Result<Vec<u8>, Error> {
+    let mut buf = match polymesh {
+        Some(mesh) => {
+            let buf = polymesh::write_polymesh(mesh);
+            let buf = match vertex_colors(mesh.attrib::<VertexIndex>(COLOR_ATTRIB).ok()) {
+                Some(colors) if options.vertex_colors => append_vertex_colors(buf, colors),
+                _ => buf,
+            };
+            let groups = face_groups(mesh, &options.group_attrib_prefix, &options.groups);
+            let smoothing = face_smoothing(mesh, &options.smoothing_attrib);
+            let mut statements = Vec::new();
+            if let Some(objects) = face_strings(mesh, &options.object_attrib) {
+                statements.push(("o", objects));
+            }
+            if let Some(groups) = &groups {
+                statements.push(("g", groups.iter().map(String::as_str).collect()));
+            }
+            if let Some(smoothing) = &smoothing {
+                statements.push(("s", smoothing.iter().map(String::as_str).collect()));
+            }
+            if let Some(materials) = face_strings(mesh, &options.material_attrib) {
+                statements.push(("usemtl", materials));
+            }
+            let header = if options.mtllib.is_empty() {
+                String::new()
+            } else {
+                format!("mtllib {}\n", options.mtllib)
+            };
+            if statements.is_empty() && header.is_empty() {
+                buf
+            } else {
+                insert_face_statements(buf, &header, &statements)
+            }
+        }
+        None => Vec::new(),
+    };
+    match polylines {
+        // Both meshes are built from all points in the detail, so positions are shared.
+        Some(polylines) => write_obj_lines(&mut buf, polylines, polymesh.is_none()),
+        None if polymesh.is_none() => {
+            return Err(Error(String::from("No polygons or polylines found")))
+        }
+        None => {}
+    }
+    Ok(buf)
 }
 /// Extract a `PointCloud` from the given detail and write it in Obj format into a
 /// `ByteBuffer`.
 pub fn pointcloud_to_obj_buffer(detail: &GU_Detail) -> Result<Vec<u8>, cxx::Exception> {
-    hdkrs::ffi::build_pointcloud(detail).map(|mesh| {
-        convert_pointcloud_to_obj_format(&mesh.0)
-            .map(write_obj)
-            .unwrap_or_else(|_| Default::default())
-    })
+    pointcloud_to_obj_buffer_with_options(detail, &SaveOptions::new())
+}
+
+/// Extract a `PointCloud` from the given detail and write it in Obj format into a
+/// `ByteBuffer`, writing point colors if enabled in `options`.
+pub fn pointcloud_to_obj_buffer_with_options(
+    detail: &GU_Detail,
+    options: &SaveOptions,
+) -> Result<Vec<u8>, cxx::Exception> {
+    if hdkrs::ffi::has_f64_positions(detail) {
+        hdkrs::ffi::build_pointcloud(detail).map(|mesh| write_pointcloud(&mesh.0, options))
+    } else {
+        hdkrs::ffi::build_pointcloud_f32(detail).map(|mesh| write_pointcloud(&mesh.0, options))
+    }
+}
+
+/// Write the given point cloud in Obj format as in `pointcloud_to_obj_buffer_with_options`.
+fn write_pointcloud<T: meshx::Real>(mesh: &PointCloud<T>, options: &SaveOptions) -> Vec<u8> {
+    let buf = convert_pointcloud_to_obj_format(mesh)
+        .map(write_obj)
+        .unwrap_or_else(|_| Default::default());
+    match vertex_colors(mesh.attrib::<VertexIndex>(COLOR_ATTRIB).ok()) {
+        Some(colors) if options.vertex_colors => append_vertex_colors(buf, colors),
+        _ => buf,
+    }
+}
+
+/// Get the unique materials assigned to polygons in the given detail by the given primitive
+/// string attribute, in order of first use.
+pub fn obj_materials(detail: &GU_Detail, material_attrib: &str) -> Vec<String> {
+    let mut materials = Vec::new();
+    if let Ok(mesh) = hdkrs::ffi::build_polymesh_f32(detail) {
+        for material in face_strings(&mesh.0, material_attrib)
+            .unwrap_or_default()
+            .into_iter()
+        {
+            if !material.is_empty() && !materials.iter().any(|m| m == material) {
+                materials.push(material.to_string());
+            }
+        }
+    }
+    materials
+}
+
+/// Write a material library defining the given materials with the given parameters.
+pub fn mtl_buffer(materials: &[String], params: &[MtlParam]) -> Vec<u8> {
+    mtl::write_mtl(materials, params)
 }
 
 // Helper for writing obj data to a byte buffer.
@@ -49,22 +520,400 @@ fn write_obj(obj: ObjData) -> Vec<u8> {
     vec_data
 }
 
+/// Build a polyline mesh from the given line elements over the given positions.
+///
+/// Only positions referenced by the lines are kept.
+fn build_polylines(positions: &[[f32; 3]], lines: &[Vec<usize>]) -> Box<hdkrs::PolylineMeshF32> {
+    let mut new_index = vec![None; positions.len()];
+    let mut coords = Vec::new();
+    let mut indices = Vec::new();
+    for line in lines.iter() {
+        indices.push(line.len());
+        for &pt in line.iter() {
+            let idx = *new_index[pt].get_or_insert_with(|| {
+                coords.extend_from_slice(&positions[pt]);
+                coords.len() / 3 - 1
+            });
+            indices.push(idx);
+        }
+    }
+    hdkrs::make_polylines_f32(&coords, &indices)
+}
+
+/// Add the materials assigned by `usemtl` statements to the given mesh as a face string
+/// attribute with the given name.
+fn add_material_attrib(
+    mesh: &mut hdkrs::PolyMeshF32,
+    statements: &scan::Statements,
+    name: &str,
+    warnings: &mut Vec<String>,
+) {
+    if name.is_empty() || statements.materials.is_empty() {
+        return;
+    }
+    if statements.face_materials.len() != mesh.0.num_faces() {
+        warnings.push(format!(
+            "Failed to match materials to faces, skipped \"{}\" attribute",
+            name
+        ));
+        return;
+    }
+    let materials: Vec<&str> = statements.materials.iter().map(String::as_str).collect();
+    mesh.add_attrib_str(
+        AttribLocation::FACE,
+        name,
+        1,
+        &materials,
+        &statements.face_materials,
+    );
+}
+
+/// Add the objects given by `o` statements to the given mesh as a face string attribute with
+/// the given name.
+fn add_object_attrib(
+    mesh: &mut hdkrs::PolyMeshF32,
+    statements: &scan::Statements,
+    name: &str,
+    warnings: &mut Vec<String>,
+) {
+    if name.is_empty() || statements.objects.is_empty() {
+        return;
+    }
+    if statements.face_objects.len() != mesh.0.num_faces() {
+        warnings.push(format!(
+            "Failed to match objects to faces, skipped \"{}\" attribute",
+            name
+        ));
+        return;
+    }
+    let objects: Vec<&str> = statements.objects.iter().map(String::as_str).collect();
+    mesh.add_attrib_str(
+        AttribLocation::FACE,
+        name,
+        1,
+        &objects,
+        &statements.face_objects,
+    );
+}
+
+/// Add the groups given by `g` statements to the given mesh as integer face attributes named
+/// by `group_attrib_name`.
+///
+/// Returns the names of the added groups.
+fn add_group_attribs(
+    mesh: &mut hdkrs::PolyMeshF32,
+    statements: &scan::Statements,
+    prefix: &str,
+    warnings: &mut Vec<String>,
+) -> Vec<String> {
+    if prefix.is_empty() || statements.groups.is_empty() {
+        return Vec::new();
+    }
+    if statements.face_groups.len() != mesh.0.num_faces() {
+        warnings.push(String::from("Failed to match groups to faces, skipped groups"));
+        return Vec::new();
+    }
+    for (group, name) in statements.groups.iter().enumerate() {
+        let data: Vec<i32> = (0..statements.face_groups.len())
+            .map(|face| statements.face_in_group(face, group) as i32)
+            .collect();
+        mesh.add_attrib_i32(
+            AttribLocation::FACE,
+            &group_attrib_name(prefix, name),
+            1,
+            &data,
+        );
+    }
+    statements.groups.clone()
+}
+
+/// Add the smoothing groups given by `s` statements to the given mesh as an integer face
+/// attribute with the given name.
+fn add_smoothing_attrib(
+    mesh: &mut hdkrs::PolyMeshF32,
+    statements: &scan::Statements,
+    name: &str,
+    warnings: &mut Vec<String>,
+) {
+    if name.is_empty() || !statements.has_smoothing {
+        return;
+    }
+    if statements.face_smoothing.len() != mesh.0.num_faces() {
+        warnings.push(format!(
+            "Failed to match smoothing groups to faces, skipped \"{}\" attribute",
+            name
+        ));
+        return;
+    }
+    mesh.add_attrib_i32(AttribLocation::FACE, name, 1, &statements.face_smoothing);
+}
+
+/// Check that vertex colors were found for the given number of vertices.
+fn has_vertex_colors(
+    statements: &scan::Statements,
+    num_vertices: usize,
+    warnings: &mut Vec<String>,
+) -> bool {
+    if statements.vertex_colors.is_empty() {
+        return false;
+    }
+    if statements.vertex_colors.len() != 3 * num_vertices {
+        warnings.push(String::from("Failed to match vertex colors to points, skipped colors"));
+        return false;
+    }
+    true
+}
+
+/// Build a point cloud from all positions in the given obj data.
+///
+/// Normals (`vn`) and texture coordinates (`vt`) are loaded into the `N` and `uv` point
+/// attributes if there is one for each position, as written by photogrammetry tools.
+fn build_pointcloud(
+    obj_data: &ObjData,
+    warnings: &mut Vec<String>,
+) -> Option<hdkrs::PointCloudF32> {
+    let num_points = obj_data.position.len();
+    if num_points == 0 {
+        return None;
+    }
+    let coords: Vec<f32> = obj_data.position.iter().flatten().copied().collect();
+    let mut mesh = *hdkrs::make_pointcloud_f32(&coords);
+    if obj_data.normal.len() == num_points {
+        let normals: Vec<f32> = obj_data.normal.iter().flatten().copied().collect();
+        mesh.add_attrib_f32(AttribLocation::VERTEX, polymesh::NORMAL_ATTRIB, 3, &normals);
+    } else if !obj_data.normal.is_empty() {
+        warnings.push(format!(
+            "Skipped {} normals not matching {} points",
+            obj_data.normal.len(),
+            num_points
+        ));
+    }
+    if obj_data.texture.len() == num_points {
+        let uvs: Vec<f32> = obj_data
+            .texture
+            .iter()
+            .flat_map(|uv| vec![uv[0], uv[1], 0.0])
+            .collect();
+        mesh.add_attrib_f32(AttribLocation::VERTEX, polymesh::UV_ATTRIB, 3, &uvs);
+    } else if !obj_data.texture.is_empty() {
+        warnings.push(format!(
+            "Skipped {} texture coordinates not matching {} points",
+            obj_data.texture.len(),
+            num_points
+        ));
+    }
+    Some(mesh)
+}
+
+/// Meshes and additional data loaded from an obj file.
+#[derive(Debug, Default)]
+pub struct ObjMeshes {
+    pub meshes: Vec<hdkrs::Mesh>,
+    pub info: ObjInfo,
+    /// Messages describing elements and attributes that were skipped.
+    pub warnings: Vec<String>,
+}
+
+/// Parse a given byte array into meshes assuming obj format.
+///
+/// Faces produce a `PolyMesh` and line elements produce a `PolylineMesh`. If neither are found,
+/// the vertices are loaded as a `PointCloud` with normals and texture coordinates. Materials
+/// assigned to faces are stored in the default material attribute.
+pub fn parse_obj_meshes(data: &[u8]) -> Vec<hdkrs::Mesh> {
+    parse_obj_meshes_from_reader(data).unwrap_or_default()
+}
+
+/// Parse obj data from the given reader into meshes as in `parse_obj_meshes`.
+///
+/// The data is parsed as it is read, so the file contents are never held in memory at once.
+/// Gzip and zstd compressed data is decompressed transparently.
+pub fn parse_obj_meshes_from_reader(reader: impl Read) -> Result<Vec<hdkrs::Mesh>, Error> {
+    load_obj_from_reader(reader, &LoadOptions::new()).map(|obj| obj.meshes)
+}
+
+/// Parse obj data from the given reader into meshes as in `parse_obj_meshes_from_reader`,
+/// loading materials, objects and groups as specified by the given options.
+pub fn load_obj_from_reader(reader: impl Read, options: &LoadOptions) -> Result<ObjMeshes, Error> {
+    let reader = hdkrs::stream::decompress(reader).map_err(|e| Error(e.to_string()))?;
+    let mut reader = scan::ScanReader::new(reader);
+    let obj_data = ObjData::load_buf_with_config(&mut reader, LoadConfig { strict: false })
+        .map_err(|e| Error(e.to_string()))?;
+    let statements = reader.finish();
+
+    let mut meshes = Vec::new();
+    let mut info = ObjInfo::default();
+    let mut warnings = Vec::new();
+    for (keyword, count) in statements.skipped.iter() {
+        let kind = if keyword == "l" {
+            "invalid"
+        } else {
+            "unsupported"
+        };
+        warnings.push(format!("Skipped {} {} `{}` elements", count, kind, keyword));
+    }
+
+    if let Some(mut mesh) = polymesh::build_polymesh(&obj_data) {
+        let w = &mut warnings;
+        add_material_attrib(&mut mesh, &statements, &options.material_attrib, w);
+        add_object_attrib(&mut mesh, &statements, &options.object_attrib, w);
+        info.groups = add_group_attribs(&mut mesh, &statements, &options.group_attrib_prefix, w);
+        add_smoothing_attrib(&mut mesh, &statements, &options.smoothing_attrib, w);
+        if options.vertex_colors && has_vertex_colors(&statements, mesh.0.num_vertices(), w) {
+            let colors = &statements.vertex_colors;
+            mesh.add_attrib_f32(AttribLocation::VERTEX, COLOR_ATTRIB, 3, colors);
+        }
+        meshes.push(mesh.into());
+    }
+    if !statements.lines.is_empty() {
+        meshes.push((*build_polylines(&obj_data.position, &statements.lines)).into());
+    }
+    if meshes.is_empty() {
+        if let Some(mut mesh) = build_pointcloud(&obj_data, &mut warnings) {
+            let w = &mut warnings;
+            if options.vertex_colors && has_vertex_colors(&statements, mesh.0.num_vertices(), w) {
+                let colors = &statements.vertex_colors;
+                mesh.add_attrib_f32(AttribLocation::VERTEX, COLOR_ATTRIB, 3, colors);
+            }
+            meshes.push(mesh.into());
+        }
+    }
+
+    if !options.path.is_empty() && !options.material_attrib.is_empty() {
+        info.materials =
+            mtl::load_mtl_libs(&options.path, &statements.material_libs, &mut warnings);
+    }
+    Ok(ObjMeshes {
+        meshes,
+        info,
+        warnings,
+    })
+}
+
 /// Parse a given byte array into a PolyMesh assuming obj format.
 pub fn parse_obj_mesh(data: &[u8]) -> Box<hdkrs::Mesh> {
-    if let Ok(obj_data) = ObjData::load_buf_with_config(data, LoadConfig { strict: false }) {
-        if let Ok(mesh) = obj_data.extract_polymesh() {
-            if mesh.num_faces() > 0 {
-                return Box::new(mesh.into());
-            }
+    let data = match hdkrs::stream::decompress_bytes(data) {
+        Ok(data) => data,
+        Err(_) => return Box::new(hdkrs::Mesh::None),
+    };
+    if let Ok(obj_data) = ObjData::load_buf_with_config(&*data, LoadConfig { strict: false }) {
+        if let Some(mesh) = polymesh::build_polymesh(&obj_data) {
+            return Box::new(mesh.into());
         }
-        if let Ok(mesh) = obj_data.extract_pointcloud() {
+        if let Some(mesh) = build_pointcloud(&obj_data, &mut Vec::new()) {
             return Box::new(mesh.into());
         }
     }
     Box::new(hdkrs::Mesh::None)
 }
 
-/// Parse a given byte array into a PolyMesh or a PointCloud and add it to the given detail.
-pub fn add_obj_mesh(detail: Pin<&mut GU_Detail>, data: &[u8]) {
-    parse_obj_mesh(data).add_to_detail(detail);
+/// Parse a given byte array into polygons and polylines, or a PointCloud, and add them to the
+/// given detail.
+pub fn add_obj_mesh(mut detail: Pin<&mut GU_Detail>, data: &[u8]) {
+    for mesh in parse_obj_meshes(data) {
+        mesh.add_to_detail(detail.as_mut());
+    }
+}
+
+/// Parse obj data read incrementally from the given stream and add the resulting meshes to the
+/// given detail.
+///
+/// Returns `false` if the stream could not be read or parsed, including when the user
+/// interrupts the load.
+pub fn add_obj_mesh_from_stream(
+    detail: Pin<&mut GU_Detail>,
+    reader: Pin<&mut IStreamReader>,
+) -> bool {
+    let result = add_obj_mesh_from_stream_with_options(
+        detail,
+        reader,
+        &LoadOptions::new(),
+        &mut ObjInfo::default(),
+    );
+    result.tag != CookResultTag::ERROR
+}
+
+/// Parse obj data read incrementally from the given stream and add the resulting meshes to the
+/// given detail as in `add_obj_mesh_from_stream`.
+///
+/// Parameters of materials found in referenced material libraries and the names of loaded
+/// groups are written to `info`. Skipped elements and attributes are reported as a warning in
+/// the returned cook result.
+pub fn add_obj_mesh_from_stream_with_options(
+    mut detail: Pin<&mut GU_Detail>,
+    reader: Pin<&mut IStreamReader>,
+    options: &LoadOptions,
+    info: &mut ObjInfo,
+) -> CookResult {
+    match load_obj_from_reader(hdkrs::stream::StreamReader::new(reader), options) {
+        Ok(obj) => {
+            for mesh in obj.meshes {
+                mesh.add_to_detail(detail.as_mut());
+            }
+            *info = obj.info;
+            if obj.warnings.is_empty() {
+                interop::CookResult::Success(String::new())
+            } else {
+                interop::CookResult::Warning(obj.warnings.join("\n"))
+            }
+        }
+        Err(err) => interop::CookResult::Error(format!("Failed to load obj: {}", err)),
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vertex_colors_are_appended_to_positions() {
+        let buf = b"# comment\nv 0 0 0\nvn 0 0 1\nv 1 0 0\nf 1 2\nv 0 1 0".to_vec();
+        let colors = [[1.0, 0.0, 0.0], [0.0, 0.5, 0.0]];
+        assert_eq!(
+            String::from_utf8(append_vertex_colors(buf, &colors)).unwrap(),
+            "# comment\nv 0 0 0 1 0 0\nvn 0 0 1\nv 1 0 0 0 0.5 0\nf 1 2\nv 0 1 0"
+        );
+
+        // The last position is colored even without a trailing newline.
+        let buf = b"v 0 0 0\nv 1 0 0".to_vec();
+        assert_eq!(
+            String::from_utf8(append_vertex_colors(buf, &colors)).unwrap(),
+            "v 0 0 0 1 0 0\nv 1 0 0 0 0.5 0"
+        );
+    }
+
+    #[test]
+    fn group_attrib_names_are_valid_variable_names() {
+        assert_eq!(group_attrib_name("group_", "wheel"), "group_wheel");
+        assert_eq!(
+            group_attrib_name("group_", "front-left.001"),
+            "group_front_left_001"
+        );
+        assert_eq!(group_attrib_name("group_", "2nd"), "group_2nd");
+        assert_eq!(group_attrib_name("", "2nd"), "_2nd");
+        assert_eq!(group_attrib_name("", "rad\u{e9}"), "rad_");
+    }
+
+    #[test]
+    fn groups_are_loaded_into_sanitized_attribs() {
+        let data = b"v 0 0 0\nv 1 0 0\nv 0 1 0\n\
+            g front-left.001\nf 1 2 3\n\
+            g 2nd front-left.001\nf 1 2 3\n";
+        let obj = load_obj_from_reader(&data[..], &LoadOptions::new()).unwrap();
+        assert_eq!(obj.info.groups, vec!["front-left.001", "2nd"]);
+        let mesh = match &obj.meshes[..] {
+            [hdkrs::Mesh::PolyMeshF32(mesh)] => mesh,
+            _ => panic!("Expected a single polygon mesh"),
+        };
+        // The attributes are found by the same names when converted into primitive groups.
+        let membership = |group: &str| {
+            let name = group_attrib_name(GROUP_ATTRIB_PREFIX, group);
+            mesh.0
+                .attrib_as_slice::<i32, FaceIndex>(&name)
+                .unwrap()
+                .to_vec()
+        };
+        assert_eq!(membership("front-left.001"), vec![1, 1]);
+        assert_eq!(membership("2nd"), vec![0, 1]);
+    }
 }
@@ -0,0 +1,146 @@
+//!
+//! This module reads and writes Wavefront material libraries (`.mtl`).
+//!
+//! Materials are represented as a flat list of parameters, one for each statement following a
+//! `newmtl` statement, so that they can be stored in detail attributes.
+//!
+
+use std::io::Write;
+use std::path::Path;
+
+use crate::ffi::MtlParam;
+
+/// Parse the given material library contents into a list of material parameters.
+///
+/// Statements with only numeric arguments are parsed into `values`, all others are kept as text.
+pub fn parse_mtl(data: &[u8]) -> Vec<MtlParam> {
+    let data = String::from_utf8_lossy(data);
+    let mut params = Vec::new();
+    let mut material = None;
+    for line in data.lines() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        let (key, args) = match line.split_once(char::is_whitespace) {
+            Some((key, args)) => (key, args.trim()),
+            None => (line, ""),
+        };
+        match key {
+            "" => {}
+            "newmtl" => material = Some(args.to_string()),
+            _ => {
+                let material = match &material {
+                    Some(material) => material.clone(),
+                    None => continue,
+                };
+                let values = args
+                    .split_whitespace()
+                    .map(|arg| arg.parse::<f64>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .unwrap_or_default();
+                params.push(MtlParam {
+                    material,
+                    key: key.to_string(),
+                    text: if values.is_empty() {
+                        args.to_string()
+                    } else {
+                        String::new()
+                    },
+                    values,
+                });
+            }
+        }
+    }
+    params
+}
+
+/// Load the given material libraries referenced by the obj file at `obj_path`.
+///
+/// Library file names are resolved relative to the directory containing the obj file.
+/// Libraries that fail to load are skipped with a message added to `warnings`.
+pub fn load_mtl_libs(
+    obj_path: &str,
+    libs: &[String],
+    warnings: &mut Vec<String>,
+) -> Vec<MtlParam> {
+    let dir = Path::new(obj_path)
+        .parent()
+        .unwrap_or_else(|| Path::new(""));
+    let mut params = Vec::new();
+    for lib in libs.iter() {
+        match std::fs::read(dir.join(lib)) {
+            Ok(data) => params.extend(parse_mtl(&data)),
+            Err(err) => {
+                warnings.push(format!("Failed to load material library {}: {}", lib, err))
+            }
+        }
+    }
+    params
+}
+
+/// Write a material library defining the given materials.
+///
+/// Each material is written with its parameters in `params`. Materials without parameters are
+/// given a default diffuse color.
+pub fn write_mtl(materials: &[String], params: &[MtlParam]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    for material in materials.iter() {
+        writeln!(buf, "newmtl {}", material).unwrap();
+        let mut found = false;
+        for param in params.iter().filter(|p| &p.material == material) {
+            write!(buf, "{}", param.key).unwrap();
+            if param.values.is_empty() && !param.text.is_empty() {
+                write!(buf, " {}", param.text).unwrap();
+            }
+            for value in param.values.iter() {
+                write!(buf, " {}", value).unwrap();
+            }
+            writeln!(buf).unwrap();
+            found = true;
+        }
+        if !found {
+            writeln!(buf, "Kd 0.8 0.8 0.8").unwrap();
+        }
+        writeln!(buf).unwrap();
+    }
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mtl_round_trip() {
+        let data = b"# Exported materials\n\
+            newmtl red # trailing comment\n\
+            Kd 1 0 0.25\n\
+            Ns 96.078431\n\
+            \n\
+            illum 2\n\
+            map_Kd textures/red brick.png\n\
+            newmtl glass\n\
+            # Transparent\n\
+            d 0.5\n\
+            Tf 0.9 0.9 1.0\n";
+        let params = parse_mtl(data);
+        let param = |material: &str, key: &str, values: &[f64], text: &str| MtlParam {
+            material: material.to_string(),
+            key: key.to_string(),
+            values: values.to_vec(),
+            text: text.to_string(),
+        };
+        assert_eq!(
+            params,
+            vec![
+                param("red", "Kd", &[1.0, 0.0, 0.25], ""),
+                param("red", "Ns", &[96.078431], ""),
+                param("red", "illum", &[2.0], ""),
+                param("red", "map_Kd", &[], "textures/red brick.png"),
+                param("glass", "d", &[0.5], ""),
+                param("glass", "Tf", &[0.9, 0.9, 1.0], ""),
+            ]
+        );
+
+        let materials = vec![String::from("red"), String::from("glass")];
+        assert_eq!(parse_mtl(&write_mtl(&materials, &params)), params);
+    }
+}
@@ -0,0 +1,273 @@
+//!
+//! This module converts between polygon meshes and obj data including texture coordinates
+//! (`vt`) and normals (`vn`).
+//!
+//! Texture coordinates and normals are stored in the `uv` and `N` attributes. On export, vertex
+//! attributes (face-vertex attributes on the mesh) are preferred over point attributes (vertex
+//! attributes on the mesh). Vertex values are deduplicated into shared `vt` and `vn` tables,
+//! while point values are written once per point. On import, `vt` and `vn` references are
+//! always loaded into vertex attributes, matching the native obj reader in Houdini.
+//!
+
+use std::collections::HashMap;
+use std::io::Write;
+
+use meshx::attrib::{Attrib, Attribute};
+use meshx::io::obj::ObjData;
+use meshx::mesh::topology::*;
+use meshx::mesh::{PolyMesh, VertexPositions};
+
+use hdkrs::ffi::AttribLocation;
+
+/// Name of the attribute storing texture coordinates.
+pub const UV_ATTRIB: &str = "uv";
+
+/// Name of the attribute storing normals.
+pub const NORMAL_ATTRIB: &str = "N";
+
+/// A table of `vt` or `vn` values with an index into the table for each face-vertex.
+struct IndexedTable {
+    values: Vec<[f32; 3]>,
+    indices: Vec<usize>,
+}
+
+/// Get the values of the given attribute as triplets, padding pairs with zero.
+fn attrib_triplets<I>(attrib: &Attribute<I>) -> Option<Vec<[f32; 3]>> {
+    if let Ok(data) = attrib.as_slice::<[f32; 3]>() {
+        Some(data.to_vec())
+    } else if let Ok(data) = attrib.as_slice::<[f32; 2]>() {
+        Some(data.iter().map(|v| [v[0], v[1], 0.0]).collect())
+    } else if let Ok(data) = attrib.as_slice::<[f64; 3]>() {
+        Some(
+            data.iter()
+                .map(|v| [v[0] as f32, v[1] as f32, v[2] as f32])
+                .collect(),
+        )
+    } else {
+        None
+    }
+}
+
+/// Build the table of values of the given attribute, preferring face-vertex over vertex values.
+fn build_table<T: meshx::Real>(mesh: &PolyMesh<T>, name: &str) -> Option<IndexedTable> {
+    if let Some(data) = mesh
+        .attrib::<FaceVertexIndex>(name)
+        .ok()
+        .and_then(attrib_triplets)
+    {
+        // Deduplicate values by their exact bit patterns.
+        let mut index = HashMap::new();
+        let mut values = Vec::new();
+        let indices = data
+            .iter()
+            .map(|v| {
+                let key = [v[0].to_bits(), v[1].to_bits(), v[2].to_bits()];
+                *index.entry(key).or_insert_with(|| {
+                    values.push(*v);
+                    values.len() - 1
+                })
+            })
+            .collect();
+        return Some(IndexedTable { values, indices });
+    }
+    let values = mesh
+        .attrib::<VertexIndex>(name)
+        .ok()
+        .and_then(attrib_triplets)?;
+    let indices = mesh.face_iter().flatten().copied().collect();
+    Some(IndexedTable { values, indices })
+}
+
+/// Write the given polygon mesh in obj format with texture coordinates and normals.
+pub fn write_polymesh<T: meshx::Real + std::fmt::Display>(mesh: &PolyMesh<T>) -> Vec<u8> {
+    let mut buf = Vec::new();
+    for p in mesh.vertex_positions() {
+        writeln!(buf, "v {} {} {}", p[0], p[1], p[2]).unwrap();
+    }
+    let uvs = build_table(mesh, UV_ATTRIB);
+    if let Some(uvs) = &uvs {
+        for uv in uvs.values.iter() {
+            writeln!(buf, "vt {} {}", uv[0], uv[1]).unwrap();
+        }
+    }
+    let normals = build_table(mesh, NORMAL_ATTRIB);
+    if let Some(normals) = &normals {
+        for n in normals.values.iter() {
+            writeln!(buf, "vn {} {} {}", n[0], n[1], n[2]).unwrap();
+        }
+    }
+    let mut face_vertex = 0;
+    for face in mesh.face_iter() {
+        write!(buf, "f").unwrap();
+        for &vtx in face.iter() {
+            write!(buf, " {}", vtx + 1).unwrap();
+            match (&uvs, &normals) {
+                (Some(uvs), Some(normals)) => write!(
+                    buf,
+                    "/{}/{}",
+                    uvs.indices[face_vertex] + 1,
+                    normals.indices[face_vertex] + 1
+                ),
+                (Some(uvs), None) => write!(buf, "/{}", uvs.indices[face_vertex] + 1),
+                (None, Some(normals)) => write!(buf, "//{}", normals.indices[face_vertex] + 1),
+                (None, None) => Ok(()),
+            }
+            .unwrap();
+            face_vertex += 1;
+        }
+        writeln!(buf).unwrap();
+    }
+    buf
+}
+
+/// Build a polygon mesh from the faces in the given obj data, with texture coordinates and
+/// normals loaded into face-vertex attributes.
+///
+/// All positions are kept, so vertex indices match positions in the file. Faces are ordered as
+/// in the file. Positions are kept in single precision as parsed.
+pub fn build_polymesh(obj_data: &ObjData) -> Option<hdkrs::PolyMeshF32> {
+    let coords: Vec<f32> = obj_data.position.iter().flatten().copied().collect();
+    let mut indices = Vec::new();
+    let mut uvs = Vec::new();
+    let mut normals = Vec::new();
+    let mut has_uvs = false;
+    let mut has_normals = false;
+    for object in obj_data.objects.iter() {
+        for group in object.groups.iter() {
+            for poly in group.polys.iter() {
+                indices.push(poly.0.len());
+                for tuple in poly.0.iter() {
+                    indices.push(tuple.0);
+                    let uv = tuple.1.and_then(|i| obj_data.texture.get(i));
+                    has_uvs |= uv.is_some();
+                    let uv = uv.copied().unwrap_or_default();
+                    uvs.extend_from_slice(&[uv[0], uv[1], 0.0]);
+                    let n = tuple.2.and_then(|i| obj_data.normal.get(i));
+                    has_normals |= n.is_some();
+                    normals.extend_from_slice(&n.copied().unwrap_or_default());
+                }
+            }
+        }
+    }
+    if indices.is_empty() {
+        return None;
+    }
+    let mut mesh = *hdkrs::make_polymesh_f32(&coords, &indices);
+    if has_uvs {
+        mesh.add_attrib_f32(AttribLocation::FACEVERTEX, UV_ATTRIB, 3, &uvs);
+    }
+    if has_normals {
+        mesh.add_attrib_f32(AttribLocation::FACEVERTEX, NORMAL_ATTRIB, 3, &normals);
+    }
+    Some(mesh)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use meshx::io::obj::LoadConfig;
+
+    // Two triangles sharing an edge of a unit square.
+    fn square() -> hdkrs::PolyMeshF32 {
+        let coords = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 1.0, 0.0];
+        *hdkrs::make_polymesh_f32(&coords, &[3, 0, 1, 2, 3, 0, 2, 3])
+    }
+
+    // Texture coordinates of each face-vertex of `square`, reversed on the second triangle.
+    const FACE_VERTEX_UVS: [f32; 18] = [
+        0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0,
+    ];
+
+    #[test]
+    fn face_vertex_values_are_preferred_and_deduplicated() {
+        let mut mesh = square();
+        let point_uvs = [0.5; 12];
+        mesh.add_attrib_f32(AttribLocation::VERTEX, UV_ATTRIB, 3, &point_uvs);
+        mesh.add_attrib_f32(AttribLocation::FACEVERTEX, UV_ATTRIB, 3, &FACE_VERTEX_UVS);
+        let table = build_table(&mesh.0, UV_ATTRIB).unwrap();
+        assert_eq!(
+            table.values,
+            vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0]]
+        );
+        assert_eq!(table.indices, vec![0, 1, 2, 2, 1, 0]);
+
+        // Values are only shared if they are bit-identical, so signed zeros are kept apart.
+        let mut mesh = square();
+        let mut uvs = FACE_VERTEX_UVS;
+        uvs[15] = -0.0;
+        mesh.add_attrib_f32(AttribLocation::FACEVERTEX, UV_ATTRIB, 3, &uvs);
+        let table = build_table(&mesh.0, UV_ATTRIB).unwrap();
+        assert_eq!(table.values.len(), 4);
+        assert_eq!(table.indices, vec![0, 1, 2, 2, 1, 3]);
+    }
+
+    #[test]
+    fn point_values_are_written_once_per_point() {
+        let mut mesh = square();
+        let normals = [0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0];
+        mesh.add_attrib_f32(AttribLocation::VERTEX, NORMAL_ATTRIB, 3, &normals);
+        let table = build_table(&mesh.0, NORMAL_ATTRIB).unwrap();
+        assert_eq!(table.values, vec![[0.0, 0.0, 1.0]; 4]);
+        assert_eq!(table.indices, vec![0, 1, 2, 0, 2, 3]);
+        assert!(build_table(&mesh.0, UV_ATTRIB).is_none());
+    }
+
+    #[test]
+    fn faces_reference_texture_coordinates_and_normals() {
+        let mut mesh = square();
+        mesh.add_attrib_f32(AttribLocation::FACEVERTEX, UV_ATTRIB, 3, &FACE_VERTEX_UVS);
+        let normals = [0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 1.0];
+        mesh.add_attrib_f32(AttribLocation::VERTEX, NORMAL_ATTRIB, 3, &normals);
+        assert_eq!(
+            String::from_utf8(write_polymesh(&mesh.0)).unwrap(),
+            "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+             vt 0 0\nvt 1 0\nvt 1 1\n\
+             vn 0 0 1\nvn 0 0 1\nvn 0 0 1\nvn 0 0 1\n\
+             f 1/1/1 2/2/2 3/3/3\nf 1/3/1 3/2/3 4/1/4\n"
+        );
+
+        let mut mesh = square();
+        mesh.add_attrib_f32(AttribLocation::VERTEX, NORMAL_ATTRIB, 3, &normals);
+        let obj = String::from_utf8(write_polymesh(&mesh.0)).unwrap();
+        assert!(obj.ends_with("f 1//1 2//2 3//3\nf 1//1 3//3 4//4\n"));
+    }
+
+    #[test]
+    fn texture_coordinates_and_normals_are_loaded_per_face_vertex() {
+        let data = b"v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+            vt 0 0\nvt 1 0\nvt 1 1\nvn 0 0 1\n\
+            f 1/1/1 2/2/1 3/3/1\nf 1/3 3/2 4/1\n";
+        let obj_data =
+            ObjData::load_buf_with_config(&data[..], LoadConfig { strict: false }).unwrap();
+        let mesh = build_polymesh(&obj_data).unwrap();
+        assert_eq!(
+            mesh.0
+                .attrib_as_slice::<[f32; 3], FaceVertexIndex>(UV_ATTRIB)
+                .unwrap(),
+            &[
+                [0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [1.0, 1.0, 0.0],
+                [1.0, 1.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [0.0, 0.0, 0.0]
+            ]
+        );
+        // Face-vertices without a normal are given a zero normal.
+        assert_eq!(
+            mesh.0
+                .attrib_as_slice::<[f32; 3], FaceVertexIndex>(NORMAL_ATTRIB)
+                .unwrap(),
+            &[
+                [0.0, 0.0, 1.0],
+                [0.0, 0.0, 1.0],
+                [0.0, 0.0, 1.0],
+                [0.0; 3],
+                [0.0; 3],
+                [0.0; 3]
+            ]
+        );
+        assert!(mesh.0.attrib::<VertexIndex>(UV_ATTRIB).is_err());
+        assert!(mesh.0.attrib::<VertexIndex>(NORMAL_ATTRIB).is_err());
+    }
+}
@@ -0,0 +1,370 @@
+//!
+//! This module scans OBJ files for statements that are not supported by the `obj` crate.
+//!
+//! Currently this includes line elements (`l`), which are imported as open polylines, vertex
+//! colors given by the `v x y z r g b` extension, as well as material assignments (`mtllib`,
+//! `usemtl`), objects (`o`), groups (`g`) and smoothing groups (`s`), which are tracked per face.
+//!
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Read};
+
+/// Statements collected from an OBJ file in addition to the ones handled by the `obj` crate.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Statements {
+    /// Zero-based position indices of each line element.
+    pub lines: Vec<Vec<usize>>,
+    /// File names of material libraries referenced by `mtllib` statements.
+    pub material_libs: Vec<String>,
+    /// Material names referenced by `usemtl` statements in order of first use.
+    pub materials: Vec<String>,
+    /// Index into `materials` of the material assigned to each face, or `-1` if none.
+    pub face_materials: Vec<i64>,
+    /// Object names given by `o` statements in order of first use.
+    pub objects: Vec<String>,
+    /// Index into `objects` of the object containing each face, or `-1` if none.
+    pub face_objects: Vec<i64>,
+    /// Group names given by `g` statements in order of first use.
+    pub groups: Vec<String>,
+    /// Distinct sets of indices into `groups` given by `g` statements.
+    pub group_sets: Vec<Vec<usize>>,
+    /// Index into `group_sets` of the groups containing each face, or `-1` if none.
+    pub face_groups: Vec<i64>,
+    /// Colors of each position given by the `v x y z r g b` extension, flattened into triplets.
+    /// Empty if no position has a color.
+    pub vertex_colors: Vec<f32>,
+    /// Smoothing group of each face given by `s` statements, or `0` if smoothing is off.
+    pub face_smoothing: Vec<i32>,
+    /// Whether any `s` statements were found.
+    pub has_smoothing: bool,
+    /// Number of skipped elements for each keyword, including invalid line elements and
+    /// unsupported point, curve and surface elements.
+    pub skipped: BTreeMap<String, usize>,
+}
+
+impl Statements {
+    /// Check if the face at the given index belongs to the group at the given index.
+    pub fn face_in_group(&self, face: usize, group: usize) -> bool {
+        match self.face_groups.get(face) {
+            Some(&set) if set >= 0 => self.group_sets[set as usize].contains(&group),
+            _ => false,
+        }
+    }
+}
+
+// Get the index of the given name in `names`, appending it if it is not found.
+fn intern(names: &mut Vec<String>, index: &mut HashMap<String, usize>, name: &str) -> usize {
+    *index.entry(name.to_string()).or_insert_with(|| {
+        names.push(name.to_string());
+        names.len() - 1
+    })
+}
+
+// Resolve a one-based, possibly negative (relative), OBJ index into a zero-based index.
+fn resolve_index(token: &str, num_positions: usize) -> Option<usize> {
+    // Only the position index is used from `v/vt` pairs.
+    let idx = token.split('/').next()?.parse::<isize>().ok()?;
+    if idx > 0 && (idx as usize) <= num_positions {
+        Some(idx as usize - 1)
+    } else if idx < 0 && idx.unsigned_abs() <= num_positions {
+        Some(num_positions - idx.unsigned_abs())
+    } else {
+        None
+    }
+}
+
+/// Incremental scanner for OBJ file contents fed in chunks of arbitrary size.
+#[derive(Clone, Debug)]
+pub struct Scanner {
+    statements: Statements,
+    num_positions: usize,
+    // Indices of the material, object and group set given by the last `usemtl`, `o` and `g`
+    // statements respectively.
+    current_material: i64,
+    current_object: i64,
+    current_groups: i64,
+    current_smoothing: i32,
+    material_index: HashMap<String, usize>,
+    object_index: HashMap<String, usize>,
+    group_index: HashMap<String, usize>,
+    group_set_index: HashMap<Vec<usize>, usize>,
+    // Trailing bytes of the last chunk not yet terminated by a newline.
+    partial: Vec<u8>,
+}
+
+impl Default for Scanner {
+    fn default() -> Self {
+        Scanner {
+            statements: Statements::default(),
+            num_positions: 0,
+            current_material: -1,
+            current_object: -1,
+            current_groups: -1,
+            current_smoothing: 0,
+            material_index: HashMap::new(),
+            object_index: HashMap::new(),
+            group_index: HashMap::new(),
+            group_set_index: HashMap::new(),
+            partial: Vec::new(),
+        }
+    }
+}
+
+impl Scanner {
+    /// Scan the next chunk of the file.
+    pub fn feed(&mut self, mut data: &[u8]) {
+        while let Some(pos) = data.iter().position(|&b| b == b'\n') {
+            if self.partial.is_empty() {
+                self.scan_line(&data[..pos]);
+            } else {
+                let mut line = std::mem::take(&mut self.partial);
+                line.extend_from_slice(&data[..pos]);
+                self.scan_line(&line);
+            }
+            data = &data[pos + 1..];
+        }
+        self.partial.extend_from_slice(data);
+    }
+
+    /// Scan any remaining unterminated line and return the collected statements.
+    pub fn finish(mut self) -> Statements {
+        let line = std::mem::take(&mut self.partial);
+        self.scan_line(&line);
+        self.statements
+    }
+
+    fn skip(&mut self, keyword: &str) {
+        *self
+            .statements
+            .skipped
+            .entry(keyword.to_string())
+            .or_insert(0) += 1;
+    }
+
+    fn scan_line(&mut self, line: &[u8]) {
+        let line = String::from_utf8_lossy(line);
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("v") => {
+                let args: Vec<&str> = tokens.collect();
+                let color: Option<Vec<f32>> = if args.len() == 6 {
+                    args[3..]
+                        .iter()
+                        .map(|arg| arg.parse::<f32>().ok())
+                        .collect()
+                } else {
+                    None
+                };
+                let colors = &mut self.statements.vertex_colors;
+                match color {
+                    Some(color) => {
+                        // Positions without colors are white.
+                        colors.resize(3 * self.num_positions, 1.0);
+                        colors.extend_from_slice(&color);
+                    }
+                    None if !colors.is_empty() => colors.extend_from_slice(&[1.0; 3]),
+                    None => {}
+                }
+                self.num_positions += 1;
+            }
+            Some("l") => {
+                let num_positions = self.num_positions;
+                let indices: Option<Vec<usize>> = tokens
+                    .map(|token| resolve_index(token, num_positions))
+                    .collect();
+                match indices {
+                    Some(indices) if indices.len() > 1 => self.statements.lines.push(indices),
+                    _ => self.skip("l"),
+                }
+            }
+            Some(keyword @ "p")
+            | Some(keyword @ "curv")
+            | Some(keyword @ "curv2")
+            | Some(keyword @ "surf") => self.skip(keyword),
+            Some("f") => {
+                self.statements.face_materials.push(self.current_material);
+                self.statements.face_objects.push(self.current_object);
+                self.statements.face_groups.push(self.current_groups);
+                self.statements.face_smoothing.push(self.current_smoothing);
+            }
+            Some("s") => {
+                self.statements.has_smoothing = true;
+                self.current_smoothing = tokens
+                    .next()
+                    .and_then(|arg| arg.parse::<i32>().ok())
+                    .unwrap_or(0);
+            }
+            Some("mtllib") => self
+                .statements
+                .material_libs
+                .extend(tokens.map(String::from)),
+            Some("usemtl") => {
+                self.current_material = match tokens.next() {
+                    Some(name) => intern(
+                        &mut self.statements.materials,
+                        &mut self.material_index,
+                        name,
+                    ) as i64,
+                    None => -1,
+                }
+            }
+            Some("o") => {
+                self.current_object = match tokens.next() {
+                    Some(name) => {
+                        intern(&mut self.statements.objects, &mut self.object_index, name) as i64
+                    }
+                    None => -1,
+                }
+            }
+            Some("g") => {
+                let mut set: Vec<usize> = tokens
+                    .map(|name| intern(&mut self.statements.groups, &mut self.group_index, name))
+                    .collect();
+                set.sort_unstable();
+                set.dedup();
+                self.current_groups = if set.is_empty() {
+                    -1
+                } else {
+                    let group_sets = &mut self.statements.group_sets;
+                    *self.group_set_index.entry(set).or_insert_with_key(|set| {
+                        group_sets.push(set.clone());
+                        group_sets.len() - 1
+                    }) as i64
+                };
+            }
+            _ => {}
+        }
+    }
+}
+
+/// A reader that scans the data passing through it.
+///
+/// This allows statements to be collected while the `obj` crate parses the same stream.
+pub struct ScanReader<R> {
+    inner: R,
+    scanner: Scanner,
+}
+
+impl<R: Read> ScanReader<R> {
+    pub fn new(inner: R) -> Self {
+        ScanReader {
+            inner,
+            scanner: Scanner::default(),
+        }
+    }
+
+    /// Return the statements collected from the data read so far.
+    pub fn finish(self) -> Statements {
+        self.scanner.finish()
+    }
+}
+
+impl<R: Read> Read for ScanReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.scanner.feed(&buf[..n]);
+        Ok(n)
+    }
+}
+
+impl Statements {
+    /// Scan the given OBJ file contents.
+    ///
+    /// Line elements referencing missing positions are skipped and counted in `skipped`.
+    pub fn scan(data: &[u8]) -> Statements {
+        let mut scanner = Scanner::default();
+        scanner.feed(data);
+        scanner.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRIANGLE: &str = "v 0 0 0\nv 1 0 0\nv 0 1 0\n";
+
+    #[test]
+    fn faces_belong_to_all_listed_groups() {
+        let data = format!(
+            "{}g a b\nf 1 2 3\ng b\nf 1 2 3\ng b a a\nf 1 2 3\ng\nf 1 2 3\n",
+            TRIANGLE
+        );
+        let statements = Statements::scan(data.as_bytes());
+        assert_eq!(statements.groups, vec!["a", "b"]);
+        // Repeated and reordered names refer to the same set of groups.
+        assert_eq!(statements.group_sets, vec![vec![0, 1], vec![1]]);
+        assert_eq!(statements.face_groups, vec![0, 1, 0, -1]);
+        let membership: Vec<(bool, bool)> = (0..4)
+            .map(|face| {
+                (
+                    statements.face_in_group(face, 0),
+                    statements.face_in_group(face, 1),
+                )
+            })
+            .collect();
+        assert_eq!(
+            membership,
+            vec![(true, true), (false, true), (true, true), (false, false)]
+        );
+    }
+
+    #[test]
+    fn reentered_objects_keep_their_index() {
+        let data = format!(
+            "{}f 1 2 3\no first\nf 1 2 3\no second\nf 1 2 3\no first\nf 1 2 3\nf 1 2 3\n",
+            TRIANGLE
+        );
+        let statements = Statements::scan(data.as_bytes());
+        assert_eq!(statements.objects, vec!["first", "second"]);
+        assert_eq!(statements.face_objects, vec![-1, 0, 1, 0, 0]);
+    }
+
+    #[test]
+    fn missing_vertex_colors_are_white() {
+        let data = "v 0 0 0\nv 1 0 0 1 0 0.5\nv 0 1 0\nv 0 0 1 0 1 0\n";
+        let statements = Statements::scan(data.as_bytes());
+        assert_eq!(
+            statements.vertex_colors,
+            vec![1.0, 1.0, 1.0, 1.0, 0.0, 0.5, 1.0, 1.0, 1.0, 0.0, 1.0, 0.0]
+        );
+        assert!(Statements::scan(TRIANGLE.as_bytes())
+            .vertex_colors
+            .is_empty());
+    }
+
+    #[test]
+    fn smoothing_off_is_group_zero() {
+        let data = format!(
+            "{}f 1 2 3\ns 1\nf 1 2 3\ns off\nf 1 2 3\ns 2\nf 1 2 3\ns 0\nf 1 2 3\n",
+            TRIANGLE
+        );
+        let statements = Statements::scan(data.as_bytes());
+        assert!(statements.has_smoothing);
+        assert_eq!(statements.face_smoothing, vec![0, 1, 0, 2, 0]);
+        assert!(!Statements::scan(TRIANGLE.as_bytes()).has_smoothing);
+    }
+
+    #[test]
+    fn lines_split_across_chunks() {
+        let data = format!(
+            "{}v 1 1 0 0.5 0.5 0.5\nusemtl red\ng a b\ns 1\nf 1 2 3\nl 1 2 -1\no last\nf 1 2 4",
+            TRIANGLE
+        );
+        let data = data.as_bytes();
+        let expected = Statements::scan(data);
+        assert_eq!(expected.lines, vec![vec![0, 1, 3]]);
+        assert_eq!(expected.face_objects, vec![-1, 0]);
+        for split in 0..=data.len() {
+            let mut scanner = Scanner::default();
+            scanner.feed(&data[..split]);
+            scanner.feed(&data[split..]);
+            assert_eq!(scanner.finish(), expected, "split at {}", split);
+        }
+        let mut scanner = Scanner::default();
+        for byte in data.chunks(1) {
+            scanner.feed(byte);
+        }
+        assert_eq!(scanner.finish(), expected);
+    }
+}
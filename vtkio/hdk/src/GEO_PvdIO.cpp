@@ -0,0 +1,119 @@
+#include <cstdlib>
+#include <iostream>
+
+#include <GU/GU_Detail.h>
+#include <GEO/GEO_IOTranslator.h>
+#include <UT/UT_IStream.h>
+#include <UT/UT_IOTable.h>
+#include <UT/UT_ErrorManager.h>
+#include <SOP/SOP_Node.h>
+#include <CH/CH_Manager.h>
+
+#include <vtkio/src/lib.rs.h>
+#include <hdkrs/prelude.h>
+
+#include "GEO_PvdIO.h"
+
+// Prefix of environment variables selecting the coordinate system and vertex order of VTK
+// files.
+static const char *ENV_PREFIX = "VTKIO";
+
+// Environment variable selecting a time step to load by its position in the collection instead
+// of loading the current evaluation time.
+static const char *TIMESTEP_ENV_VAR = "VTKIO_PVD_TIMESTEP";
+
+// Vertex order of files, which defaults to the convention of the format.
+static hdkrs::VertexOrder
+vertexOrder()
+{
+    return hdkrs::vertex_order_from_env(ENV_PREFIX, vtkio::default_vertex_order());
+}
+
+GEO_IOTranslator *
+GEO_PvdIO::duplicate() const
+{
+    return new GEO_PvdIO(*this);
+}
+
+hdkrs::CoordTransform
+GEO_PvdIO::coordTransform() const
+{
+    return hdkrs::coord_transform_from_env(ENV_PREFIX);
+}
+
+const char *
+GEO_PvdIO::formatName() const
+{
+    return "ParaView Data (PVD) Collection of VTK Files";
+}
+
+int
+GEO_PvdIO::checkExtension(const char *name) 
+{
+    UT_String sname(name);
+    if (sname.fileExtension() && !strcmp(sname.fileExtension(), ".pvd"))
+        return true;
+    return false;
+}
+
+int
+GEO_PvdIO::checkMagicNumber(unsigned magic)
+{
+    return 0;
+}
+
+GA_Detail::IOStatus
+GEO_PvdIO::fileLoad(GEO_Detail *detail, UT_IStream &is, bool)
+{
+    if (!detail) // nothing to do
+        return GA_Detail::IOStatus(true);
+
+    // The collection references other files relative to itself, so we need the path rather than
+    // the stream contents.
+    const char *path = is.getLabel();
+    if (!path)
+        return GA_Detail::IOStatus(false);
+
+    // Load the chosen time step if any, otherwise the frame at the current evaluation time.
+    GU_Detail &gdp = static_cast<GU_Detail&>(*detail);
+    const char *timestep = std::getenv(TIMESTEP_ENV_VAR);
+    char *end = nullptr;
+    long index = timestep ? std::strtol(timestep, &end, 10) : -1;
+    bool by_index = timestep && end != timestep && index >= 0;
+    hdkrs::CookResult result = by_index
+        ? vtkio::add_pvd_mesh_at_index(gdp, path, static_cast<std::size_t>(index))
+        : vtkio::add_pvd_mesh_at_time(gdp, path, CHgetEvalTime());
+    bool success = hdkrs::report_cook_result(result);
+    if (success)
+        hdkrs::import_detail(gdp, coordTransform(), vertexOrder());
+    return GA_Detail::IOStatus(success);
+}
+
+GA_Detail::IOStatus
+GEO_PvdIO::fileSave(const GEO_Detail *detail, std::ostream &os)
+{
+    // Collections span multiple files, so they can only be written through fileSaveToFile.
+    return GA_Detail::IOStatus(false);
+}
+
+GA_Detail::IOStatus
+GEO_PvdIO::fileSaveToFile(const GEO_Detail *detail, const char *fname)
+{
+    if (!detail) // nothing to do
+        return GA_Detail::IOStatus(true);
+
+    GU_Detail copy;
+    const GU_Detail &gud = hdkrs::export_detail(
+            copy, static_cast<const GU_Detail&>(*detail),
+            coordTransform(), vertexOrder());
+
+    // Append the current frame to the collection.
+    try {
+        vtkio::write_pvd_frame(gud, fname, CHgetEvalTime());
+    } catch (const rust::Error &e) {
+        // Report the failure to the node writing the file.
+        UTaddError("SOP", SOP_MESSAGE, e.what());
+        return GA_Detail::IOStatus(false);
+    }
+    return GA_Detail::IOStatus(true);
+}
@@ -0,0 +1,25 @@
+#pragma once
+
+#include <iostream>
+
+#include <GU/GU_Detail.h>
+#include <GEO/GEO_IOTranslator.h>
+#include <UT/UT_IStream.h>
+#include <hdkrs/src/lib.rs.h>
+
+class GEO_PvdIO : public GEO_IOTranslator
+{
+public:
+	GEO_PvdIO() {}
+	GEO_PvdIO(const GEO_PvdIO&) {}
+	virtual ~GEO_PvdIO() {}
+	virtual GEO_IOTranslator *duplicate() const;
+	virtual const char *formatName() const;
+	virtual int checkExtension(const char *);
+	virtual int checkMagicNumber(unsigned);
+	virtual GA_Detail::IOStatus fileLoad(GEO_Detail*, UT_IStream&, bool);
+	virtual GA_Detail::IOStatus fileSave(const GEO_Detail*, std::ostream&);
+	virtual GA_Detail::IOStatus fileSaveToFile(const GEO_Detail*, const char *);
+	// Coordinate system of files, read from the environment on each use.
+	hdkrs::CoordTransform coordTransform() const;
+};
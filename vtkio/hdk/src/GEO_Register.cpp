@@ -1,3 +1,4 @@
+#include <string>
 #include <vector>
 
 // Required for proper loading.
@@ -9,6 +10,9 @@
 #include "GEO_VtkIO.h"
 #include "GEO_VtuIO.h"
 #include "GEO_VtpIO.h"
+#include "GEO_VtiIO.h"
+#include "GEO_VtsIO.h"
+#include "GEO_PvdIO.h"
 
 void
 newGeometryIO(void *)
@@ -16,13 +20,22 @@ newGeometryIO(void *)
     GU_Detail::registerIOTranslator(new GEO_VtkIO());
     GU_Detail::registerIOTranslator(new GEO_VtuIO());
     GU_Detail::registerIOTranslator(new GEO_VtpIO());
-    std::vector<const char *> extensions{"vtk", "vtu", "vtp", "pvtp", "pvtu"};
+    GU_Detail::registerIOTranslator(new GEO_VtiIO());
+    GU_Detail::registerIOTranslator(new GEO_VtsIO());
+    GU_Detail::registerIOTranslator(new GEO_PvdIO());
+    std::vector<std::string> extensions{"vtk", "vtu", "vtp", "pvtp", "pvtu", "vti", "vtr", "vts"};
 
     UT_ExtensionList *geoextension;
     geoextension = UTgetGeoExtensions();
 
-    for (auto ext : extensions) {
-        if (!geoextension->findExtension(ext))
-            geoextension->addExtension(ext);
+    // Compressed variants are accepted for all formats except collections, which reference
+    // other files.
+    for (const auto &ext : extensions) {
+        for (const auto &name : {ext, ext + ".gz", ext + ".zst"}) {
+            if (!geoextension->findExtension(name.c_str()))
+                geoextension->addExtension(name.c_str());
+        }
     }
+    if (!geoextension->findExtension("pvd"))
+        geoextension->addExtension("pvd");
 }
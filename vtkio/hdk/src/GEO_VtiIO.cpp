@@ -0,0 +1,84 @@
+#include <iostream>
+
+#include <GU/GU_Detail.h>
+#include <GEO/GEO_AttributeHandle.h>
+#include <GEO/GEO_IOTranslator.h>
+#include <UT/UT_IStream.h>
+#include <SOP/SOP_Node.h>
+#include <UT/UT_IOTable.h>
+
+#include <vtkio/src/lib.rs.h>
+#include <hdkrs/prelude.h>
+
+#include "GEO_VtiIO.h"
+
+// Prefix of environment variables selecting the coordinate system of VTK files.
+static const char *ENV_PREFIX = "VTKIO";
+
+using hdkrs::cast_box;
+
+GEO_IOTranslator *
+GEO_VtiIO::duplicate() const
+{
+    return new GEO_VtiIO(*this);
+}
+
+hdkrs::CoordTransform
+GEO_VtiIO::coordTransform() const
+{
+    return hdkrs::coord_transform_from_env(ENV_PREFIX);
+}
+
+const char *
+GEO_VtiIO::formatName() const
+{
+    return "Visualization ToolKit (VTK) Image Data in XML Format";
+}
+
+int
+GEO_VtiIO::checkExtension(const char *name) 
+{
+    return hdkrs::has_extension(name, ".vti");
+}
+
+int
+GEO_VtiIO::checkMagicNumber(unsigned magic)
+{
+    return 0;
+}
+
+GA_Detail::IOStatus
+GEO_VtiIO::fileLoad(GEO_Detail *detail, UT_IStream &is, bool)
+{
+    if (!detail) // nothing to do
+        return GA_Detail::IOStatus(true);
+
+    hdkrs::IStreamReader reader(is, "Loading VTI");
+    hdkrs::CookResult result = vtkio::add_vtk_grid_from_stream_with_transform(
+            static_cast<GU_Detail&>(*detail), reader, coordTransform());
+    return GA_Detail::IOStatus(hdkrs::report_cook_result(result));
+}
+
+GA_Detail::IOStatus
+GEO_VtiIO::fileSave(const GEO_Detail *detail, std::ostream &os)
+{
+    if (!detail) // nothing to do
+        return GA_Detail::IOStatus(true);
+
+    try {
+        auto buf = vtkio::grid_to_vti_buffer_with_transform(
+                static_cast<const GU_Detail&>(*detail), coordTransform());
+        os.write(reinterpret_cast<const char *>(buf.data()), buf.size());
+        return GA_Detail::IOStatus(true);
+    } catch(...) {}
+
+    return GA_Detail::IOStatus(false);
+}
+
+GA_Detail::IOStatus
+GEO_VtiIO::fileSaveToFile(const GEO_Detail *detail, const char *fname)
+{
+    if (!fname || !hdkrs::is_compressed_path(fname))
+        return GEO_IOTranslator::fileSaveToFile(detail, fname);
+    return hdkrs::save_compressed(fname, [&](std::ostream &os) { return fileSave(detail, os); });
+}
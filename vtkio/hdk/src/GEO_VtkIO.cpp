@@ -12,12 +12,29 @@
 
 #include "GEO_VtkIO.h"
 
+// Prefix of environment variables selecting the coordinate system and vertex order of VTK
+// files.
+static const char *ENV_PREFIX = "VTKIO";
+
+// Vertex order of files, which defaults to the convention of the format.
+static hdkrs::VertexOrder
+vertexOrder()
+{
+    return hdkrs::vertex_order_from_env(ENV_PREFIX, vtkio::default_vertex_order());
+}
+
 GEO_IOTranslator *
 GEO_VtkIO::duplicate() const
 {
     return new GEO_VtkIO(*this);
 }
 
+hdkrs::CoordTransform
+GEO_VtkIO::coordTransform() const
+{
+    return hdkrs::coord_transform_from_env(ENV_PREFIX);
+}
+
 const char *
 GEO_VtkIO::formatName() const
 {
@@ -27,10 +44,7 @@ GEO_VtkIO::formatName() const
 int
 GEO_VtkIO::checkExtension(const char *name) 
 {
-    UT_String sname(name);
-    if (sname.fileExtension() && !strcmp(sname.fileExtension(), ".vtk"))
-        return true;
-    return false;
+    return hdkrs::has_extension(name, ".vtk");
 }
 
 int
@@ -45,12 +59,12 @@ GEO_VtkIO::fileLoad(GEO_Detail *detail, UT_IStream &is, bool)
     if (!detail) // nothing to do
         return GA_Detail::IOStatus(true);
 
-    UT_WorkBuffer buf;
-    bool success = is.getAll(buf);
-    if (!success)
-        return GA_Detail::IOStatus(success);
-    rust::Slice<const uint8_t> slice(reinterpret_cast<const unsigned char*>(buf.buffer()), buf.length());
-    vtkio::add_vtk_mesh(*static_cast<GU_Detail*>(detail), slice);
+    GU_Detail &gdp = static_cast<GU_Detail&>(*detail);
+    hdkrs::IStreamReader reader(is, "Loading VTK");
+    hdkrs::CookResult result = vtkio::add_vtk_mesh_from_stream(gdp, reader);
+    bool success = hdkrs::report_cook_result(result);
+    if (success)
+        hdkrs::import_detail(gdp, coordTransform(), vertexOrder());
     return GA_Detail::IOStatus(success);
 }
 
@@ -60,7 +74,10 @@ GEO_VtkIO::fileSave(const GEO_Detail *detail, std::ostream &os)
     if (!detail) // nothing to do
         return GA_Detail::IOStatus(true);
 
-    const GU_Detail &gud = *static_cast<const GU_Detail*>(detail);
+    GU_Detail copy;
+    const GU_Detail &gud = hdkrs::export_detail(
+            copy, static_cast<const GU_Detail&>(*detail),
+            coordTransform(), vertexOrder());
 
     // Try to save unstructured mesh first
     try {
@@ -78,3 +95,11 @@ GEO_VtkIO::fileSave(const GEO_Detail *detail, std::ostream &os)
 
     return GA_Detail::IOStatus(false);
 }
+
+GA_Detail::IOStatus
+GEO_VtkIO::fileSaveToFile(const GEO_Detail *detail, const char *fname)
+{
+    if (!fname || !hdkrs::is_compressed_path(fname))
+        return GEO_IOTranslator::fileSaveToFile(detail, fname);
+    return hdkrs::save_compressed(fname, [&](std::ostream &os) { return fileSave(detail, os); });
+}
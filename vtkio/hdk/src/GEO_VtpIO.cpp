@@ -12,6 +12,17 @@
 
 #include "GEO_VtpIO.h"
 
+// Prefix of environment variables selecting the coordinate system and vertex order of VTK
+// files.
+static const char *ENV_PREFIX = "VTKIO";
+
+// Vertex order of files, which defaults to the convention of the format.
+static hdkrs::VertexOrder
+vertexOrder()
+{
+    return hdkrs::vertex_order_from_env(ENV_PREFIX, vtkio::default_vertex_order());
+}
+
 using hdkrs::cast_box;
 
 GEO_IOTranslator *
@@ -20,6 +31,12 @@ GEO_VtpIO::duplicate() const
     return new GEO_VtpIO(*this);
 }
 
+hdkrs::CoordTransform
+GEO_VtpIO::coordTransform() const
+{
+    return hdkrs::coord_transform_from_env(ENV_PREFIX);
+}
+
 const char *
 GEO_VtpIO::formatName() const
 {
@@ -29,12 +46,8 @@ GEO_VtpIO::formatName() const
 int
 GEO_VtpIO::checkExtension(const char *name) 
 {
-    UT_String sname(name);
-    if (sname.fileExtension() &&
-        (!strcmp(sname.fileExtension(), ".vtp") ||
-         !strcmp(sname.fileExtension(), ".pvtp")))
-        return true;
-    return false;
+    return hdkrs::has_extension(name, ".vtp") ||
+        hdkrs::has_extension(name, ".pvtp");
 }
 
 int
@@ -49,12 +62,12 @@ GEO_VtpIO::fileLoad(GEO_Detail *detail, UT_IStream &is, bool)
     if (!detail) // nothing to do
         return GA_Detail::IOStatus(true);
 
-    UT_WorkBuffer buf;
-    bool success = is.getAll(buf);
-    if (!success)
-        return GA_Detail::IOStatus(success);
-    rust::Slice<const uint8_t> slice(reinterpret_cast<const unsigned char*>(buf.buffer()), buf.length());
-    vtkio::add_vtp_mesh(static_cast<GU_Detail&>(*detail), slice);
+    GU_Detail &gdp = static_cast<GU_Detail&>(*detail);
+    hdkrs::IStreamReader reader(is, "Loading VTP");
+    hdkrs::CookResult result = vtkio::add_vtp_mesh_from_stream(gdp, reader);
+    bool success = hdkrs::report_cook_result(result);
+    if (success)
+        hdkrs::import_detail(gdp, coordTransform(), vertexOrder());
     return GA_Detail::IOStatus(success);
 }
 
@@ -64,19 +77,32 @@ GEO_VtpIO::fileSave(const GEO_Detail *detail, std::ostream &os)
     if (!detail) // nothing to do
         return GA_Detail::IOStatus(true);
 
+    GU_Detail copy;
+    const GU_Detail &gud = hdkrs::export_detail(
+            copy, static_cast<const GU_Detail&>(*detail),
+            coordTransform(), vertexOrder());
+
     // Try to save the polymesh
     try {
-        auto buf = vtkio::polymesh_to_vtp_buffer(static_cast<const GU_Detail&>(*detail));
+        auto buf = vtkio::polymesh_to_vtp_buffer(gud);
         os.write(reinterpret_cast<const char *>(buf.data()), buf.size());
         return GA_Detail::IOStatus(true);
     } catch (...) {}
 
     // If no polygons are found we try to save the pointcloud
     try {
-        auto buf = vtkio::pointcloud_to_vtp_buffer(static_cast<const GU_Detail&>(*detail));
+        auto buf = vtkio::pointcloud_to_vtp_buffer(gud);
         os.write(reinterpret_cast<const char *>(buf.data()), buf.size());
         return GA_Detail::IOStatus(true);
     } catch (...) {}
 
     return GA_Detail::IOStatus(false);
 }
+
+GA_Detail::IOStatus
+GEO_VtpIO::fileSaveToFile(const GEO_Detail *detail, const char *fname)
+{
+    if (!fname || !hdkrs::is_compressed_path(fname))
+        return GEO_IOTranslator::fileSaveToFile(detail, fname);
+    return hdkrs::save_compressed(fname, [&](std::ostream &os) { return fileSave(detail, os); });
+}
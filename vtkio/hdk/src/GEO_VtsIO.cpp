@@ -0,0 +1,68 @@
+#include <iostream>
+
+#include <GU/GU_Detail.h>
+#include <GEO/GEO_AttributeHandle.h>
+#include <GEO/GEO_IOTranslator.h>
+#include <UT/UT_IStream.h>
+#include <SOP/SOP_Node.h>
+#include <UT/UT_IOTable.h>
+
+#include <vtkio/src/lib.rs.h>
+#include <hdkrs/prelude.h>
+
+#include "GEO_VtsIO.h"
+
+// Prefix of environment variables selecting the coordinate system of VTK files.
+static const char *ENV_PREFIX = "VTKIO";
+
+using hdkrs::cast_box;
+
+GEO_IOTranslator *
+GEO_VtsIO::duplicate() const
+{
+    return new GEO_VtsIO(*this);
+}
+
+hdkrs::CoordTransform
+GEO_VtsIO::coordTransform() const
+{
+    return hdkrs::coord_transform_from_env(ENV_PREFIX);
+}
+
+const char *
+GEO_VtsIO::formatName() const
+{
+    return "Visualization ToolKit (VTK) Rectilinear or Structured Grid in XML Format";
+}
+
+int
+GEO_VtsIO::checkExtension(const char *name) 
+{
+    return hdkrs::has_extension(name, ".vtr") ||
+        hdkrs::has_extension(name, ".vts");
+}
+
+int
+GEO_VtsIO::checkMagicNumber(unsigned magic)
+{
+    return 0;
+}
+
+GA_Detail::IOStatus
+GEO_VtsIO::fileLoad(GEO_Detail *detail, UT_IStream &is, bool)
+{
+    if (!detail) // nothing to do
+        return GA_Detail::IOStatus(true);
+
+    hdkrs::IStreamReader reader(is, "Loading VTS");
+    hdkrs::CookResult result = vtkio::add_vtk_grid_from_stream_with_transform(
+            static_cast<GU_Detail&>(*detail), reader, coordTransform());
+    return GA_Detail::IOStatus(hdkrs::report_cook_result(result));
+}
+
+GA_Detail::IOStatus
+GEO_VtsIO::fileSave(const GEO_Detail *detail, std::ostream &os)
+{
+    // Volumes are exported as image data, see GEO_VtiIO.
+    return GA_Detail::IOStatus(false);
+}
@@ -0,0 +1,24 @@
+#pragma once
+
+#include <iostream>
+
+#include <GU/GU_Detail.h>
+#include <GEO/GEO_IOTranslator.h>
+#include <UT/UT_IStream.h>
+#include <hdkrs/src/lib.rs.h>
+
+class GEO_VtsIO : public GEO_IOTranslator
+{
+public:
+	GEO_VtsIO() {}
+	GEO_VtsIO(const GEO_VtsIO&) {}
+	virtual ~GEO_VtsIO() {}
+	virtual GEO_IOTranslator *duplicate() const;
+	virtual const char *formatName() const;
+	virtual int checkExtension(const char *);
+	virtual int checkMagicNumber(unsigned);
+	virtual GA_Detail::IOStatus fileLoad(GEO_Detail*, UT_IStream&, bool);
+	virtual GA_Detail::IOStatus fileSave(const GEO_Detail*, std::ostream&);
+	// Coordinate system of files, read from the environment on each use.
+	hdkrs::CoordTransform coordTransform() const;
+};
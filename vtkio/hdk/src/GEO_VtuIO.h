@@ -5,6 +5,7 @@
 #include <GU/GU_Detail.h>
 #include <GEO/GEO_IOTranslator.h>
 #include <UT/UT_IStream.h>
+#include <hdkrs/src/lib.rs.h>
 
 class GEO_VtuIO : public GEO_IOTranslator
 {
@@ -18,4 +19,7 @@ public:
 	virtual int checkMagicNumber(unsigned);
 	virtual GA_Detail::IOStatus fileLoad(GEO_Detail*, UT_IStream&, bool);
 	virtual GA_Detail::IOStatus fileSave(const GEO_Detail*, std::ostream&);
+	virtual GA_Detail::IOStatus fileSaveToFile(const GEO_Detail*, const char*);
+	// Coordinate system of files, read from the environment on each use.
+	hdkrs::CoordTransform coordTransform() const;
 };
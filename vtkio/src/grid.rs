@@ -0,0 +1,386 @@
+//!
+//! This module converts between `hdkrs::GridMesh` and the structured VTK data set types:
+//! image data (`.vti`), rectilinear grids (`.vtr`) and structured grids (`.vts`).
+//!
+
+use meshx::io::vtk::model::*;
+
+use crate::Error;
+
+// Number of components stored in each element of a VTK data array.
+pub(crate) fn num_comp(elem: &ElementType) -> usize {
+    match elem {
+        ElementType::ColorScalars(n) => *n as usize,
+        ElementType::LookupTable => 4,
+        ElementType::Scalars { num_comp, .. } => *num_comp as usize,
+        ElementType::Vectors | ElementType::Normals => 3,
+        ElementType::TCoords(n) => *n as usize,
+        ElementType::Tensors => 9,
+        ElementType::Generic(n) => *n as usize,
+    }
+}
+
+/// Add the given VTK attributes to the grid as fields.
+///
+/// Each field is passed through `resample` along with its number of components before being
+/// added. Attributes that cannot be stored in the grid are reported in `warnings`.
+fn add_fields(
+    grid: &mut hdkrs::GridMesh,
+    attribs: Vec<Attribute>,
+    cell: bool,
+    resample: impl Fn(&[f32], usize) -> Vec<f32>,
+    warnings: &mut Vec<String>,
+) {
+    let num_samples = grid.num_samples(cell);
+    for attrib in attribs {
+        if let Attribute::DataArray(DataArray { name, elem, data }) = attrib {
+            let n = num_comp(&elem);
+            let added = match data.cast_into::<f32>() {
+                Some(values) if values.len() == n * num_samples => {
+                    grid.add_field(&name, n, cell, &resample(&values, n))
+                }
+                _ => Err(hdkrs::Error::MeshMismatch),
+            };
+            if let Err(err) = added {
+                warnings.push(format!("Failed to add field \"{}\": {}", name, err));
+            }
+        }
+    }
+}
+
+fn to_f64_vec(buf: IOBuffer) -> Result<Vec<f64>, Error> {
+    buf.cast_into::<f64>()
+        .ok_or_else(|| Error(String::from("Unsupported coordinate type")))
+}
+
+// Uniform grid parameters spanning the given sorted coordinates along one axis.
+fn uniform_axis(coords: &[f64]) -> (f64, f64) {
+    match (coords.first(), coords.last()) {
+        (Some(&first), Some(&last)) if coords.len() > 1 => {
+            (first, (last - first) / (coords.len() - 1) as f64)
+        }
+        (Some(&first), _) => (first, 1.0),
+        _ => (0.0, 1.0),
+    }
+}
+
+/// Resampling of one axis of a rectilinear grid onto uniformly spaced samples spanning the same
+/// bounds.
+struct AxisResampling {
+    origin: f64,
+    spacing: f64,
+    /// Interval of source points containing each uniform point, along with the interpolation
+    /// weight of the interval end.
+    points: Vec<(usize, f64)>,
+    /// Source cell containing the center of each uniform cell.
+    cells: Vec<usize>,
+}
+
+impl AxisResampling {
+    fn new(coords: &[f64]) -> Result<Self, Error> {
+        if coords.is_empty() {
+            return Err(Error(String::from("Rectilinear grid has no coordinates")));
+        }
+        let increasing = |w: &[f64]| w[0].partial_cmp(&w[1]) == Some(std::cmp::Ordering::Less);
+        if !coords.windows(2).all(increasing) {
+            return Err(Error(String::from(
+                "Rectilinear grid coordinates must be strictly increasing",
+            )));
+        }
+        let n = coords.len();
+        let (origin, spacing) = uniform_axis(coords);
+        // Interval [coords[j], coords[j + 1]] containing x.
+        let interval = |x: f64| coords.partition_point(|&c| c <= x).clamp(1, n.max(2) - 1) - 1;
+        let points = (0..n)
+            .map(|i| {
+                let x = origin + spacing * i as f64;
+                if n < 2 {
+                    return (0, 0.0);
+                }
+                let j = interval(x);
+                let weight = (x - coords[j]) / (coords[j + 1] - coords[j]);
+                (j, weight.clamp(0.0, 1.0))
+            })
+            .collect();
+        let cells = (0..n.max(2) - 1)
+            .map(|i| interval(origin + spacing * (i as f64 + 0.5)))
+            .collect();
+        Ok(AxisResampling {
+            origin,
+            spacing,
+            points,
+            cells,
+        })
+    }
+}
+
+/// Linearly interpolate point data of a rectilinear grid at the uniformly spaced points.
+fn resample_points(data: &[f32], num_comp: usize, axes: &[AxisResampling; 3]) -> Vec<f32> {
+    let nx = axes[0].points.len();
+    let ny = axes[1].points.len();
+    let mut out = Vec::with_capacity(data.len());
+    let mut value = vec![0.0_f64; num_comp];
+    for &(k, wz) in axes[2].points.iter() {
+        for &(j, wy) in axes[1].points.iter() {
+            for &(i, wx) in axes[0].points.iter() {
+                value.iter_mut().for_each(|v| *v = 0.0);
+                for corner in 0..8 {
+                    let (dx, dy, dz) = (corner & 1, (corner >> 1) & 1, (corner >> 2) & 1);
+                    let weight = |w: f64, d: usize| if d == 0 { 1.0 - w } else { w };
+                    let w = weight(wx, dx) * weight(wy, dy) * weight(wz, dz);
+                    // Zero weights also cover the missing upper corner of single point axes.
+                    if w == 0.0 {
+                        continue;
+                    }
+                    let src = (i + dx) + nx * ((j + dy) + ny * (k + dz));
+                    for (v, &d) in value.iter_mut().zip(&data[num_comp * src..]) {
+                        *v += w * f64::from(d);
+                    }
+                }
+                out.extend(value.iter().map(|&v| v as f32));
+            }
+        }
+    }
+    out
+}
+
+/// Sample cell data of a rectilinear grid at the centers of the uniformly spaced cells.
+fn resample_cells(data: &[f32], num_comp: usize, axes: &[AxisResampling; 3]) -> Vec<f32> {
+    let nx = axes[0].cells.len();
+    let ny = axes[1].cells.len();
+    let mut out = Vec::with_capacity(data.len());
+    for &k in axes[2].cells.iter() {
+        for &j in axes[1].cells.iter() {
+            for &i in axes[0].cells.iter() {
+                let src = i + nx * (j + ny * k);
+                out.extend_from_slice(&data[num_comp * src..num_comp * (src + 1)]);
+            }
+        }
+    }
+    out
+}
+
+/// Convert rectilinear grid data with the given axis coordinates into a uniform grid.
+///
+/// Point data is linearly interpolated and cell data is sampled at cell centers. Uniformly
+/// spaced coordinates are copied without resampling.
+fn rectilinear_to_grid(
+    coords: [Vec<f64>; 3],
+    data: Attributes,
+    warnings: &mut Vec<String>,
+) -> Result<hdkrs::GridMesh, Error> {
+    let [x, y, z] = coords;
+    let axes = [
+        AxisResampling::new(&x)?,
+        AxisResampling::new(&y)?,
+        AxisResampling::new(&z)?,
+    ];
+    let is_uniform = [&x, &y, &z].iter().zip(axes.iter()).all(|(c, axis)| {
+        let tol = 1e-6 * (axis.spacing.abs() * c.len() as f64).max(f64::MIN_POSITIVE);
+        c.iter()
+            .enumerate()
+            .all(|(i, &c)| (c - (axis.origin + axis.spacing * i as f64)).abs() <= tol)
+    });
+    let mut grid = hdkrs::GridMesh::new(
+        [axes[0].origin, axes[1].origin, axes[2].origin],
+        [axes[0].spacing, axes[1].spacing, axes[2].spacing],
+        [x.len(), y.len(), z.len()],
+    );
+    if is_uniform {
+        let copy = |data: &[f32], _: usize| data.to_vec();
+        add_fields(&mut grid, data.point, false, copy, warnings);
+        add_fields(&mut grid, data.cell, true, copy, warnings);
+    } else {
+        let points = |data: &[f32], n: usize| resample_points(data, n, &axes);
+        let cells = |data: &[f32], n: usize| resample_cells(data, n, &axes);
+        add_fields(&mut grid, data.point, false, points, warnings);
+        add_fields(&mut grid, data.cell, true, cells, warnings);
+    }
+    Ok(grid)
+}
+
+/// Axis coordinates of a structured grid whose points form an axis aligned lattice.
+///
+/// Returns `None` for curvilinear grids.
+fn lattice_axes(points: &[f64], dims: [usize; 3]) -> Option<[Vec<f64>; 3]> {
+    if points.len() != 3 * dims.iter().product::<usize>() {
+        return None;
+    }
+    let stride = [1, dims[0], dims[0] * dims[1]];
+    let axes = [0, 1, 2].map(|a| {
+        (0..dims[a])
+            .map(|i| points[3 * i * stride[a] + a])
+            .collect::<Vec<_>>()
+    });
+    let extent = (0..3)
+        .map(|a| axes[a].last().unwrap_or(&0.0) - axes[a].first().unwrap_or(&0.0))
+        .fold(0.0_f64, |m, e| m.max(e.abs()));
+    let tol = 1e-6 * extent.max(f64::MIN_POSITIVE);
+    let mut idx = 0;
+    for k in 0..dims[2] {
+        for j in 0..dims[1] {
+            for i in 0..dims[0] {
+                let p = &points[3 * idx..3 * idx + 3];
+                let expected = [axes[0][i], axes[1][j], axes[2][k]];
+                if p.iter().zip(expected).any(|(&p, e)| (p - e).abs() > tol) {
+                    return None;
+                }
+                idx += 1;
+            }
+        }
+    }
+    Some(axes)
+}
+
+fn first_inline_piece<P>(pieces: Vec<Piece<P>>) -> Result<P, Error> {
+    match pieces.into_iter().next() {
+        Some(Piece::Inline(piece)) => Ok(*piece),
+        Some(_) => Err(Error(String::from("Only inline pieces are supported"))),
+        None => Err(Error(String::from("Data set has no pieces"))),
+    }
+}
+
+/// Convert a structured VTK data set into a regular grid.
+///
+/// Rectilinear grids with non-uniformly spaced coordinates are resampled onto a uniform grid
+/// with the same number of points spanning the same bounds. Structured grids are supported only
+/// when their points form an axis aligned lattice, in which case they are treated as rectilinear
+/// grids. Curvilinear structured grids produce an error. Fields that cannot be added to the
+/// grid are reported in `warnings`.
+pub fn convert_vtk_to_grid(vtk: Vtk, warnings: &mut Vec<String>) -> Result<hdkrs::GridMesh, Error> {
+    match vtk.data {
+        DataSet::ImageData {
+            extent,
+            origin,
+            spacing,
+            pieces,
+            ..
+        } => {
+            let dims = extent.into_dims();
+            let piece = first_inline_piece(pieces)?;
+            let mut grid = hdkrs::GridMesh::new(
+                [origin[0] as f64, origin[1] as f64, origin[2] as f64],
+                [spacing[0] as f64, spacing[1] as f64, spacing[2] as f64],
+                [dims[0] as usize, dims[1] as usize, dims[2] as usize],
+            );
+            let copy = |data: &[f32], _: usize| data.to_vec();
+            add_fields(&mut grid, piece.data.point, false, copy, warnings);
+            add_fields(&mut grid, piece.data.cell, true, copy, warnings);
+            Ok(grid)
+        }
+        DataSet::RectilinearGrid { pieces, .. } => {
+            let piece = first_inline_piece(pieces)?;
+            let coords = [
+                to_f64_vec(piece.coords.x)?,
+                to_f64_vec(piece.coords.y)?,
+                to_f64_vec(piece.coords.z)?,
+            ];
+            rectilinear_to_grid(coords, piece.data, warnings)
+        }
+        DataSet::StructuredGrid { pieces, .. } => {
+            let piece = first_inline_piece(pieces)?;
+            let dims = piece.extent.clone().into_dims();
+            let dims = [dims[0] as usize, dims[1] as usize, dims[2] as usize];
+            let points = to_f64_vec(piece.points)?;
+            if points.is_empty() {
+                return Err(Error(String::from("Structured grid has no points")));
+            }
+            let coords = lattice_axes(&points, dims).ok_or_else(|| {
+                Error(String::from(
+                    "Curvilinear structured grids cannot be imported as volumes",
+                ))
+            })?;
+            rectilinear_to_grid(coords, piece.data, warnings)
+        }
+        _ => Err(Error(String::from("Not a structured data set"))),
+    }
+}
+
+/// Convert a regular grid into VTK image data.
+pub fn convert_grid_to_vtk_format(grid: &hdkrs::GridMesh) -> Vtk {
+    let mut data = Attributes {
+        point: Vec::new(),
+        cell: Vec::new(),
+    };
+    for field in grid.fields.iter() {
+        let elem = if field.tuple_size == 3 {
+            ElementType::Vectors
+        } else {
+            ElementType::Scalars {
+                num_comp: field.tuple_size as u32,
+                lookup_table: None,
+            }
+        };
+        let attrib = Attribute::DataArray(DataArray {
+            name: field.name.clone(),
+            elem,
+            data: IOBuffer::F32(field.data.clone()),
+        });
+        if field.cell {
+            data.cell.push(attrib);
+        } else {
+            data.point.push(attrib);
+        }
+    }
+
+    let extent = Extent::Dims([
+        grid.dims[0] as u32,
+        grid.dims[1] as u32,
+        grid.dims[2] as u32,
+    ]);
+    Vtk {
+        version: Version::new((1, 0)),
+        title: String::from("GridMesh"),
+        byte_order: ByteOrder::LittleEndian,
+        file_path: None,
+        data: DataSet::ImageData {
+            extent: extent.clone(),
+            origin: [
+                grid.origin[0] as f32,
+                grid.origin[1] as f32,
+                grid.origin[2] as f32,
+            ],
+            spacing: [
+                grid.spacing[0] as f32,
+                grid.spacing[1] as f32,
+                grid.spacing[2] as f32,
+            ],
+            meta: None,
+            pieces: vec![Piece::Inline(Box::new(ImageDataPiece { extent, data }))],
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rectilinear_point_data_is_interpolated() {
+        let axes = [
+            AxisResampling::new(&[0.0, 1.0, 4.0]).unwrap(),
+            AxisResampling::new(&[0.0]).unwrap(),
+            AxisResampling::new(&[0.0]).unwrap(),
+        ];
+        assert_eq!(axes[0].origin, 0.0);
+        assert_eq!(axes[0].spacing, 2.0);
+        // A linear function of x is reproduced at the uniform points 0, 2 and 4.
+        let data = [0.0, 1.0, 4.0];
+        assert_eq!(resample_points(&data, 1, &axes), vec![0.0, 2.0, 4.0]);
+        // Uniform cells centered at 1 and 3 both fall into the second source cell.
+        assert_eq!(axes[0].cells, vec![1, 1]);
+        assert_eq!(resample_cells(&[5.0, 7.0], 1, &axes), vec![7.0, 7.0]);
+    }
+
+    #[test]
+    fn structured_grid_lattice() {
+        let points = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 2.0, 0.0, 1.0, 2.0, 0.0];
+        let axes = lattice_axes(&points, [2, 2, 1]).unwrap();
+        assert_eq!(axes, [vec![0.0, 1.0], vec![0.0, 2.0], vec![0.0]]);
+
+        // Shearing the grid makes it curvilinear.
+        let mut sheared = points;
+        sheared[6] = 0.5;
+        assert!(lattice_axes(&sheared, [2, 2, 1]).is_none());
+    }
+}
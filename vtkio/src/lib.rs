@@ -1,9 +1,14 @@
+use std::io::Read;
 use std::pin::Pin;
 
 use meshx::io::vtk::*;
 use meshx::io::MeshExtractor;
 use meshx::mesh::topology::*;
-use model::Vtk;
+use model::{Attribute, DataArray, DataSet, Piece, Vtk};
+
+pub mod grid;
+pub mod polydata;
+pub mod pvd;
 
 #[cxx::bridge(namespace = "vtkio")]
 mod ffi {
@@ -12,7 +17,16 @@ mod ffi {
         include!("hdkrs/src/lib.rs.h");
         type GU_Detail = hdkrs::ffi::GU_Detail;
     }
+    #[namespace = "hdkrs"]
+    extern "C++" {
+        type IStreamReader = hdkrs::ffi::IStreamReader;
+        type VertexOrder = hdkrs::ffi::VertexOrder;
+        type CookResult = hdkrs::ffi::CookResult;
+        type CoordTransform = hdkrs::ffi::CoordTransform;
+    }
     extern "Rust" {
+        fn default_vertex_order() -> VertexOrder;
+
         fn polymesh_to_vtk_buffer(detail: &GU_Detail) -> Result<Vec<u8>>;
         fn polymesh_to_vtu_buffer(detail: &GU_Detail) -> Result<Vec<u8>>;
         fn polymesh_to_vtp_buffer(detail: &GU_Detail) -> Result<Vec<u8>>;
@@ -27,16 +41,64 @@ mod ffi {
         fn mesh_to_vtu_buffer(detail: &GU_Detail) -> Result<Vec<u8>>;
         fn mesh_to_vtk_buffer(detail: &GU_Detail) -> Result<Vec<u8>>;
 
+        fn grid_to_vti_buffer(detail: &GU_Detail) -> Result<Vec<u8>>;
+        fn grid_to_vti_buffer_with_transform(
+            detail: &GU_Detail,
+            transform: &CoordTransform,
+        ) -> Result<Vec<u8>>;
+
         fn add_vtp_mesh(detail: Pin<&mut GU_Detail>, data: &[u8]);
         fn add_vtu_mesh(detail: Pin<&mut GU_Detail>, data: &[u8]);
         fn add_vtk_mesh(detail: Pin<&mut GU_Detail>, data: &[u8]);
+        fn add_vtk_grid(detail: Pin<&mut GU_Detail>, data: &[u8]) -> CookResult;
+        fn add_vtp_mesh_from_stream(
+            detail: Pin<&mut GU_Detail>,
+            reader: Pin<&mut IStreamReader>,
+        ) -> CookResult;
+        fn add_vtu_mesh_from_stream(
+            detail: Pin<&mut GU_Detail>,
+            reader: Pin<&mut IStreamReader>,
+        ) -> CookResult;
+        fn add_vtk_mesh_from_stream(
+            detail: Pin<&mut GU_Detail>,
+            reader: Pin<&mut IStreamReader>,
+        ) -> CookResult;
+        fn add_vtk_grid_from_stream(
+            detail: Pin<&mut GU_Detail>,
+            reader: Pin<&mut IStreamReader>,
+        ) -> CookResult;
+        fn add_vtk_grid_from_stream_with_transform(
+            detail: Pin<&mut GU_Detail>,
+            reader: Pin<&mut IStreamReader>,
+            transform: &CoordTransform,
+        ) -> CookResult;
+
+        fn pvd_timesteps(path: &str) -> Result<Vec<f64>>;
+        fn add_pvd_mesh_at_index(
+            detail: Pin<&mut GU_Detail>,
+            path: &str,
+            index: usize,
+        ) -> CookResult;
+        fn add_pvd_mesh_at_time(detail: Pin<&mut GU_Detail>, path: &str, time: f64) -> CookResult;
+        fn write_pvd_frame(detail: &GU_Detail, path: &str, time: f64) -> Result<()>;
     }
 }
 
-use hdkrs::ffi::GU_Detail;
+use hdkrs::ffi::{
+    CookResult, CoordTransform, GU_Detail, IStreamReader, TransformDirection, VertexOrder,
+};
+use hdkrs::interop;
+
+/// Vertex order of polygons and tetrahedra in VTK files.
+pub const VERTEX_ORDER: VertexOrder = VertexOrder::STANDARD;
+
+/// Vertex order of VTK files used when no convention is given in the environment.
+pub fn default_vertex_order() -> VertexOrder {
+    VERTEX_ORDER
+}
 
 #[derive(Debug)]
-struct Error(String);
+pub struct Error(String);
 impl std::error::Error for Error {}
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -44,123 +106,260 @@ impl std::fmt::Display for Error {
     }
 }
 
+// Remove attributes reserved for internal use by `hdkrs`, such as source point indices of
+// compacted meshes, from all inline pieces of the given data set.
+fn strip_reserved_attribs(vtk: &mut Vtk) {
+    fn strip(attribs: &mut Vec<Attribute>) {
+        attribs.retain(|attrib| {
+            let name = match attrib {
+                Attribute::DataArray(DataArray { name, .. }) => name,
+                Attribute::Field { name, .. } => name,
+            };
+            !name.starts_with(hdkrs::RESERVED_ATTRIB_PREFIX)
+        });
+    }
+    macro_rules! strip_pieces {
+        ($pieces:expr) => {
+            for piece in $pieces.iter_mut() {
+                if let Piece::Inline(piece) = piece {
+                    strip(&mut piece.data.point);
+                    strip(&mut piece.data.cell);
+                }
+            }
+        };
+    }
+    match &mut vtk.data {
+        DataSet::ImageData { pieces, .. } => strip_pieces!(pieces),
+        DataSet::StructuredGrid { pieces, .. } => strip_pieces!(pieces),
+        DataSet::RectilinearGrid { pieces, .. } => strip_pieces!(pieces),
+        DataSet::UnstructuredGrid { pieces, .. } => strip_pieces!(pieces),
+        DataSet::PolyData { pieces, .. } => strip_pieces!(pieces),
+        DataSet::Field { .. } => {}
+    }
+}
+
 // Helper for writing legacy vtk to a byte buffer.
-fn write_legacy_vtk(vtk: Vtk) -> Vec<u8> {
+fn write_legacy_vtk(mut vtk: Vtk) -> Vec<u8> {
+    strip_reserved_attribs(&mut vtk);
     let mut vec_data = Vec::<u8>::new();
     vtk.write_legacy(&mut vec_data)
         .expect("Failed to write Vtk data to byte buffer");
     vec_data
 }
 // Helper for writing xml vtk to a byte buffer.
-fn write_xml_vtk(vtk: Vtk) -> Vec<u8> {
+fn write_xml_vtk(mut vtk: Vtk) -> Vec<u8> {
+    strip_reserved_attribs(&mut vtk);
     let mut vec_data = Vec::<u8>::new();
     vtk.write_xml(&mut vec_data)
         .expect("Failed to write Vtk data to byte buffer");
     vec_data
 }
 
-/// Extract a PolyMesh from the given detail and write it as a polygon mesh in XML VTK format
-/// returned through an appropriately sized `ByteBuffer`.
-pub fn polymesh_to_vtp_buffer(detail: &hdkrs::ffi::GU_Detail) -> Result<Vec<u8>, cxx::Exception> {
-    hdkrs::ffi::build_polymesh(detail).map(|mesh| {
-        convert_polymesh_to_vtk_format(&mesh.0, VTKPolyExportStyle::PolyData)
-            .map(write_xml_vtk)
-            .unwrap_or_else(|_| Default::default())
-    })
+/// Build a VTK data set from the polygons and open polylines in the given detail.
+///
+/// Points are written with the same precision as the positions stored in the detail.
+fn polygons_to_vtk(detail: &GU_Detail, style: VTKPolyExportStyle) -> Result<Vtk, Error> {
+    let polymesh = if hdkrs::ffi::has_f64_positions(detail) {
+        hdkrs::ffi::build_polymesh(detail)
+            .ok()
+            .and_then(|mesh| convert_polymesh_to_vtk_format(&mesh.0, style).ok())
+    } else {
+        hdkrs::ffi::build_polymesh_f32(detail)
+            .ok()
+            .and_then(|mesh| convert_polymesh_to_vtk_format(&mesh.0, style).ok())
+    };
+    with_polylines(detail, polymesh, style)
+}
+
+/// Add open polylines in the given detail to the VTK data set built from its other primitives.
+///
+/// If there are no other primitives, the data set is built from the polylines alone.
+fn with_polylines(
+    detail: &GU_Detail,
+    vtk: Option<Vtk>,
+    style: VTKPolyExportStyle,
+) -> Result<Vtk, Error> {
+    let lines = if hdkrs::ffi::has_f64_positions(detail) {
+        hdkrs::ffi::build_polylines(detail)
+            .ok()
+            .and_then(|mesh| polydata::convert_polylines_to_vtk_format(&mesh.0, style))
+    } else {
+        hdkrs::ffi::build_polylines_f32(detail)
+            .ok()
+            .and_then(|mesh| polydata::convert_polylines_to_vtk_format(&mesh.0, style))
+    };
+    match (vtk, lines) {
+        (Some(mut vtk), Some(lines)) => {
+            polydata::append_polylines(&mut vtk, lines)?;
+            Ok(vtk)
+        }
+        (Some(vtk), None) | (None, Some(vtk)) => Ok(vtk),
+        (None, None) => Err(Error(String::from("No polygons or polylines found"))),
+    }
+}
+
+/// Extract polygons and polylines from the given detail and write them as poly data in XML VTK
+/// format returned through an appropriately sized `ByteBuffer`.
+pub fn polymesh_to_vtp_buffer(detail: &hdkrs::ffi::GU_Detail) -> Result<Vec<u8>, Error> {
+    polygons_to_vtk(detail, VTKPolyExportStyle::PolyData).map(write_xml_vtk)
 }
-/// Extract a PolyMesh from the given detail and write it as an unstructured grid in XML VTK format
+/// Extract polygons and polylines from the given detail and write them as an unstructured grid
+/// in XML VTK format returned through an appropriately sized `ByteBuffer`.
+pub fn polymesh_to_vtu_buffer(detail: &hdkrs::ffi::GU_Detail) -> Result<Vec<u8>, Error> {
+    polygons_to_vtk(detail, VTKPolyExportStyle::UnstructuredGrid).map(write_xml_vtk)
+}
+/// Extract polygons and polylines from the given detail and write them into a binary VTK format
 /// returned through an appropriately sized `ByteBuffer`.
-pub fn polymesh_to_vtu_buffer(detail: &hdkrs::ffi::GU_Detail) -> Result<Vec<u8>, cxx::Exception> {
-    hdkrs::ffi::build_polymesh(detail).map(|mesh| {
-        convert_polymesh_to_vtk_format(&mesh.0, VTKPolyExportStyle::UnstructuredGrid)
-            .map(write_xml_vtk)
-            .unwrap_or_else(|_| Default::default())
-    })
+pub fn polymesh_to_vtk_buffer(detail: &hdkrs::ffi::GU_Detail) -> Result<Vec<u8>, Error> {
+    polygons_to_vtk(detail, VTKPolyExportStyle::PolyData).map(write_legacy_vtk)
 }
-/// Extract a PolyMesh from the given detail and write it into a binary VTK format returned through
-/// an appropriately sized `ByteBuffer`.
-pub fn polymesh_to_vtk_buffer(detail: &hdkrs::ffi::GU_Detail) -> Result<Vec<u8>, cxx::Exception> {
-    hdkrs::ffi::build_polymesh(detail).map(|mesh| {
-        convert_polymesh_to_vtk_format(&mesh.0, VTKPolyExportStyle::PolyData)
-            .map(write_legacy_vtk)
-            .unwrap_or_else(|_| Default::default())
+
+/// Extract a PointCloud from the given detail and convert it into a VTK data set.
+///
+/// Points are written with the same precision as the positions stored in the detail.
+fn pointcloud_to_vtk(
+    detail: &GU_Detail,
+    style: VTKPolyExportStyle,
+) -> Result<Option<Vtk>, cxx::Exception> {
+    Ok(if hdkrs::ffi::has_f64_positions(detail) {
+        hdkrs::ffi::build_pointcloud(detail)
+            .map(|mesh| convert_pointcloud_to_vtk_format(&mesh.0, style).ok())?
+    } else {
+        hdkrs::ffi::build_pointcloud_f32(detail)
+            .map(|mesh| convert_pointcloud_to_vtk_format(&mesh.0, style).ok())?
     })
 }
 
 /// Extract a PointCloud from the given detail and write it as a polygon mesh in
 /// XML VTK format returned through an appropriately sized `ByteBuffer`.
 pub fn pointcloud_to_vtp_buffer(detail: &hdkrs::ffi::GU_Detail) -> Result<Vec<u8>, cxx::Exception> {
-    hdkrs::ffi::build_pointcloud(detail).map(|mesh| {
-        convert_pointcloud_to_vtk_format(&mesh.0, VTKPolyExportStyle::PolyData)
-            .map(write_xml_vtk)
-            .unwrap_or_else(|_| Default::default())
-    })
+    pointcloud_to_vtk(detail, VTKPolyExportStyle::PolyData)
+        .map(|vtk| vtk.map(write_xml_vtk).unwrap_or_default())
 }
 /// Extract a PointCloud from the given detail and write it as an unstructured
 /// grid in XML VTK format returned through an appropriately sized `ByteBuffer`.
 pub fn pointcloud_to_vtu_buffer(detail: &hdkrs::ffi::GU_Detail) -> Result<Vec<u8>, cxx::Exception> {
-    hdkrs::ffi::build_pointcloud(detail).map(|mesh| {
-        convert_pointcloud_to_vtk_format(&mesh.0, VTKPolyExportStyle::UnstructuredGrid)
-            .map(write_xml_vtk)
-            .unwrap_or_else(|_| Default::default())
-    })
+    pointcloud_to_vtk(detail, VTKPolyExportStyle::UnstructuredGrid)
+        .map(|vtk| vtk.map(write_xml_vtk).unwrap_or_default())
 }
 /// Extract a PointCloud from the given detail and write it into a binary VTK
 /// format returned through an appropriately sized `ByteBuffer`.
 pub fn pointcloud_to_vtk_buffer(detail: &hdkrs::ffi::GU_Detail) -> Result<Vec<u8>, cxx::Exception> {
-    hdkrs::ffi::build_pointcloud(detail).map(|mesh| {
-        convert_pointcloud_to_vtk_format(&mesh.0, VTKPolyExportStyle::PolyData)
-            .map(write_legacy_vtk)
-            .unwrap_or_else(|_| Default::default())
+    pointcloud_to_vtk(detail, VTKPolyExportStyle::PolyData)
+        .map(|vtk| vtk.map(write_legacy_vtk).unwrap_or_default())
+}
+
+/// Extract a TetMesh without any loose points from the given detail and convert it into a VTK
+/// data set.
+///
+/// Points are written with the same precision as the positions stored in the detail.
+fn tetmesh_to_vtk(detail: &hdkrs::ffi::GU_Detail) -> Result<Option<Vtk>, cxx::Exception> {
+    let options = hdkrs::ffi::ExtractOptions {
+        compact_points: true,
+        ..Default::default()
+    };
+    Ok(if hdkrs::ffi::has_f64_positions(detail) {
+        hdkrs::ffi::build_tetmesh_with_options(detail, &options)
+            .map(|mesh| convert_tetmesh_to_vtk_format(&mesh.0).ok())?
+    } else {
+        hdkrs::ffi::build_tetmesh_f32_with_options(detail, &options)
+            .map(|mesh| convert_tetmesh_to_vtk_format(&mesh.0).ok())?
     })
 }
 
 /// Extract a TetMesh from the given detail and write it as an unstructured grid
 /// in XML VTK format returned through an appropriately sized `ByteBuffer`.
 pub fn tetmesh_to_vtu_buffer(detail: &hdkrs::ffi::GU_Detail) -> Result<Vec<u8>, cxx::Exception> {
-    hdkrs::ffi::build_tetmesh(detail).map(|mesh| {
-        convert_tetmesh_to_vtk_format(&mesh.0)
-            .map(write_xml_vtk)
-            .unwrap_or_else(|_| Default::default())
-    })
+    tetmesh_to_vtk(detail).map(|vtk| vtk.map(write_xml_vtk).unwrap_or_default())
 }
 
 /// Extract a TetMesh from the given detail and write it into a binary VTK
 /// format returned through an appropriately sized `ByteBuffer`.
 pub fn tetmesh_to_vtk_buffer(detail: &hdkrs::ffi::GU_Detail) -> Result<Vec<u8>, cxx::Exception> {
-    hdkrs::ffi::build_tetmesh(detail).map(|mesh| {
-        convert_tetmesh_to_vtk_format(&mesh.0)
-            .map(write_legacy_vtk)
-            .unwrap_or_else(|_| Default::default())
-    })
+    tetmesh_to_vtk(detail).map(|vtk| vtk.map(write_legacy_vtk).unwrap_or_default())
 }
 
-/// Extract an unstructured mesh from the given detail and write it as an unstructured grid
-/// in XML VTK format returned through an appropriately sized `ByteBuffer`.
-pub fn mesh_to_vtu_buffer(detail: &hdkrs::ffi::GU_Detail) -> Result<Vec<u8>, cxx::Exception> {
-    hdkrs::ffi::build_unstructured_mesh(detail).map(|mesh| {
-        convert_mesh_to_vtk_format(&mesh.0)
-            .map(write_xml_vtk)
-            .unwrap_or_else(|_| Default::default())
-    })
+/// Build a VTK unstructured grid from the cells and open polylines in the given detail.
+///
+/// Polygons with more than three vertices are split into triangles by ear clipping, which keeps
+/// concave faces intact. Points are written with the same precision as the positions stored in
+/// the detail.
+fn cells_to_vtk(detail: &GU_Detail) -> Result<Vtk, Error> {
+    let options = hdkrs::ffi::ExtractOptions {
+        triangulation: hdkrs::ffi::Triangulation::EarClip,
+        ..Default::default()
+    };
+    let mesh = if hdkrs::ffi::has_f64_positions(detail) {
+        hdkrs::ffi::build_unstructured_mesh_with_options(detail, &options)
+            .ok()
+            .and_then(|mesh| convert_mesh_to_vtk_format(&mesh.0).ok())
+    } else {
+        hdkrs::ffi::build_unstructured_mesh_f32_with_options(detail, &options)
+            .ok()
+            .and_then(|mesh| convert_mesh_to_vtk_format(&mesh.0).ok())
+    };
+    with_polylines(detail, mesh, VTKPolyExportStyle::UnstructuredGrid)
 }
 
-/// Extract an unstructured mesh from the given detail and write it into a binary VTK
-/// format returned through an appropriately sized `ByteBuffer`.
-pub fn mesh_to_vtk_buffer(detail: &hdkrs::ffi::GU_Detail) -> Result<Vec<u8>, cxx::Exception> {
-    hdkrs::ffi::build_unstructured_mesh(detail).map(|mesh| {
-        convert_mesh_to_vtk_format(&mesh.0)
-            .map(write_legacy_vtk)
-            .unwrap_or_else(|_| Default::default())
+/// Extract an unstructured mesh and polylines from the given detail and write them as an
+/// unstructured grid in XML VTK format returned through an appropriately sized `ByteBuffer`.
+pub fn mesh_to_vtu_buffer(detail: &hdkrs::ffi::GU_Detail) -> Result<Vec<u8>, Error> {
+    cells_to_vtk(detail).map(write_xml_vtk)
+}
+
+/// Extract an unstructured mesh and polylines from the given detail and write them into a
+/// binary VTK format returned through an appropriately sized `ByteBuffer`.
+pub fn mesh_to_vtk_buffer(detail: &hdkrs::ffi::GU_Detail) -> Result<Vec<u8>, Error> {
+    cells_to_vtk(detail).map(write_legacy_vtk)
+}
+
+/// Extract a regular grid from the volumes in the given detail and write it as image data in XML
+/// VTK format returned through an appropriately sized `ByteBuffer`.
+pub fn grid_to_vti_buffer(detail: &hdkrs::ffi::GU_Detail) -> Result<Vec<u8>, cxx::Exception> {
+    grid_to_vti_buffer_with_transform(detail, &CoordTransform::default())
+}
+
+/// Extract a regular grid from the volumes in the given detail and write it as image data in XML
+/// VTK format as in `grid_to_vti_buffer`, converting the grid into the given coordinate system.
+pub fn grid_to_vti_buffer_with_transform(
+    detail: &hdkrs::ffi::GU_Detail,
+    transform: &CoordTransform,
+) -> Result<Vec<u8>, cxx::Exception> {
+    hdkrs::ffi::build_grid(detail).map(|mut grid| {
+        grid.transform(transform, TransformDirection::Export);
+        write_xml_vtk(grid::convert_grid_to_vtk_format(&grid))
     })
 }
 
+/// Check if the points of the given data set are stored in single precision, in which case
+/// meshes are extracted with single precision positions.
+fn has_f32_points(vtk: &Vtk) -> bool {
+    let points = match &vtk.data {
+        DataSet::UnstructuredGrid { pieces, .. } => match pieces.first() {
+            Some(Piece::Inline(piece)) => &piece.points,
+            _ => return false,
+        },
+        DataSet::PolyData { pieces, .. } => match pieces.first() {
+            Some(Piece::Inline(piece)) => &piece.points,
+            _ => return false,
+        },
+        _ => return false,
+    };
+    matches!(points, model::IOBuffer::F32(_))
+}
+
 /// Helper to convert the given VTK data set into a valid `Mesh` type representing an unstructured
 /// mesh.
 ///
 /// In case of failure `None` is returned.
 fn convert_vtk_to_mesh(vtk: &Vtk) -> hdkrs::Mesh {
-    if let Ok(mesh) = vtk.extract_mesh() {
+    if has_f32_points(vtk) {
+        if let Ok(mesh) = MeshExtractor::<f32>::extract_mesh(vtk) {
+            if mesh.num_cells() > 0 {
+                return mesh.into();
+            }
+        }
+    } else if let Ok(mesh) = MeshExtractor::<f64>::extract_mesh(vtk) {
         if mesh.num_cells() > 0 {
             return mesh.into();
         }
@@ -172,7 +371,13 @@ fn convert_vtk_to_mesh(vtk: &Vtk) -> hdkrs::Mesh {
 ///
 /// In case of failure `None` is returned.
 fn convert_vtk_to_polymesh(vtk: &Vtk) -> hdkrs::Mesh {
-    if let Ok(mesh) = vtk.extract_polymesh() {
+    if has_f32_points(vtk) {
+        if let Ok(mesh) = MeshExtractor::<f32>::extract_polymesh(vtk) {
+            if mesh.num_faces() > 0 {
+                return mesh.into();
+            }
+        }
+    } else if let Ok(mesh) = MeshExtractor::<f64>::extract_polymesh(vtk) {
         if mesh.num_faces() > 0 {
             return mesh.into();
         }
@@ -184,12 +389,18 @@ fn convert_vtk_to_polymesh(vtk: &Vtk) -> hdkrs::Mesh {
 ///
 /// In case of failure `None` is returned.
 fn convert_vtk_to_pointcloud(vtk: &Vtk) -> hdkrs::Mesh {
-    vtk.extract_pointcloud().ok().into()
+    if has_f32_points(vtk) {
+        MeshExtractor::<f32>::extract_pointcloud(vtk).ok().into()
+    } else {
+        MeshExtractor::<f64>::extract_pointcloud(vtk).ok().into()
+    }
 }
 
-/// Parse a given byte array into a Mesh and add it to the given detail.
-pub fn add_vtp_mesh(detail: Pin<&mut GU_Detail>, data: &[u8]) {
-    parse_vtp_mesh(data).add_to_detail(detail);
+/// Parse a given byte array into meshes and add them to the given detail.
+pub fn add_vtp_mesh(mut detail: Pin<&mut GU_Detail>, data: &[u8]) {
+    for mesh in parse_vtp_meshes(data) {
+        mesh.add_to_detail(detail.as_mut());
+    }
 }
 
 /// Parse a given byte array into a TetMesh or a PolyMesh and add it to the given detail.
@@ -197,51 +408,360 @@ pub fn add_vtu_mesh(detail: Pin<&mut GU_Detail>, data: &[u8]) {
     parse_vtu_mesh(data).add_to_detail(detail);
 }
 
-/// Parse a given byte array into a TetMesh or a PolyMesh and add it to the given detail.
-pub fn add_vtk_mesh(detail: Pin<&mut GU_Detail>, data: &[u8]) {
-    parse_vtk_mesh(data).add_to_detail(detail);
+/// Parse a given byte array into meshes and add them to the given detail.
+pub fn add_vtk_mesh(mut detail: Pin<&mut GU_Detail>, data: &[u8]) {
+    for mesh in parse_vtk_meshes(data) {
+        mesh.add_to_detail(detail.as_mut());
+    }
+}
+
+/// Add the given loaded meshes to the detail, reporting load errors and warnings in the
+/// returned cook result.
+fn add_loaded_meshes(
+    mut detail: Pin<&mut GU_Detail>,
+    meshes: Result<Vec<hdkrs::Mesh>, Error>,
+    warnings: Vec<String>,
+) -> CookResult {
+    match meshes {
+        Ok(meshes) => {
+            for mesh in meshes {
+                mesh.add_to_detail(detail.as_mut());
+            }
+            if warnings.is_empty() {
+                interop::CookResult::Success(String::new())
+            } else {
+                interop::CookResult::Warning(warnings.join("\n"))
+            }
+        }
+        Err(err) => interop::CookResult::Error(format!("Failed to load VTK file: {}", err.0)),
+    }
+    .into()
+}
+
+/// Parse polygon VTK data read incrementally from the given stream and add the resulting meshes
+/// to the given detail.
+///
+/// Returns an error if the stream could not be read or parsed, including when the user
+/// interrupts the load.
+pub fn add_vtp_mesh_from_stream(
+    detail: Pin<&mut GU_Detail>,
+    reader: Pin<&mut IStreamReader>,
+) -> CookResult {
+    let reader = hdkrs::stream::StreamReader::new(reader);
+    let mut warnings = Vec::new();
+    let meshes = parse_vtp_meshes_from_reader(reader, &mut warnings);
+    add_loaded_meshes(detail, meshes, warnings)
+}
+
+/// Parse unstructured grid VTK data read incrementally from the given stream and add the
+/// resulting mesh to the given detail.
+///
+/// Returns an error if the stream could not be read or parsed, including when the user
+/// interrupts the load.
+pub fn add_vtu_mesh_from_stream(
+    detail: Pin<&mut GU_Detail>,
+    reader: Pin<&mut IStreamReader>,
+) -> CookResult {
+    let reader = hdkrs::stream::StreamReader::new(reader);
+    add_loaded_meshes(
+        detail,
+        parse_vtu_mesh_from_reader(reader).map(|mesh| vec![mesh]),
+        Vec::new(),
+    )
+}
+
+/// Parse legacy VTK data read incrementally from the given stream and add the resulting meshes
+/// to the given detail.
+///
+/// Returns an error if the stream could not be read or parsed, including when the user
+/// interrupts the load.
+pub fn add_vtk_mesh_from_stream(
+    detail: Pin<&mut GU_Detail>,
+    reader: Pin<&mut IStreamReader>,
+) -> CookResult {
+    let reader = hdkrs::stream::StreamReader::new(reader);
+    let mut warnings = Vec::new();
+    let meshes = parse_vtk_meshes_from_reader(reader, &mut warnings);
+    add_loaded_meshes(detail, meshes, warnings)
+}
+
+/// Parse a given byte array into a regular grid and add it to the given detail as volumes.
+///
+/// Fields that could not be loaded are reported as warnings in the returned cook result.
+pub fn add_vtk_grid(detail: Pin<&mut GU_Detail>, data: &[u8]) -> CookResult {
+    let mut warnings = Vec::new();
+    let grid = load_vtk_grid(data, &mut warnings).map(|grid| vec![grid.into()]);
+    add_loaded_meshes(detail, grid, warnings)
+}
+
+/// Parse a regular grid read incrementally from the given stream and add it to the given detail
+/// as volumes.
+///
+/// Returns an error if the stream could not be read or parsed, including when the user
+/// interrupts the load. Fields that could not be loaded are reported as warnings.
+pub fn add_vtk_grid_from_stream(
+    detail: Pin<&mut GU_Detail>,
+    reader: Pin<&mut IStreamReader>,
+) -> CookResult {
+    add_vtk_grid_from_stream_with_transform(detail, reader, &CoordTransform::default())
+}
+
+/// Parse a regular grid read incrementally from the given stream as in
+/// `add_vtk_grid_from_stream`, and add it to the given detail as volumes converted from the given
+/// coordinate system.
+///
+/// The grid is converted before it is added, so the volumes stay aligned with the axes.
+pub fn add_vtk_grid_from_stream_with_transform(
+    detail: Pin<&mut GU_Detail>,
+    reader: Pin<&mut IStreamReader>,
+    transform: &CoordTransform,
+) -> CookResult {
+    let reader = hdkrs::stream::StreamReader::new(reader);
+    let mut warnings = Vec::new();
+    let grid = load_vtk_grid_from_reader(reader, &mut warnings).map(|mut grid| {
+        grid.transform(transform, TransformDirection::Import);
+        vec![grid.into()]
+    });
+    add_loaded_meshes(detail, grid, warnings)
+}
+
+/// Parse a given byte array into a regular grid assuming XML image data, rectilinear grid or
+/// structured grid VTK format.
+///
+/// Fields that could not be loaded are reported in `warnings`.
+pub fn load_vtk_grid(data: &[u8], warnings: &mut Vec<String>) -> Result<hdkrs::GridMesh, Error> {
+    load_vtk_grid_from_reader(data, warnings)
+}
+
+/// Parse a regular grid from the given reader assuming XML image data, rectilinear grid or
+/// structured grid VTK format.
+///
+/// Fields that could not be loaded are reported in `warnings`.
+pub fn load_vtk_grid_from_reader(
+    reader: impl Read,
+    warnings: &mut Vec<String>,
+) -> Result<hdkrs::GridMesh, Error> {
+    let reader = hdkrs::stream::decompress(reader).map_err(|e| Error(e.to_string()))?;
+    let vtk = Vtk::parse_xml(reader).map_err(|e| Error(e.to_string()))?;
+    grid::convert_vtk_to_grid(vtk, warnings)
+}
+
+/// Parse a given byte array into a regular grid assuming XML image data, rectilinear grid or
+/// structured grid VTK format.
+///
+/// Returns `Mesh::None` if the grid could not be loaded.
+pub fn parse_vtk_grid(data: &[u8]) -> Box<hdkrs::Mesh> {
+    match load_vtk_grid(data, &mut Vec::new()) {
+        Ok(grid) => Box::new(grid.into()),
+        Err(_) => Box::new(hdkrs::Mesh::None),
+    }
 }
 
 /// Parse a given byte array into a PolyMesh depending on what is stored in the
 /// buffer assuming polygon VTK format.
 pub fn parse_vtp_mesh(data: &[u8]) -> Box<hdkrs::Mesh> {
-    if let Ok(vtk) = Vtk::parse_xml(data) {
-        if let Ok(mesh) = vtk.extract_polymesh() {
-            if mesh.num_faces() > 0 {
-                return Box::new(mesh.into());
-            }
-        }
-        if let Ok(mesh) = vtk.extract_pointcloud() {
-            return Box::new(mesh.into());
-        }
+    let data = match hdkrs::stream::decompress_bytes(data) {
+        Ok(data) => data,
+        Err(_) => return Box::new(hdkrs::Mesh::None),
+    };
+    if let Ok(vtk) = Vtk::parse_xml(&*data) {
+        return Box::new(convert_vtp_to_mesh(&vtk));
     }
     Box::new(hdkrs::Mesh::None)
 }
 
+/// Helper to convert the given polygon VTK data set into a PolyMesh, or a PointCloud if it has
+/// no polygons.
+fn convert_vtp_to_mesh(vtk: &Vtk) -> hdkrs::Mesh {
+    convert_vtk_to_polymesh(vtk).or_else(|| convert_vtk_to_pointcloud(vtk))
+}
+
+/// Helper to convert the given poly data set into polygon and polyline meshes, falling back to
+/// a single mesh as in `convert_vtp_to_mesh`.
+///
+/// Attributes that could not be loaded, and the reason for falling back, are reported in
+/// `warnings`.
+fn convert_polydata_to_meshes(
+    vtk: &Vtk,
+    fallback: fn(&Vtk) -> hdkrs::Mesh,
+    warnings: &mut Vec<String>,
+) -> Vec<hdkrs::Mesh> {
+    match polydata::convert_polydata(vtk, warnings) {
+        Some(Ok(meshes)) => return meshes,
+        Some(Err(err)) => warnings.push(err.0),
+        None => {}
+    }
+    vec![fallback(vtk)]
+}
+
+/// Parse a given byte array into meshes assuming polygon VTK format.
+///
+/// Poly data with vertex or line cells produces a polyline mesh in addition to the polygon mesh
+/// built from polygons and triangle strips, with each point of a vertex cell as a separate
+/// curve.
+pub fn parse_vtp_meshes(data: &[u8]) -> Vec<hdkrs::Mesh> {
+    parse_vtp_meshes_from_reader(data, &mut Vec::new()).unwrap_or_else(|_| vec![hdkrs::Mesh::None])
+}
+
+/// Parse polygon VTK data from the given reader into meshes as in `parse_vtp_meshes`.
+///
+/// Attributes that could not be loaded are reported in `warnings`.
+pub fn parse_vtp_meshes_from_reader(
+    reader: impl Read,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<hdkrs::Mesh>, Error> {
+    let reader = hdkrs::stream::decompress(reader).map_err(|e| Error(e.to_string()))?;
+    let vtk = Vtk::parse_xml(reader).map_err(|e| Error(e.to_string()))?;
+    Ok(convert_polydata_to_meshes(
+        &vtk,
+        convert_vtp_to_mesh,
+        warnings,
+    ))
+}
+
 /// Parse a given byte array into a TetMesh or a PolyMesh depending on what is stored in the
 /// buffer assuming unstructured grid VTK format.
 pub fn parse_vtu_mesh(data: &[u8]) -> Box<hdkrs::Mesh> {
-    if let Ok(vtk) = Vtk::parse_xml(data) {
-        return Box::new(
-            convert_vtk_to_mesh(&vtk)
-                .or_else(|| convert_vtk_to_polymesh(&vtk))
-                .or_else(|| convert_vtk_to_pointcloud(&vtk))
-                .or(hdkrs::Mesh::None),
-        );
-    }
-    Box::new(hdkrs::Mesh::None)
+    Box::new(parse_vtu_mesh_from_reader(data).unwrap_or(hdkrs::Mesh::None))
+}
+
+/// Parse unstructured grid VTK data from the given reader as in `parse_vtu_mesh`.
+pub fn parse_vtu_mesh_from_reader(reader: impl Read) -> Result<hdkrs::Mesh, Error> {
+    let reader = hdkrs::stream::decompress(reader).map_err(|e| Error(e.to_string()))?;
+    let vtk = Vtk::parse_xml(reader).map_err(|e| Error(e.to_string()))?;
+    Ok(convert_vtk_to_any_mesh(&vtk))
+}
+
+/// Helper to convert the given VTK data set into an unstructured mesh, a PolyMesh or a
+/// PointCloud, whichever is found first.
+fn convert_vtk_to_any_mesh(vtk: &Vtk) -> hdkrs::Mesh {
+    convert_vtk_to_mesh(vtk)
+        .or_else(|| convert_vtk_to_polymesh(vtk))
+        .or_else(|| convert_vtk_to_pointcloud(vtk))
+        .or(hdkrs::Mesh::None)
 }
 
 /// Parse a given byte array into a TetMesh or a PolyMesh depending on what is stored in the
 /// buffer assuming VTK format.
 pub fn parse_vtk_mesh(data: &[u8]) -> Box<hdkrs::Mesh> {
-    if let Ok(vtk) = Vtk::parse_legacy_be(data) {
-        return Box::new(
-            convert_vtk_to_mesh(&vtk)
-                .or_else(|| convert_vtk_to_polymesh(&vtk))
-                .or_else(|| convert_vtk_to_pointcloud(&vtk))
-                .or(hdkrs::Mesh::None),
-        );
+    let data = match hdkrs::stream::decompress_bytes(data) {
+        Ok(data) => data,
+        Err(_) => return Box::new(hdkrs::Mesh::None),
+    };
+    if let Ok(vtk) = Vtk::parse_legacy_be(&*data) {
+        return Box::new(convert_vtk_to_any_mesh(&vtk));
     }
     Box::new(hdkrs::Mesh::None)
 }
+
+/// Parse a given byte array into meshes assuming VTK format.
+///
+/// Poly data with vertex, line or triangle strip cells is split into polygon and polyline meshes.
+pub fn parse_vtk_meshes(data: &[u8]) -> Vec<hdkrs::Mesh> {
+    parse_vtk_meshes_from_reader(data, &mut Vec::new()).unwrap_or_else(|_| vec![hdkrs::Mesh::None])
+}
+
+/// Parse legacy VTK data from the given reader into meshes as in `parse_vtk_meshes`.
+///
+/// Attributes that could not be loaded are reported in `warnings`.
+pub fn parse_vtk_meshes_from_reader(
+    reader: impl Read,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<hdkrs::Mesh>, Error> {
+    let reader = hdkrs::stream::decompress(reader).map_err(|e| Error(e.to_string()))?;
+    let vtk = Vtk::parse_legacy_be(reader).map_err(|e| Error(e.to_string()))?;
+    Ok(convert_polydata_to_meshes(
+        &vtk,
+        convert_vtk_to_any_mesh,
+        warnings,
+    ))
+}
+
+/// Get the sorted list of time steps stored in the given `.pvd` collection file.
+pub fn pvd_timesteps(path: &str) -> Result<Vec<f64>, Error> {
+    pvd::Collection::load(path).map(|c| c.timesteps())
+}
+
+/// Load all parts of the time step at the given position in the sorted list of time steps of
+/// the given `.pvd` file and add them to the given detail.
+///
+/// Attributes that could not be loaded are reported as a warning in the returned cook result.
+pub fn add_pvd_mesh_at_index(detail: Pin<&mut GU_Detail>, path: &str, index: usize) -> CookResult {
+    let mut warnings = Vec::new();
+    let meshes = pvd::Collection::load(path).and_then(|collection| {
+        let timestep = *collection
+            .timesteps()
+            .get(index)
+            .ok_or_else(|| Error(format!("Time step index {} is out of range", index)))?;
+        pvd::load_timestep(
+            std::path::Path::new(path),
+            &collection,
+            timestep,
+            &mut warnings,
+        )
+    });
+    add_loaded_meshes(detail, meshes, warnings)
+}
+
+/// Load all parts at the given time from the given `.pvd` file and add them to the given detail.
+///
+/// Point positions are interpolated between the two closest time steps. Parts that cannot be
+/// interpolated are reported as a warning in the returned cook result.
+pub fn add_pvd_mesh_at_time(detail: Pin<&mut GU_Detail>, path: &str, time: f64) -> CookResult {
+    let mut warnings = Vec::new();
+    let meshes = pvd::Collection::load(path).and_then(|collection| {
+        pvd::load_time(std::path::Path::new(path), &collection, time, &mut warnings)
+    });
+    add_loaded_meshes(detail, meshes, warnings)
+}
+
+/// Write the given detail as an unstructured grid next to the given `.pvd` file and register it
+/// in the collection at the given time.
+///
+/// The collection file is created if it doesn't exist. New frames are written to
+/// `<name>_<index>.vtu`, where `<name>` is the stem of the `.pvd` file and `<index>` is the first
+/// index not used by another data set. Writing a frame at an existing time step overwrites the
+/// file of that time step.
+pub fn write_pvd_frame(detail: &GU_Detail, path: &str, time: f64) -> Result<(), Error> {
+    let pvd_path = std::path::Path::new(path);
+    let mut collection = if pvd_path.exists() {
+        pvd::Collection::load(pvd_path)?
+    } else {
+        pvd::Collection::default()
+    };
+
+    let buf = mesh_to_vtu_buffer(detail)
+        .or_else(|_| pointcloud_to_vtu_buffer(detail))
+        .map_err(|e| Error(format!("Failed to extract mesh: {}", e)))?;
+
+    let existing = collection
+        .datasets
+        .iter()
+        .find(|ds| ds.timestep == time && ds.part == 0)
+        .map(|ds| ds.file.clone());
+    let file = existing.unwrap_or_else(|| {
+        let stem = pvd_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("frame");
+        // Data sets are sorted by time, so their count doesn't give an unused name on its own.
+        (collection.datasets.len()..)
+            .map(|index| format!("{}_{:04}.vtu", stem, index))
+            .find(|file| collection.datasets.iter().all(|ds| &ds.file != file))
+            .unwrap()
+    });
+    let frame_path = pvd_path
+        .parent()
+        .map(|dir| dir.join(&file))
+        .unwrap_or_else(|| std::path::PathBuf::from(&file));
+    std::fs::write(&frame_path, buf)
+        .map_err(|e| Error(format!("Failed to write {:?}: {}", frame_path, e)))?;
+
+    collection.insert(pvd::DataSet {
+        timestep: time,
+        group: String::new(),
+        part: 0,
+        file,
+    });
+    collection.save(pvd_path)
+}
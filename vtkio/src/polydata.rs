@@ -0,0 +1,656 @@
+//!
+//! This module converts VTK poly data containing vertex, line or triangle strip cells.
+//!
+//! Polygons and triangle strips are imported as a polygon mesh, with strips expanded into
+//! triangles. Lines are imported as a polyline mesh, and each point of a vertex cell as a single
+//! point curve in the same mesh. Cell data is transferred to the polygons and curves generated
+//! from each cell.
+//!
+//! Polyline meshes are exported as lines in poly data or as poly line cells in unstructured
+//! grids.
+//!
+
+use hdkrs::ffi::AttribLocation;
+use meshx::io::vtk::model::*;
+use meshx::io::vtk::{convert_polymesh_to_vtk_format, VTKPolyExportStyle};
+
+use crate::Error;
+
+// Attribute values in one of the types supported by `hdkrs` meshes.
+enum Values {
+    I8(Vec<i8>),
+    I32(Vec<i32>),
+    I64(Vec<i64>),
+    F32(Vec<f32>),
+    F64(Vec<f64>),
+}
+
+impl Values {
+    fn from_buffer(buf: IOBuffer) -> Option<Values> {
+        match buf {
+            IOBuffer::Bit(_) => None,
+            IOBuffer::I8(v) => Some(Values::I8(v)),
+            IOBuffer::U8(_) | IOBuffer::I16(_) | IOBuffer::U16(_) | IOBuffer::I32(_) => {
+                buf.cast_into::<i32>().map(Values::I32)
+            }
+            IOBuffer::U32(_) | IOBuffer::I64(_) | IOBuffer::U64(_) => {
+                buf.cast_into::<i64>().map(Values::I64)
+            }
+            IOBuffer::F32(v) => Some(Values::F32(v)),
+            IOBuffer::F64(v) => Some(Values::F64(v)),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Values::I8(v) => v.len(),
+            Values::I32(v) => v.len(),
+            Values::I64(v) => v.len(),
+            Values::F32(v) => v.len(),
+            Values::F64(v) => v.len(),
+        }
+    }
+
+    /// Collect the tuples at the given indices.
+    fn gather(&self, tuple_size: usize, indices: &[usize]) -> Values {
+        fn gather_vec<T: Copy>(v: &[T], n: usize, indices: &[usize]) -> Vec<T> {
+            indices
+                .iter()
+                .flat_map(|&i| v[i * n..(i + 1) * n].iter().copied())
+                .collect()
+        }
+        match self {
+            Values::I8(v) => Values::I8(gather_vec(v, tuple_size, indices)),
+            Values::I32(v) => Values::I32(gather_vec(v, tuple_size, indices)),
+            Values::I64(v) => Values::I64(gather_vec(v, tuple_size, indices)),
+            Values::F32(v) => Values::F32(gather_vec(v, tuple_size, indices)),
+            Values::F64(v) => Values::F64(gather_vec(v, tuple_size, indices)),
+        }
+    }
+}
+
+struct Field {
+    name: String,
+    tuple_size: usize,
+    values: Values,
+}
+
+// Collect the data arrays with the expected number of tuples, reporting others in `warnings`.
+fn fields(attribs: Vec<Attribute>, num_elements: usize, warnings: &mut Vec<String>) -> Vec<Field> {
+    let mut fields = Vec::new();
+    for attrib in attribs {
+        if let Attribute::DataArray(DataArray { name, elem, data }) = attrib {
+            let tuple_size = crate::grid::num_comp(&elem);
+            if let Some(values) = Values::from_buffer(data) {
+                if values.len() == num_elements * tuple_size {
+                    fields.push(Field {
+                        name,
+                        tuple_size,
+                        values,
+                    });
+                } else {
+                    warnings.push(format!(
+                        "Skipping attribute \"{}\" with unexpected size",
+                        name
+                    ));
+                }
+            }
+        }
+    }
+    fields
+}
+
+macro_rules! add_fields {
+    ($mesh:expr, $loc:expr, $fields:expr, $indices:expr) => {
+        for field in $fields.iter() {
+            match field.values.gather(field.tuple_size, $indices) {
+                Values::I8(v) => $mesh.add_attrib_i8($loc, &field.name, field.tuple_size, &v),
+                Values::I32(v) => $mesh.add_attrib_i32($loc, &field.name, field.tuple_size, &v),
+                Values::I64(v) => $mesh.add_attrib_i64($loc, &field.name, field.tuple_size, &v),
+                Values::F32(v) => $mesh.add_attrib_f32($loc, &field.name, field.tuple_size, &v),
+                Values::F64(v) => $mesh.add_attrib_f64($loc, &field.name, field.tuple_size, &v),
+            }
+        }
+    };
+}
+
+// Split cells into lists of point indices.
+fn cell_list(cells: Option<VertexNumbers>) -> Vec<Vec<usize>> {
+    let (connectivity, offsets) = match cells {
+        Some(cells) => cells.into_xml(),
+        None => return Vec::new(),
+    };
+    let mut begin = 0;
+    offsets
+        .iter()
+        .map(|&end| {
+            let cell = connectivity[begin..end as usize]
+                .iter()
+                .map(|&i| i as usize)
+                .collect();
+            begin = end as usize;
+            cell
+        })
+        .collect()
+}
+
+// Polygons or curves generated from the cells of a poly data piece.
+#[derive(Default)]
+struct Part {
+    // Polygons or curves as lists of point indices.
+    faces: Vec<Vec<usize>>,
+    // Index of the source cell for each face.
+    source_cells: Vec<usize>,
+}
+
+impl Part {
+    fn push(&mut self, face: Vec<usize>, cell: usize) {
+        self.faces.push(face);
+        self.source_cells.push(cell);
+    }
+
+    /// Compute point coordinates and indices of this part over the points it references.
+    ///
+    /// Also returns the original index of each referenced point.
+    fn compact<T: Copy>(&self, points: &[T]) -> (Vec<T>, Vec<usize>, Vec<usize>) {
+        let mut new_index = vec![None; points.len() / 3];
+        let mut orig_points = Vec::new();
+        let mut indices = Vec::new();
+        for face in self.faces.iter() {
+            indices.push(face.len());
+            for &pt in face.iter() {
+                let idx = *new_index[pt].get_or_insert_with(|| {
+                    orig_points.push(pt);
+                    orig_points.len() - 1
+                });
+                indices.push(idx);
+            }
+        }
+        let coords = orig_points
+            .iter()
+            .flat_map(|&pt| points[3 * pt..3 * pt + 3].iter().copied())
+            .collect();
+        (coords, indices, orig_points)
+    }
+}
+
+/// Convert VTK poly data with vertex, line or triangle strip cells into `hdkrs` meshes.
+///
+/// Returns `None` if the data set is not poly data or contains only polygons, in which case the
+/// regular polygon mesh or point cloud extraction applies. Attributes that cannot be transferred
+/// are reported in `warnings`.
+pub fn convert_polydata(
+    vtk: &Vtk,
+    warnings: &mut Vec<String>,
+) -> Option<Result<Vec<hdkrs::Mesh>, Error>> {
+    let piece = match &vtk.data {
+        DataSet::PolyData { pieces, .. } => match pieces.first() {
+            Some(Piece::Inline(piece)) => piece,
+            _ => return None,
+        },
+        _ => return None,
+    };
+    if piece.verts.is_none() && piece.lines.is_none() && piece.strips.is_none() {
+        return None;
+    }
+    Some(convert_polydata_piece(piece.as_ref().clone(), warnings))
+}
+
+// Build a polygon mesh and a polyline mesh from the given parts over `points`, skipping empty
+// parts.
+macro_rules! build_meshes {
+    ($points:expr, $make_polymesh:path, $make_polylines:path,
+     $surface:ident, $curves:ident, $point_fields:ident, $cell_fields:ident) => {{
+        let mut meshes = Vec::new();
+        if !$surface.faces.is_empty() {
+            let (coords, indices, orig_points) = $surface.compact($points);
+            let mut mesh = $make_polymesh(&coords, &indices);
+            add_fields!(mesh, AttribLocation::VERTEX, $point_fields, &orig_points);
+            add_fields!(
+                mesh,
+                AttribLocation::FACE,
+                $cell_fields,
+                &$surface.source_cells
+            );
+            meshes.push(hdkrs::Mesh::from(*mesh));
+        }
+        if !$curves.faces.is_empty() {
+            let (coords, indices, orig_points) = $curves.compact($points);
+            let mut mesh = $make_polylines(&coords, &indices);
+            add_fields!(mesh, AttribLocation::VERTEX, $point_fields, &orig_points);
+            add_fields!(
+                mesh,
+                AttribLocation::CURVE,
+                $cell_fields,
+                &$curves.source_cells
+            );
+            meshes.push(hdkrs::Mesh::from(*mesh));
+        }
+        meshes
+    }};
+}
+
+fn convert_polydata_piece(
+    piece: PolyDataPiece,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<hdkrs::Mesh>, Error> {
+    let num_points = piece.points.len() / 3;
+
+    // Cell data is ordered by vertices, lines, polygons and then strips.
+    let verts = cell_list(piece.verts);
+    let lines = cell_list(piece.lines);
+    let polys = cell_list(piece.polys);
+    let strips = cell_list(piece.strips);
+    let num_cells = verts.len() + lines.len() + polys.len() + strips.len();
+
+    if verts
+        .iter()
+        .chain(lines.iter())
+        .chain(polys.iter())
+        .chain(strips.iter())
+        .flatten()
+        .any(|&pt| pt >= num_points)
+    {
+        return Err(Error(String::from("Cell references a missing point")));
+    }
+
+    let mut curves = Part::default();
+    let mut surface = Part::default();
+    let mut cell = 0;
+    for vert in verts.into_iter() {
+        // Poly vertex cells hold any number of unconnected points.
+        for pt in vert {
+            curves.push(vec![pt], cell);
+        }
+        cell += 1;
+    }
+    for line in lines.into_iter() {
+        curves.push(line, cell);
+        cell += 1;
+    }
+    for poly in polys.into_iter() {
+        surface.push(poly, cell);
+        cell += 1;
+    }
+    for strip in strips.into_iter() {
+        // Alternate the orientation of consecutive triangles to keep a consistent winding.
+        for i in 0..strip.len().saturating_sub(2) {
+            let tri = if i % 2 == 0 {
+                vec![strip[i], strip[i + 1], strip[i + 2]]
+            } else {
+                vec![strip[i + 1], strip[i], strip[i + 2]]
+            };
+            surface.push(tri, cell);
+        }
+        cell += 1;
+    }
+
+    let point_fields = fields(piece.data.point, num_points, warnings);
+    let cell_fields = fields(piece.data.cell, num_cells, warnings);
+
+    // Single precision points are kept in single precision meshes.
+    match piece.points {
+        IOBuffer::F32(points) => Ok(build_meshes!(
+            &points,
+            hdkrs::make_polymesh_f32,
+            hdkrs::make_polylines_f32,
+            surface,
+            curves,
+            point_fields,
+            cell_fields
+        )),
+        points => {
+            let points = points
+                .cast_into::<f64>()
+                .ok_or_else(|| Error(String::from("Unsupported point coordinate type")))?;
+            Ok(build_meshes!(
+                &points,
+                hdkrs::make_polymesh,
+                hdkrs::make_polylines,
+                surface,
+                curves,
+                point_fields,
+                cell_fields
+            ))
+        }
+    }
+}
+
+macro_rules! impl_buffer_ops {
+    ($($variant:ident),*) => {
+        // Create a buffer of the same type as the given buffer filled with `len` zeros.
+        fn zeros_like(buf: &IOBuffer, len: usize) -> IOBuffer {
+            match buf {
+                $(IOBuffer::$variant(_) => IOBuffer::$variant(vec![Default::default(); len]),)*
+            }
+        }
+
+        // Concatenate two buffers of the same type.
+        fn concat_buffers(a: IOBuffer, b: IOBuffer) -> Option<IOBuffer> {
+            match (a, b) {
+                $((IOBuffer::$variant(mut a), IOBuffer::$variant(b)) => {
+                    a.extend(b);
+                    Some(IOBuffer::$variant(a))
+                })*
+                _ => None,
+            }
+        }
+    };
+}
+
+impl_buffer_ops!(Bit, U8, I8, U16, I16, U32, I32, U64, I64, F32, F64);
+
+// Merge point or cell data of two consecutive sets of points or cells.
+//
+// Arrays missing from one of the sets, or stored with a different type, are padded with zeros.
+fn merge_data(
+    first: Vec<Attribute>,
+    num_first: usize,
+    mut second: Vec<Attribute>,
+    num_second: usize,
+) -> Vec<Attribute> {
+    let mut merged = Vec::new();
+    for attrib in first {
+        if let Attribute::DataArray(DataArray { name, elem, data }) = attrib {
+            let n = crate::grid::num_comp(&elem);
+            let other = second
+                .iter()
+                .position(|a| matches!(a, Attribute::DataArray(d) if d.name == name))
+                .map(|i| second.remove(i));
+            let other_data = match other {
+                Some(Attribute::DataArray(other))
+                    if crate::grid::num_comp(&other.elem) == n
+                        && std::mem::discriminant(&other.data) == std::mem::discriminant(&data) =>
+                {
+                    other.data
+                }
+                _ => zeros_like(&data, n * num_second),
+            };
+            if let Some(data) = concat_buffers(data, other_data) {
+                merged.push(Attribute::DataArray(DataArray { name, elem, data }));
+            }
+        }
+    }
+    for attrib in second {
+        if let Attribute::DataArray(DataArray { name, elem, data }) = attrib {
+            let n = crate::grid::num_comp(&elem);
+            if let Some(data) = concat_buffers(zeros_like(&data, n * num_first), data) {
+                merged.push(Attribute::DataArray(DataArray { name, elem, data }));
+            }
+        }
+    }
+    merged
+}
+
+// Shift the point indices referenced by the given cells.
+fn shift_cells(cells: VertexNumbers, num_points: u64) -> VertexNumbers {
+    let (connectivity, offsets) = cells.into_xml();
+    VertexNumbers::XML {
+        connectivity: connectivity.into_iter().map(|i| i + num_points).collect(),
+        offsets,
+    }
+}
+
+// Concatenate two lists of cells.
+fn concat_cells(a: VertexNumbers, b: VertexNumbers) -> VertexNumbers {
+    let (mut connectivity, mut offsets) = a.into_xml();
+    let (b_connectivity, b_offsets) = b.into_xml();
+    let shift = connectivity.len() as u64;
+    connectivity.extend(b_connectivity);
+    offsets.extend(b_offsets.into_iter().map(|o| o + shift));
+    VertexNumbers::XML {
+        connectivity,
+        offsets,
+    }
+}
+
+// Concatenate two point coordinate buffers, promoting them to `f64` if their types differ.
+fn concat_points(a: IOBuffer, b: IOBuffer) -> Option<IOBuffer> {
+    if std::mem::discriminant(&a) == std::mem::discriminant(&b) {
+        return concat_buffers(a, b);
+    }
+    let mut a = a.cast_into::<f64>()?;
+    a.extend(b.cast_into::<f64>()?);
+    Some(IOBuffer::F64(a))
+}
+
+// Append the points and point data of `other` to `points` and `data`, unless both sets of
+// points are identical, in which case they are shared.
+//
+// Returns the offset to add to point indices referring to `other`.
+fn append_points(
+    points: &mut IOBuffer,
+    data: &mut Vec<Attribute>,
+    other: IOBuffer,
+    other_data: Vec<Attribute>,
+) -> Option<u64> {
+    if *points == other {
+        return Some(0);
+    }
+    let num_points = points.len() / 3;
+    let num_other = other.len() / 3;
+    let first = std::mem::replace(points, IOBuffer::F64(Vec::new()));
+    *points = concat_points(first, other)?;
+    *data = merge_data(std::mem::take(data), num_points, other_data, num_other);
+    Some(num_points as u64)
+}
+
+fn inline_piece_mut<P>(pieces: &mut [Piece<P>]) -> Option<&mut P> {
+    match pieces.first_mut() {
+        Some(Piece::Inline(piece)) => Some(piece.as_mut()),
+        _ => None,
+    }
+}
+
+fn into_inline_piece<P>(pieces: Vec<Piece<P>>) -> Option<P> {
+    match pieces.into_iter().next() {
+        Some(Piece::Inline(piece)) => Some(*piece),
+        _ => None,
+    }
+}
+
+/// Convert the polygons of a polyline mesh into VTK curves.
+///
+/// Curves are written as lines in poly data, or as poly line cells in an unstructured grid.
+pub fn convert_polylines_to_vtk_format<T: meshx::Real>(
+    polylines: &meshx::mesh::PolyMesh<T>,
+    style: VTKPolyExportStyle,
+) -> Option<Vtk> {
+    let mut vtk = convert_polymesh_to_vtk_format(polylines, style).ok()?;
+    match &mut vtk.data {
+        DataSet::PolyData { pieces, .. } => {
+            let piece = inline_piece_mut(pieces)?;
+            piece.lines = piece.polys.take();
+        }
+        DataSet::UnstructuredGrid { pieces, .. } => {
+            let piece = inline_piece_mut(pieces)?;
+            for cell_type in piece.cells.types.iter_mut() {
+                *cell_type = CellType::PolyLine;
+            }
+        }
+        _ => return None,
+    }
+    Some(vtk)
+}
+
+/// Add the curves of `lines`, produced by `convert_polylines_to_vtk_format`, to the given VTK
+/// data set.
+///
+/// Points are shared if both data sets store the same points, as when they are extracted from
+/// the same detail without compaction. Otherwise the points of `lines` are appended after the
+/// points of `vtk`, promoting coordinates to `f64` if the two are stored with different
+/// precision.
+pub fn append_polylines(vtk: &mut Vtk, lines: Vtk) -> Result<(), Error> {
+    let mismatch = || {
+        Error(String::from(
+            "Cannot combine polylines with the given data set",
+        ))
+    };
+    match (&mut vtk.data, lines.data) {
+        (DataSet::PolyData { pieces, .. }, DataSet::PolyData { pieces: lines, .. }) => {
+            let piece = inline_piece_mut(pieces).ok_or_else(mismatch)?;
+            let lines = into_inline_piece(lines).ok_or_else(mismatch)?;
+            let num_lines = lines.lines.as_ref().map_or(0, |l| l.num_cells());
+            // VTK orders cell data by vertices, lines, polygons and strips.
+            let num_before = piece.verts.as_ref().map_or(0, |v| v.num_cells());
+            let num_after = piece.polys.as_ref().map_or(0, |p| p.num_cells())
+                + piece.strips.as_ref().map_or(0, |s| s.num_cells());
+            if num_before > 0 || piece.lines.is_some() {
+                return Err(mismatch());
+            }
+            let shift = append_points(
+                &mut piece.points,
+                &mut piece.data.point,
+                lines.points,
+                lines.data.point,
+            )
+            .ok_or_else(mismatch)?;
+            let cell = std::mem::take(&mut piece.data.cell);
+            piece.data.cell = merge_data(lines.data.cell, num_lines, cell, num_after);
+            piece.lines = lines.lines.map(|lines| shift_cells(lines, shift));
+            Ok(())
+        }
+        (
+            DataSet::UnstructuredGrid { pieces, .. },
+            DataSet::UnstructuredGrid { pieces: lines, .. },
+        ) => {
+            let piece = inline_piece_mut(pieces).ok_or_else(mismatch)?;
+            let lines = into_inline_piece(lines).ok_or_else(mismatch)?;
+            let num_cells = piece.cells.types.len();
+            let num_lines = lines.cells.types.len();
+            let shift = append_points(
+                &mut piece.points,
+                &mut piece.data.point,
+                lines.points,
+                lines.data.point,
+            )
+            .ok_or_else(mismatch)?;
+            let cell_verts = std::mem::replace(
+                &mut piece.cells.cell_verts,
+                VertexNumbers::XML {
+                    connectivity: Vec::new(),
+                    offsets: Vec::new(),
+                },
+            );
+            let line_verts = shift_cells(lines.cells.cell_verts, shift);
+            piece.cells.cell_verts = concat_cells(cell_verts, line_verts);
+            piece.cells.types.extend(lines.cells.types);
+            let cell = std::mem::take(&mut piece.data.cell);
+            piece.data.cell = merge_data(cell, num_cells, lines.data.cell, num_lines);
+            Ok(())
+        }
+        _ => Err(mismatch()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn polyline_points_are_appended_unless_shared() {
+        let mut points = IOBuffer::F64(vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0]);
+        let mut data = Vec::new();
+        let shared = points.clone();
+        assert_eq!(
+            append_points(&mut points, &mut data, shared, Vec::new()),
+            Some(0)
+        );
+        assert_eq!(points.len(), 6);
+
+        // Compacted single precision points are appended and promoted to double precision.
+        let lines = IOBuffer::F32(vec![0.0, 1.0, 0.0]);
+        assert_eq!(
+            append_points(&mut points, &mut data, lines, Vec::new()),
+            Some(2)
+        );
+        assert_eq!(
+            points,
+            IOBuffer::F64(vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0])
+        );
+
+        let cells = VertexNumbers::XML {
+            connectivity: vec![0, 1],
+            offsets: vec![2],
+        };
+        assert_eq!(shift_cells(cells, 2).into_xml(), (vec![2, 3], vec![2]));
+    }
+
+    #[test]
+    fn poly_vertex_cells_are_split_into_points() {
+        use meshx::attrib::Attrib;
+        use meshx::mesh::topology::FaceIndex;
+
+        let piece = PolyDataPiece {
+            points: IOBuffer::F64(vec![0.0; 9]),
+            verts: Some(VertexNumbers::XML {
+                connectivity: vec![0, 1],
+                offsets: vec![2],
+            }),
+            lines: Some(VertexNumbers::XML {
+                connectivity: vec![1, 2],
+                offsets: vec![2],
+            }),
+            data: Attributes {
+                point: Vec::new(),
+                cell: vec![Attribute::scalars("id", 1).with_data(vec![7i32, 8])],
+            },
+            ..Default::default()
+        };
+        let mut warnings = Vec::new();
+        let meshes = convert_polydata_piece(piece, &mut warnings).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(meshes.len(), 1);
+        match &meshes[0] {
+            hdkrs::Mesh::PolylineMesh(mesh) => {
+                assert_eq!(mesh.get_indices(), vec![1, 0, 1, 1, 2, 1, 2]);
+                // The cell data of the poly vertex cell is shared by both of its points.
+                assert_eq!(
+                    mesh.0.attrib_as_slice::<i32, FaceIndex>("id").unwrap(),
+                    &[7, 7, 8]
+                );
+            }
+            _ => panic!("Expected a polyline mesh"),
+        }
+    }
+
+    #[test]
+    fn single_precision_points_are_kept() {
+        let coords = vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+        let piece = PolyDataPiece {
+            points: IOBuffer::F32(coords.clone()),
+            lines: Some(VertexNumbers::XML {
+                connectivity: vec![0, 1, 2],
+                offsets: vec![3],
+            }),
+            ..Default::default()
+        };
+        let meshes = convert_polydata_piece(piece, &mut Vec::new()).unwrap();
+        match &meshes[..] {
+            [hdkrs::Mesh::PolylineMeshF32(mesh)] => assert_eq!(mesh.point_coords(), &coords[..]),
+            _ => panic!("Expected a single precision polyline mesh"),
+        }
+    }
+
+    #[test]
+    fn double_precision_points_far_from_origin_are_kept() {
+        let (a, b) = (1.0e6, 1.0e6 + 0.1);
+        let coords = vec![a, a, 0.0, b, a, 0.0, a, b, 0.0];
+        let piece = PolyDataPiece {
+            points: IOBuffer::F64(coords.clone()),
+            lines: Some(VertexNumbers::XML {
+                connectivity: vec![0, 1, 2],
+                offsets: vec![3],
+            }),
+            ..Default::default()
+        };
+        let meshes = convert_polydata_piece(piece, &mut Vec::new()).unwrap();
+        match &meshes[..] {
+            [mesh @ hdkrs::Mesh::PolylineMesh(_)] => {
+                assert_eq!(mesh.point_coords(), &coords[..]);
+                assert!(mesh.needs_f64_positions());
+            }
+            _ => panic!("Expected a double precision polyline mesh"),
+        }
+    }
+}
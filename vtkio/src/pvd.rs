@@ -0,0 +1,539 @@
+//!
+//! This module implements reading and writing of ParaView data (`.pvd`) collections.
+//!
+//! A `.pvd` file is a small XML index listing one VTK file per time step (and optionally per
+//! part), which is how most solvers export simulation sequences.
+//!
+
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+use meshx::io::vtk::model::{DataSet as VtkDataSet, Piece, Vtk};
+use meshx::mesh::VertexPositions;
+
+use crate::Error;
+
+/// A single entry in a `.pvd` collection.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DataSet {
+    pub timestep: f64,
+    pub group: String,
+    pub part: u32,
+    /// Path to the data file relative to the `.pvd` file.
+    pub file: String,
+}
+
+/// A collection of VTK files indexed by time step.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Collection {
+    pub datasets: Vec<DataSet>,
+}
+
+// Extract the value of the given XML attribute from the inside of a tag.
+fn xml_attribute<'a>(tag: &'a str, key: &str) -> Option<&'a str> {
+    let mut rest = tag;
+    while let Some(pos) = rest.find(key) {
+        let preceded_by_space = rest[..pos]
+            .chars()
+            .last()
+            .map_or(false, char::is_whitespace);
+        let after = rest[pos + key.len()..].trim_start();
+        if preceded_by_space && after.starts_with('=') {
+            let value = after[1..].trim_start();
+            let quote = value.chars().next()?;
+            if quote != '"' && quote != '\'' {
+                return None;
+            }
+            let end = value[1..].find(quote)?;
+            return Some(&value[1..end + 1]);
+        }
+        rest = &rest[pos + key.len()..];
+    }
+    None
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn unescape_xml(s: &str) -> String {
+    s.replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+impl Collection {
+    /// Parse a collection from the contents of a `.pvd` file.
+    pub fn parse(text: &str) -> Result<Collection, Error> {
+        if !text.contains("Collection") {
+            return Err(Error(String::from("Not a VTK collection file")));
+        }
+
+        let mut datasets = Vec::new();
+        let mut rest = text;
+        while let Some(start) = rest.find("<DataSet") {
+            let end = rest[start..]
+                .find('>')
+                .ok_or_else(|| Error(String::from("Unterminated DataSet tag")))?;
+            let tag = &rest[start + "<DataSet".len()..start + end];
+            let file = xml_attribute(tag, "file")
+                .ok_or_else(|| Error(String::from("DataSet is missing a file attribute")))?;
+            let timestep = match xml_attribute(tag, "timestep") {
+                Some(t) => t
+                    .trim()
+                    .parse::<f64>()
+                    .map_err(|_| Error(format!("Invalid timestep: {}", t)))?,
+                None => 0.0,
+            };
+            let part = match xml_attribute(tag, "part") {
+                Some(p) => p
+                    .trim()
+                    .parse::<u32>()
+                    .map_err(|_| Error(format!("Invalid part: {}", p)))?,
+                None => 0,
+            };
+            datasets.push(DataSet {
+                timestep,
+                group: xml_attribute(tag, "group")
+                    .map(unescape_xml)
+                    .unwrap_or_default(),
+                part,
+                file: unescape_xml(file),
+            });
+            rest = &rest[start + end..];
+        }
+
+        Ok(Collection { datasets })
+    }
+
+    /// Read a collection from the given `.pvd` file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Collection, Error> {
+        let text = std::fs::read_to_string(path.as_ref())
+            .map_err(|e| Error(format!("Failed to read {:?}: {}", path.as_ref(), e)))?;
+        Collection::parse(&text)
+    }
+
+    /// Write this collection in `.pvd` format.
+    pub fn write(&self) -> String {
+        let mut out = String::from(
+            "<?xml version=\"1.0\"?>\n\
+             <VTKFile type=\"Collection\" version=\"0.1\" byte_order=\"LittleEndian\">\n  \
+             <Collection>\n",
+        );
+        for ds in self.datasets.iter() {
+            out.push_str(&format!(
+                "    <DataSet timestep=\"{}\" group=\"{}\" part=\"{}\" file=\"{}\"/>\n",
+                ds.timestep,
+                escape_xml(&ds.group),
+                ds.part,
+                escape_xml(&ds.file)
+            ));
+        }
+        out.push_str("  </Collection>\n</VTKFile>\n");
+        out
+    }
+
+    /// Save this collection into the given `.pvd` file.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), Error> {
+        std::fs::write(path.as_ref(), self.write())
+            .map_err(|e| Error(format!("Failed to write {:?}: {}", path.as_ref(), e)))
+    }
+
+    /// Sorted list of unique time steps in this collection.
+    pub fn timesteps(&self) -> Vec<f64> {
+        let mut timesteps: Vec<f64> = self.datasets.iter().map(|ds| ds.timestep).collect();
+        timesteps.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        timesteps.dedup();
+        timesteps
+    }
+
+    /// All data sets (one per part) at exactly the given time step.
+    pub fn datasets_at(&self, timestep: f64) -> impl Iterator<Item = &DataSet> {
+        self.datasets
+            .iter()
+            .filter(move |ds| ds.timestep == timestep)
+    }
+
+    /// Add the given data set, replacing any existing data set with the same time step and part.
+    pub fn insert(&mut self, dataset: DataSet) {
+        if let Some(existing) = self
+            .datasets
+            .iter_mut()
+            .find(|ds| ds.timestep == dataset.timestep && ds.part == dataset.part)
+        {
+            *existing = dataset;
+        } else {
+            self.datasets.push(dataset);
+            self.datasets.sort_by(|a, b| {
+                a.timestep
+                    .partial_cmp(&b.timestep)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+                    .then(a.part.cmp(&b.part))
+            });
+        }
+    }
+}
+
+// Resolve the given file path relative to the directory of the file that references it.
+fn resolve_relative(referrer: &Path, file: &str) -> PathBuf {
+    let file = Path::new(file);
+    if file.is_absolute() {
+        file.to_path_buf()
+    } else {
+        referrer
+            .parent()
+            .map(|dir| dir.join(file))
+            .unwrap_or_else(|| file.to_path_buf())
+    }
+}
+
+/// Resolve the path of a data set file relative to the `.pvd` file that references it.
+pub fn resolve_dataset_path(pvd_path: &Path, dataset: &DataSet) -> PathBuf {
+    resolve_relative(pvd_path, &dataset.file)
+}
+
+fn open_file(path: &Path) -> Result<BufReader<File>, Error> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|e| Error(format!("Failed to read {:?}: {}", path, e)))
+}
+
+// Paths of the piece files referenced by a parallel data set.
+fn piece_sources(vtk: &Vtk) -> Result<Vec<String>, Error> {
+    fn sources<P>(pieces: &[Piece<P>]) -> Result<Vec<String>, Error> {
+        pieces
+            .iter()
+            .map(|piece| match piece {
+                Piece::Source(source, _) => Ok(source.clone()),
+                _ => Err(Error(String::from(
+                    "Parallel data sets must reference their pieces by file",
+                ))),
+            })
+            .collect()
+    }
+    match &vtk.data {
+        VtkDataSet::UnstructuredGrid { pieces, .. } => sources(pieces),
+        VtkDataSet::PolyData { pieces, .. } => sources(pieces),
+        _ => Err(Error(String::from("Unsupported parallel data set"))),
+    }
+}
+
+// Load the meshes stored in a single serial VTK file, dispatching on the file extension.
+fn load_piece_meshes(path: &Path, warnings: &mut Vec<String>) -> Result<Vec<hdkrs::Mesh>, Error> {
+    let extension = path.extension().and_then(|ext| ext.to_str());
+    if !matches!(
+        extension,
+        Some("vtu") | Some("vtp") | Some("vtk") | Some("vti") | Some("vtr") | Some("vts")
+    ) {
+        return Err(Error(format!("Unsupported data set file: {:?}", path)));
+    }
+    let reader = open_file(path)?;
+    let meshes = match extension {
+        Some("vtu") => crate::parse_vtu_mesh_from_reader(reader).map(|mesh| vec![mesh]),
+        Some("vtp") => crate::parse_vtp_meshes_from_reader(reader, warnings),
+        Some("vtk") => crate::parse_vtk_meshes_from_reader(reader, warnings),
+        _ => crate::load_vtk_grid_from_reader(reader, warnings).map(|grid| vec![grid.into()]),
+    }
+    .map_err(|e| Error(format!("Failed to load {:?}: {}", path, e.0)))?;
+
+    let meshes: Vec<_> = meshes
+        .into_iter()
+        .filter(|mesh| !matches!(mesh, hdkrs::Mesh::None))
+        .collect();
+    if meshes.is_empty() {
+        return Err(Error(format!("No mesh found in {:?}", path)));
+    }
+    Ok(meshes)
+}
+
+/// Load the meshes stored in a single data set file, dispatching on the file extension.
+///
+/// Poly data files may produce both a polygon mesh and a polyline mesh. Parallel files
+/// (`.pvtu` and `.pvtp`) produce the meshes of all their pieces, which are loaded from the
+/// files they reference. Attributes that could not be loaded are reported in `warnings`.
+pub fn load_dataset_meshes(
+    path: &Path,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<hdkrs::Mesh>, Error> {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("pvtu") | Some("pvtp") => {
+            let reader =
+                hdkrs::stream::decompress(open_file(path)?).map_err(|e| Error(e.to_string()))?;
+            let vtk = Vtk::parse_xml(reader)
+                .map_err(|e| Error(format!("Failed to load {:?}: {}", path, e)))?;
+            let mut meshes = Vec::new();
+            for source in piece_sources(&vtk)? {
+                meshes.extend(load_piece_meshes(
+                    &resolve_relative(path, &source),
+                    warnings,
+                )?);
+            }
+            Ok(meshes)
+        }
+        _ => load_piece_meshes(path, warnings),
+    }
+}
+
+/// Load all parts of the data set at the given time step.
+///
+/// Attributes that could not be loaded are reported in `warnings`.
+pub fn load_timestep(
+    pvd_path: &Path,
+    collection: &Collection,
+    timestep: f64,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<hdkrs::Mesh>, Error> {
+    let mut meshes = Vec::new();
+    for ds in collection.datasets_at(timestep) {
+        meshes.extend(load_dataset_meshes(
+            &resolve_dataset_path(pvd_path, ds),
+            warnings,
+        )?);
+    }
+    Ok(meshes)
+}
+
+fn lerp_positions<M, T>(a: &mut M, b: &M, t: T) -> bool
+where
+    M: VertexPositions<Element = [T; 3]>,
+    T: meshx::Real,
+{
+    if a.vertex_positions().len() != b.vertex_positions().len() {
+        return false;
+    }
+    for (pa, pb) in a
+        .vertex_positions_mut()
+        .iter_mut()
+        .zip(b.vertex_positions().iter())
+    {
+        for i in 0..3 {
+            pa[i] = pa[i] + (pb[i] - pa[i]) * t;
+        }
+    }
+    true
+}
+
+fn lerp_grid(a: &mut hdkrs::GridMesh, b: &hdkrs::GridMesh, t: f64) -> bool {
+    let same_fields = a.fields.len() == b.fields.len()
+        && a.fields.iter().zip(b.fields.iter()).all(|(fa, fb)| {
+            fa.name == fb.name && fa.cell == fb.cell && fa.data.len() == fb.data.len()
+        });
+    if a.dims != b.dims || !same_fields {
+        return false;
+    }
+    for i in 0..3 {
+        a.origin[i] += (b.origin[i] - a.origin[i]) * t;
+        a.spacing[i] += (b.spacing[i] - a.spacing[i]) * t;
+    }
+    for (fa, fb) in a.fields.iter_mut().zip(b.fields.iter()) {
+        for (va, vb) in fa.data.iter_mut().zip(fb.data.iter()) {
+            *va += ((f64::from(*vb) - f64::from(*va)) * t) as f32;
+        }
+    }
+    true
+}
+
+/// Linearly interpolate mesh `a` towards mesh `b` by the factor `t`.
+///
+/// Point positions of meshes are interpolated, taking attributes from `a`. Grids interpolate
+/// their placement and field values. If the two meshes differ in type, in the number of points
+/// or in the layout of their fields, they cannot be paired up, so `a` is left unchanged and an
+/// error is returned.
+pub fn lerp_mesh(a: &mut hdkrs::Mesh, b: &hdkrs::Mesh, t: f64) -> Result<(), Error> {
+    use hdkrs::Mesh;
+    let interpolated = match (a, b) {
+        (Mesh::TetMesh(a), Mesh::TetMesh(b)) => lerp_positions(&mut a.0, &b.0, t),
+        (Mesh::PolyMesh(a), Mesh::PolyMesh(b)) => lerp_positions(&mut a.0, &b.0, t),
+        (Mesh::PolylineMesh(a), Mesh::PolylineMesh(b)) => lerp_positions(&mut a.0, &b.0, t),
+        (Mesh::PointCloud(a), Mesh::PointCloud(b)) => lerp_positions(&mut a.0, &b.0, t),
+        (Mesh::UnstructuredMesh(a), Mesh::UnstructuredMesh(b)) => lerp_positions(&mut a.0, &b.0, t),
+        (Mesh::TetMeshF32(a), Mesh::TetMeshF32(b)) => lerp_positions(&mut a.0, &b.0, t as f32),
+        (Mesh::PolyMeshF32(a), Mesh::PolyMeshF32(b)) => lerp_positions(&mut a.0, &b.0, t as f32),
+        (Mesh::PolylineMeshF32(a), Mesh::PolylineMeshF32(b)) => {
+            lerp_positions(&mut a.0, &b.0, t as f32)
+        }
+        (Mesh::PointCloudF32(a), Mesh::PointCloudF32(b)) => {
+            lerp_positions(&mut a.0, &b.0, t as f32)
+        }
+        (Mesh::UnstructuredMeshF32(a), Mesh::UnstructuredMeshF32(b)) => {
+            lerp_positions(&mut a.0, &b.0, t as f32)
+        }
+        (Mesh::GridMesh(a), Mesh::GridMesh(b)) => lerp_grid(a, b, t),
+        _ => false,
+    };
+    if interpolated {
+        Ok(())
+    } else {
+        Err(Error(String::from(
+            "Cannot interpolate between time steps with different topology",
+        )))
+    }
+}
+
+/// Load the meshes at the given time.
+///
+/// If `time` falls between two time steps of the collection, the point positions of
+/// corresponding parts are linearly interpolated. Times outside the range of the collection
+/// are clamped to the first or last time step. Parts that cannot be interpolated are loaded at
+/// the earlier time step with a message added to `warnings`, as are attributes that could not
+/// be loaded.
+pub fn load_time(
+    pvd_path: &Path,
+    collection: &Collection,
+    time: f64,
+    warnings: &mut Vec<String>,
+) -> Result<Vec<hdkrs::Mesh>, Error> {
+    let timesteps = collection.timesteps();
+    let (first, last) = match (timesteps.first(), timesteps.last()) {
+        (Some(&first), Some(&last)) => (first, last),
+        _ => return Err(Error(String::from("Collection has no data sets"))),
+    };
+
+    if time <= first {
+        return load_timestep(pvd_path, collection, first, warnings);
+    }
+    if time >= last {
+        return load_timestep(pvd_path, collection, last, warnings);
+    }
+
+    // The time is strictly within the range, so a following time step always exists.
+    let next = timesteps.iter().position(|&t| t > time).unwrap();
+    let (t0, t1) = (timesteps[next - 1], timesteps[next]);
+    let mut meshes = load_timestep(pvd_path, collection, t0, warnings)?;
+    if time == t0 {
+        return Ok(meshes);
+    }
+    let next_meshes = load_timestep(pvd_path, collection, t1, warnings)?;
+    if meshes.len() != next_meshes.len() {
+        warnings.push(format!(
+            "Time steps {} and {} have different numbers of parts, using time step {}",
+            t0, t1, t0
+        ));
+        return Ok(meshes);
+    }
+    let factor = (time - t0) / (t1 - t0);
+    for (i, (a, b)) in meshes.iter_mut().zip(next_meshes.iter()).enumerate() {
+        if let Err(err) = lerp_mesh(a, b, factor) {
+            warnings.push(format!("Part {} at time step {}: {}", i, t0, err.0));
+        }
+    }
+    Ok(meshes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dataset(timestep: f64, part: u32, file: &str) -> DataSet {
+        DataSet {
+            timestep,
+            group: String::new(),
+            part,
+            file: String::from(file),
+        }
+    }
+
+    #[test]
+    fn collection_round_trip() {
+        let text = r#"<?xml version="1.0"?>
+<VTKFile type="Collection" version="0.1">
+  <Collection>
+    <DataSet timestep="0.5" part="1" file="b &amp; c.vtu"/>
+    <DataSet group="" timestep='0' file="a.vtu"/>
+  </Collection>
+</VTKFile>"#;
+        let collection = Collection::parse(text).unwrap();
+        assert_eq!(
+            collection.datasets,
+            vec![dataset(0.5, 1, "b & c.vtu"), dataset(0.0, 0, "a.vtu")]
+        );
+        assert_eq!(Collection::parse(&collection.write()).unwrap(), collection);
+        assert!(Collection::parse("<VTKFile/>").is_err());
+    }
+
+    #[test]
+    fn insert_keeps_datasets_sorted() {
+        let mut collection = Collection::default();
+        collection.insert(dataset(1.0, 0, "b.vtu"));
+        collection.insert(dataset(0.0, 1, "a1.vtu"));
+        collection.insert(dataset(0.0, 0, "a0.vtu"));
+        // Replaces the existing data set at the same time step and part in place.
+        collection.insert(dataset(1.0, 0, "c.vtu"));
+        assert_eq!(
+            collection.datasets,
+            vec![
+                dataset(0.0, 0, "a0.vtu"),
+                dataset(0.0, 1, "a1.vtu"),
+                dataset(1.0, 0, "c.vtu"),
+            ]
+        );
+        assert_eq!(collection.timesteps(), vec![0.0, 1.0]);
+        assert_eq!(collection.datasets_at(0.0).count(), 2);
+    }
+
+    #[test]
+    fn lerp_mesh_positions() {
+        use meshx::mesh::PointCloud;
+        let mut a = hdkrs::Mesh::from(PointCloud::new(vec![[0.0, 0.0, 0.0], [1.0, 2.0, 3.0]]));
+        let b = hdkrs::Mesh::from(PointCloud::new(vec![[2.0, 0.0, 0.0], [1.0, 4.0, 3.0]]));
+        lerp_mesh(&mut a, &b, 0.25).unwrap();
+        assert_eq!(a.point_coords(), &[0.5, 0.0, 0.0, 1.0, 2.5, 3.0][..]);
+
+        // Meshes with a different number of points are left unchanged.
+        let c = hdkrs::Mesh::from(PointCloud::new(vec![[2.0, 0.0, 0.0]]));
+        assert!(lerp_mesh(&mut a, &c, 0.5).is_err());
+        assert_eq!(a.point_coords(), &[0.5, 0.0, 0.0, 1.0, 2.5, 3.0][..]);
+    }
+
+    // Write a legacy VTK file with a single triangle offset along x.
+    fn write_triangle(path: &Path, x: f64) {
+        let vtk = format!(
+            "# vtk DataFile Version 2.0\n\
+             triangle\n\
+             ASCII\n\
+             DATASET POLYDATA\n\
+             POINTS 3 double\n\
+             {x} 0 0 {x1} 0 0 {x} 1 0\n\
+             POLYGONS 1 4\n\
+             3 0 1 2\n",
+            x = x,
+            x1 = x + 1.0
+        );
+        std::fs::write(path, vtk).unwrap();
+    }
+
+    #[test]
+    fn load_time_clamps_and_interpolates() {
+        let dir = std::env::temp_dir().join(format!("vtkio_pvd_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let pvd_path = dir.join("frames.pvd");
+        write_triangle(&dir.join("frame_0.vtk"), 0.0);
+        write_triangle(&dir.join("frame_1.vtk"), 2.0);
+        let mut collection = Collection::default();
+        collection.insert(dataset(1.0, 0, "frame_0.vtk"));
+        collection.insert(dataset(2.0, 0, "frame_1.vtk"));
+
+        let x_at = |time: f64| {
+            let mut warnings = Vec::new();
+            let meshes = load_time(&pvd_path, &collection, time, &mut warnings).unwrap();
+            assert!(warnings.is_empty());
+            assert_eq!(meshes.len(), 1);
+            meshes[0].point_coords()[0]
+        };
+        assert_eq!(x_at(0.0), 0.0);
+        assert_eq!(x_at(1.5), 1.0);
+        assert_eq!(x_at(5.0), 2.0);
+
+        // Missing files are reported instead of producing empty meshes.
+        collection.insert(dataset(3.0, 0, "missing.vtk"));
+        assert!(load_time(&pvd_path, &collection, 3.0, &mut Vec::new()).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}